@@ -0,0 +1,615 @@
+// cli.rs
+
+use crate::config;
+use crate::doctor;
+use crate::logging;
+use crate::rpc::{self, HttpRpc};
+use crate::secure_storage;
+use crate::transaction_handler::{self, BatchTransaction};
+use crate::tui;
+use crate::vanity_wallet::{self, Match, VanityConfig};
+use crate::wallet_manager;
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use solana_sdk::signer::Signer;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+/// Longest prefix `vanity-bench` estimates a search time for
+const BENCH_PREFIX_LENGTHS: usize = 6;
+
+/// Command-line interface for svmai. Every subcommand prints plain,
+/// tab-separated results to stdout; diagnostics go to stderr.
+#[derive(Parser, Debug)]
+#[command(name = "svmai", version, about = "Solana wallet management tool")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Launch the interactive terminal UI (default)
+    Tui,
+    /// Import a wallet from a Solana keypair JSON file
+    Add {
+        /// Name to store the wallet under (`_2`, `_3`, ... is appended if taken)
+        name: String,
+        /// Path to the keypair file, or `-` to read the key JSON from stdin
+        file: String,
+    },
+    /// Check that a keypair file can be imported and print its public key,
+    /// without storing anything
+    Check {
+        /// Path to the keypair file
+        file: String,
+    },
+    /// Import the keypairs in ~/.config/solana, each named after its file
+    ImportSolanaCli,
+    /// List stored wallets as `name<TAB>pubkey` lines
+    List,
+    /// Remove a stored wallet
+    Remove {
+        /// Name of the wallet to remove
+        name: String,
+    },
+    /// Grind a keypair whose address starts with PREFIX and store it
+    Vanity {
+        /// Address prefix to search for (case-insensitive)
+        prefix: String,
+        /// Also accept addresses starting with this prefix (repeatable)
+        #[arg(long = "or", value_name = "PREFIX")]
+        alternatives: Vec<String>,
+        /// Where in the address the prefix has to appear
+        #[arg(long = "match", value_enum, default_value_t = Match::Prefix)]
+        match_mode: Match,
+        /// Also require the address to end with this text (case-insensitive)
+        #[arg(long)]
+        suffix: Option<String>,
+        /// Give up after this many seconds
+        #[arg(long, default_value_t = 60)]
+        timeout: u64,
+        /// Name to store the wallet under (defaults to `vanity_<matched prefix>`)
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Measure this machine's vanity search speed and estimate how long
+    /// prefixes of common lengths would take
+    VanityBench {
+        /// How long to measure for
+        #[arg(long, default_value_t = 10)]
+        seconds: u64,
+        /// Worker threads to use (defaults to `vanity.max_threads` from the config)
+        #[arg(long)]
+        threads: Option<usize>,
+    },
+    /// Request devnet/testnet SOL for a stored wallet, printing `name<TAB>signature`
+    Airdrop {
+        /// Name of the wallet to fund
+        name: String,
+        /// Amount of SOL to request
+        amount: f64,
+        /// RPC endpoint to ask (defaults to `general.rpc_url` from the config)
+        #[arg(long)]
+        url: Option<String>,
+    },
+    /// Write the names and public addresses of all wallets to FILE as JSON,
+    /// without any key material
+    ExportAddresses {
+        /// Destination file for the address book
+        file: PathBuf,
+    },
+    /// Sign MESSAGE with a stored wallet and print the base58 signature
+    Sign {
+        /// Name of the wallet to sign with
+        name: String,
+        /// Message to sign, as UTF-8 text
+        message: String,
+    },
+    /// List keys stored under more than one name, printing `pubkey<TAB>name...`
+    /// with the name that is kept first
+    Dedupe {
+        /// Only list the duplicates (the default)
+        #[arg(long, conflicts_with = "apply")]
+        dry_run: bool,
+        /// Remove every name but the first of each duplicated key, after confirmation
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Simulate a batch transfer from a stored wallet and print the simulation log
+    Batch {
+        /// Name of the paying wallet
+        source: String,
+        /// Recipients as ADDRESS=AMOUNT
+        #[arg(required = true, value_parser = parse_recipient)]
+        recipients: Vec<(String, f64)>,
+        /// Mint of the SPL token to send instead of SOL
+        #[arg(long)]
+        mint: Option<String>,
+        /// Warn about program, mint or source recipients instead of refusing them
+        #[arg(long)]
+        allow_unsafe_recipients: bool,
+        /// Also write the log to this file
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// RPC endpoint to ask (defaults to `general.rpc_url` from the config)
+        #[arg(long)]
+        url: Option<String>,
+    },
+    /// Print the address of a stored wallet's associated token account for MINT
+    Ata {
+        /// Name of the wallet that owns the token account
+        wallet: String,
+        /// Mint address of the token
+        mint: String,
+    },
+    /// Check the keychain, config file and data directory, printing
+    /// `PASS|FAIL<TAB>check<TAB>detail` for each
+    Doctor {
+        /// Also check that the RPC endpoint answers
+        #[arg(long)]
+        rpc: bool,
+        /// RPC endpoint to check (defaults to `general.rpc_url` from the config)
+        #[arg(long, requires = "rpc")]
+        url: Option<String>,
+    },
+    /// Print a shell completion script to stdout
+    #[command(hide = true)]
+    Completions {
+        /// Shell to generate completions for
+        shell: Shell,
+    },
+}
+
+/// Executes the parsed command line.
+pub fn run(cli: Cli) -> io::Result<()> {
+    let config = match config::load_existing_config() {
+        Ok(config) => config,
+        // doctor reports a broken config itself, using the default profile
+        Err(_) if matches!(cli.command, Some(Command::Doctor { .. })) => config::Config::default(),
+        Err(e) => return Err(io::Error::other(format!("Failed to load config: {:#}", e))),
+    };
+    // The profile picks the vault file and keychain entry, so it must be set
+    // before anything touches them
+    secure_storage::set_profile(&config.general.profile).map_err(|e| io::Error::other(e.to_string()))?;
+    // The TUI owns the terminal, so its log lines only go to the log file. A
+    // logger installed earlier in the process is kept.
+    let in_tui = matches!(cli.command, None | Some(Command::Tui));
+    let _ = logging::init_logger_with(&config, !in_tui);
+
+    match cli.command.unwrap_or(Command::Tui) {
+        Command::Tui => {
+            eprintln!("Starting svmai tool in TUI mode...");
+            tui::run_tui()
+        }
+        Command::Add { name, file } => {
+            let name = if file == "-" {
+                wallet_manager::add_wallet_from_reader(&name, io::stdin().lock())?
+            } else {
+                wallet_manager::add_wallet_from_file(&name, &file)?
+            };
+            print_wallet_line(&name)
+        }
+        Command::Check { file } => {
+            println!("{}", wallet_manager::validate_wallet_file(&file)?);
+            Ok(())
+        }
+        Command::ImportSolanaCli => {
+            for name in wallet_manager::import_solana_cli_keypairs()? {
+                print_wallet_line(&name)?;
+            }
+            Ok(())
+        }
+        Command::List => {
+            let mut names = secure_storage::list_wallet_names()
+                .map_err(|e| io::Error::other(e.to_string()))?;
+            names.sort();
+            for name in names {
+                print_wallet_line(&name)?;
+            }
+            Ok(())
+        }
+        Command::Remove { name } => {
+            wallet_manager::remove_wallet(&name)?;
+            println!("{}", name);
+            Ok(())
+        }
+        Command::Vanity {
+            prefix,
+            alternatives,
+            match_mode,
+            suffix,
+            timeout,
+            name,
+        } => {
+            let prefixes: Vec<String> = std::iter::once(prefix).chain(alternatives).collect();
+            for prefix in &prefixes {
+                vanity_wallet::validate_prefix(prefix)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+            }
+            let mut config = VanityConfig {
+                prefix: prefixes[0].clone(),
+                prefixes,
+                match_mode,
+                suffix: suffix.unwrap_or_default(),
+                timeout_seconds: timeout,
+                ..VanityConfig::default()
+            };
+            // --match suffix already fixes the end of the address
+            config
+                .validate_suffix()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+            config.thread_count = configured_thread_count()?;
+            // Ctrl-C stops the search cleanly instead of killing the process mid-grind
+            let cancelled = Arc::new(AtomicBool::new(false));
+            let handler_flag = Arc::clone(&cancelled);
+            if let Err(e) = ctrlc::set_handler(move || vanity_wallet::cancel_vanity_generation(&handler_flag)) {
+                eprintln!("Warning: Ctrl-C will not cancel the search: {}", e);
+            }
+            if !config.suffix.is_empty() {
+                // Both ends have to match, so their odds multiply
+                eprintln!(
+                    "Searching for {}: ~{:.0} attempts for the prefix x ~{} for the suffix = ~{:.0} attempts",
+                    config.target_description(),
+                    config.prefix_attempts(),
+                    vanity_wallet::estimate_difficulty_combined("", &config.suffix, false),
+                    config.expected_attempts()
+                );
+            }
+            let found = vanity_wallet::generate_vanity_match(&config, &cancelled)
+                .map_err(|e| io::Error::other(e.to_string()))?;
+            let keypair = found.keypair;
+
+            // The search may have taken hours, so a taken name gets a suffix instead of failing
+            let name = name.unwrap_or_else(|| format!("vanity_{}", found.prefix));
+            let name = wallet_manager::add_wallet_under_unique_name(&name, &keypair.to_bytes())?;
+            println!("{}\t{}", name, keypair.pubkey());
+            Ok(())
+        }
+        Command::VanityBench { seconds, threads } => {
+            let threads = match threads {
+                Some(threads) => threads,
+                None => configured_thread_count()?,
+            };
+            let rate = vanity_wallet::benchmark_attempts_per_second(seconds, threads);
+            println!("attempts/sec\t{:.0}", rate);
+            // A typical prefix of each length, mixing letters that match in both cases
+            for length in 1..=BENCH_PREFIX_LENGTHS {
+                let prefix = "ab".repeat(length).chars().take(length).collect::<String>();
+                let attempts = vanity_wallet::estimated_attempts(&prefix);
+                let seconds = if rate > 0.0 { attempts / rate } else { f64::INFINITY };
+                println!(
+                    "{} chars\t{}",
+                    length,
+                    vanity_wallet::format_estimated_duration(seconds)
+                );
+            }
+            Ok(())
+        }
+        Command::Airdrop { name, amount, url } => {
+            let url = rpc_url_or_configured(url)?;
+            let signature = transaction_handler::request_airdrop(&name, amount, &url)?;
+            println!("{}\t{}", name, signature);
+            Ok(())
+        }
+        Command::ExportAddresses { file } => {
+            wallet_manager::export_address_book(&file)?;
+            println!("{}", file.display());
+            Ok(())
+        }
+        Command::Sign { name, message } => {
+            let signature = transaction_handler::sign_message(&name, message.as_bytes())?;
+            println!("{}", signature);
+            Ok(())
+        }
+        Command::Dedupe { dry_run: _, apply } => {
+            let duplicates = wallet_manager::find_duplicate_wallets()?;
+            for (pubkey, names) in &duplicates {
+                println!("{}\t{}", pubkey, names.join("\t"));
+            }
+            let redundant: Vec<&String> = duplicates.iter().flat_map(|(_, names)| &names[1..]).collect();
+            if !apply || redundant.is_empty() {
+                return Ok(());
+            }
+            if !confirm(&format!(
+                "Remove {} wallet(s), keeping the first name of each key?",
+                redundant.len()
+            ))? {
+                eprintln!("Nothing removed.");
+                return Ok(());
+            }
+            for name in redundant {
+                wallet_manager::remove_wallet(name)?;
+            }
+            Ok(())
+        }
+        Command::Batch {
+            source,
+            recipients,
+            mint,
+            allow_unsafe_recipients,
+            out,
+            url,
+        } => {
+            let batch = BatchTransaction {
+                source_wallet: source,
+                recipients,
+                token_mint: mint,
+                allow_unsafe_recipients,
+            };
+            let url = rpc_url_or_configured(url)?;
+            let log = transaction_handler::execute_batch_transaction(&batch, &HttpRpc::new(url))?;
+            print!("{}", log);
+            if let Some(out) = out {
+                transaction_handler::write_simulation_log(&out, &log)?;
+                eprintln!("Log written to {}", out.display());
+            }
+            Ok(())
+        }
+        Command::Ata { wallet, mint } => {
+            let ata = transaction_handler::wallet_ata(&wallet, &mint)?;
+            println!("{}", ata);
+            Ok(())
+        }
+        Command::Doctor { rpc, url } => run_doctor(rpc, url),
+        Command::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "svmai", &mut io::stdout());
+            Ok(())
+        }
+    }
+}
+
+// The given RPC URL, or `general.rpc_url` from the config
+fn rpc_url_or_configured(url: Option<String>) -> io::Result<String> {
+    match url {
+        Some(url) => Ok(url),
+        None => Ok(config::load_config()
+            .map_err(|e| io::Error::other(format!("{:#}", e)))?
+            .general
+            .rpc_url),
+    }
+}
+
+// Runs the setup checks and prints their report, failing when any check does
+fn run_doctor(check_rpc: bool, url: Option<String>) -> io::Result<()> {
+    let data_dir = secure_storage::get_config_path()
+        .map_err(io::Error::from)?
+        .parent()
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    let mut results = vec![
+        doctor::check_keychain(secure_storage::active_master_key_store()),
+        doctor::check_config_file(&config::get_config_path()),
+        doctor::check_data_dir(&data_dir),
+    ];
+    if check_rpc {
+        // A broken config is reported above; check the default endpoint then
+        let url = url.unwrap_or_else(|| {
+            config::load_existing_config()
+                .map(|config| config.general.rpc_url)
+                .unwrap_or_else(|_| rpc::DEFAULT_RPC_URL.to_string())
+        });
+        results.push(doctor::check_rpc(&HttpRpc::new(url.clone()), &url));
+    }
+
+    print!("{}", doctor::format_report(&results));
+    if doctor::all_passed(&results) {
+        Ok(())
+    } else {
+        Err(io::Error::other("Some checks failed"))
+    }
+}
+
+// Vanity worker threads for the configured `vanity.max_threads`
+fn configured_thread_count() -> io::Result<usize> {
+    let config = config::load_existing_config().map_err(|e| io::Error::other(format!("{:#}", e)))?;
+    Ok(vanity_wallet::configured_thread_count(config.vanity.max_threads))
+}
+
+// Parses a batch recipient given as ADDRESS=AMOUNT
+fn parse_recipient(value: &str) -> Result<(String, f64), String> {
+    let (address, amount) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected ADDRESS=AMOUNT, got '{}'", value))?;
+    let amount = amount
+        .parse::<f64>()
+        .map_err(|_| format!("'{}' is not an amount", amount))?;
+    Ok((address.to_string(), amount))
+}
+
+// Asks a yes/no question on stderr and reads the answer from stdin; anything
+// but "y" or "yes" declines
+fn confirm(question: &str) -> io::Result<bool> {
+    eprint!("{} [y/N] ", question);
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+}
+
+// Prints a stored wallet as `name<TAB>pubkey`
+fn print_wallet_line(name: &str) -> io::Result<()> {
+    let pubkey = wallet_manager::get_wallet_pubkey(name)?
+        .map(|pubkey| pubkey.to_string())
+        .unwrap_or_default();
+    println!("{}\t{}", name, pubkey);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_initializes_the_logger() {
+        let (temp_dir, test_service_name, _env_guard) = secure_storage::test_env::setup_test_env();
+        run(Cli::try_parse_from(["svmai", "list"]).unwrap()).unwrap();
+        assert!(logging::is_initialized());
+        secure_storage::test_env::teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_vanity_refuses_a_suffix_on_a_suffix_search() {
+        let (temp_dir, test_service_name, _env_guard) = secure_storage::test_env::setup_test_env();
+        let cli = Cli::try_parse_from(["svmai", "vanity", "ab", "--match", "suffix", "--suffix", "cd"]).unwrap();
+        let err = run(cli).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("second suffix"), "{}", err);
+        secure_storage::test_env::teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_no_subcommand_defaults_to_tui() {
+        let cli = Cli::try_parse_from(["svmai"]).unwrap();
+        assert!(cli.command.is_none());
+    }
+
+    #[test]
+    fn test_parse_vanity_subcommand() {
+        let cli = Cli::try_parse_from(["svmai", "vanity", "ab", "--timeout", "5"]).unwrap();
+        match cli.command {
+            Some(Command::Vanity {
+                prefix,
+                alternatives,
+                match_mode,
+                suffix,
+                timeout,
+                name,
+            }) => {
+                assert_eq!(prefix, "ab");
+                assert!(alternatives.is_empty());
+                assert_eq!(match_mode, Match::Prefix);
+                assert_eq!(suffix, None);
+                assert_eq!(timeout, 5);
+                assert_eq!(name, None);
+            }
+            other => panic!("Unexpected command: {:?}", other),
+        }
+
+        let cli = Cli::try_parse_from(["svmai", "vanity", "ai", "--or", "sol", "--or", "gm", "--match", "contains"]).unwrap();
+        match cli.command {
+            Some(Command::Vanity { prefix, alternatives, match_mode, .. }) => {
+                assert_eq!(prefix, "ai");
+                assert_eq!(alternatives, vec!["sol".to_string(), "gm".to_string()]);
+                assert_eq!(match_mode, Match::Contains);
+            }
+            other => panic!("Unexpected command: {:?}", other),
+        }
+
+        let cli = Cli::try_parse_from(["svmai", "vanity", "ab", "--suffix", "cd"]).unwrap();
+        match cli.command {
+            Some(Command::Vanity { suffix, .. }) => assert_eq!(suffix.as_deref(), Some("cd")),
+            other => panic!("Unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_doctor_subcommand() {
+        let cli = Cli::try_parse_from(["svmai", "doctor"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Doctor { rpc: false, url: None })));
+
+        let cli = Cli::try_parse_from(["svmai", "doctor", "--rpc", "--url", "http://127.0.0.1:8899"]).unwrap();
+        match cli.command {
+            Some(Command::Doctor { rpc, url }) => {
+                assert!(rpc);
+                assert_eq!(url.as_deref(), Some("http://127.0.0.1:8899"));
+            }
+            other => panic!("Unexpected command: {:?}", other),
+        }
+
+        // An endpoint only makes sense when the RPC check runs
+        assert!(Cli::try_parse_from(["svmai", "doctor", "--url", "http://127.0.0.1:8899"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_airdrop_subcommand() {
+        let cli = Cli::try_parse_from(["svmai", "airdrop", "dev", "1.5", "--url", "http://127.0.0.1:8899"]).unwrap();
+        match cli.command {
+            Some(Command::Airdrop { name, amount, url }) => {
+                assert_eq!(name, "dev");
+                assert_eq!(amount, 1.5);
+                assert_eq!(url.as_deref(), Some("http://127.0.0.1:8899"));
+            }
+            other => panic!("Unexpected command: {:?}", other),
+        }
+        assert!(Cli::try_parse_from(["svmai", "airdrop", "dev", "lots"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_vanity_bench_subcommand() {
+        let cli = Cli::try_parse_from(["svmai", "vanity-bench", "--seconds", "3", "--threads", "2"]).unwrap();
+        match cli.command {
+            Some(Command::VanityBench { seconds, threads }) => {
+                assert_eq!(seconds, 3);
+                assert_eq!(threads, Some(2));
+            }
+            other => panic!("Unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_ata_subcommand() {
+        let cli = Cli::try_parse_from(["svmai", "ata", "main", "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"]).unwrap();
+        match cli.command {
+            Some(Command::Ata { wallet, mint }) => {
+                assert_eq!(wallet, "main");
+                assert_eq!(mint, "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+            }
+            other => panic!("Unexpected command: {:?}", other),
+        }
+        assert!(Cli::try_parse_from(["svmai", "ata", "main"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_dedupe_subcommand() {
+        let cli = Cli::try_parse_from(["svmai", "dedupe", "--apply"]).unwrap();
+        match cli.command {
+            Some(Command::Dedupe { dry_run, apply }) => {
+                assert!(!dry_run);
+                assert!(apply);
+            }
+            other => panic!("Unexpected command: {:?}", other),
+        }
+        assert!(Cli::try_parse_from(["svmai", "dedupe", "--dry-run", "--apply"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_batch_subcommand() {
+        let cli = Cli::try_parse_from([
+            "svmai",
+            "batch",
+            "payer",
+            "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin=1.5",
+            "F4YA4H7HeXLCvjLRKdh56FgE4cyHpPqLP1VCM6fEqEmX=0.25",
+            "--out",
+            "plan.log",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Command::Batch { source, recipients, mint, allow_unsafe_recipients, out, url }) => {
+                assert_eq!(source, "payer");
+                assert_eq!(
+                    recipients,
+                    vec![
+                        ("9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string(), 1.5),
+                        ("F4YA4H7HeXLCvjLRKdh56FgE4cyHpPqLP1VCM6fEqEmX".to_string(), 0.25),
+                    ]
+                );
+                assert_eq!(mint, None);
+                assert!(!allow_unsafe_recipients);
+                assert_eq!(out, Some(PathBuf::from("plan.log")));
+                assert_eq!(url, None);
+            }
+            other => panic!("Unexpected command: {:?}", other),
+        }
+        assert!(Cli::try_parse_from(["svmai", "batch", "payer"]).is_err());
+        assert!(Cli::try_parse_from(["svmai", "batch", "payer", "no_amount"]).is_err());
+        assert!(Cli::try_parse_from(["svmai", "batch", "payer", "addr=lots"]).is_err());
+    }
+
+    #[test]
+    fn test_add_requires_name_and_file() {
+        assert!(Cli::try_parse_from(["svmai", "add", "only_name"]).is_err());
+    }
+}
@@ -1,11 +1,13 @@
 // src/config.rs
 
+use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use anyhow::{Result, Context};
-use dirs;
+
+use crate::rpc;
 
 /// Configuration structure for the svmai CLI tool
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -24,6 +26,14 @@ pub struct Config {
     
     /// Logging settings
     pub logging: LoggingConfig,
+    
+    /// TUI color theme
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    
+    /// How balances are formatted in the TUI
+    #[serde(default)]
+    pub display: DisplayConfig,
 }
 
 /// General application settings
@@ -31,6 +41,46 @@ pub struct Config {
 pub struct GeneralConfig {
     /// Default mode to start in (tui, cli)
     pub default_mode: String,
+    
+    /// Seconds before a TUI status message is cleared (0 = never clear)
+    #[serde(default = "default_status_timeout_seconds")]
+    pub status_timeout_seconds: u64,
+    
+    /// Whether error messages stay visible until replaced
+    #[serde(default)]
+    pub persist_error_status: bool,
+    
+    /// Seconds between automatic wallet refreshes in the TUI (0 = disabled)
+    #[serde(default)]
+    pub auto_refresh_seconds: u64,
+    
+    /// Solana JSON-RPC endpoint used for balances and transaction history
+    #[serde(default = "default_rpc_url")]
+    pub rpc_url: String,
+    
+    /// Whether the TUI reloads the wallet list when another process changes the wallet file
+    #[serde(default = "default_watch_wallet_file")]
+    pub watch_wallet_file: bool,
+    
+    /// Minutes without key input before the TUI locks itself (0 = disabled)
+    #[serde(default)]
+    pub lock_timeout_minutes: u64,
+    
+    /// Profile with its own wallet file and keychain entry, e.g. "work" (empty = default)
+    #[serde(default)]
+    pub profile: String,
+}
+
+fn default_status_timeout_seconds() -> u64 {
+    5
+}
+
+fn default_rpc_url() -> String {
+    rpc::DEFAULT_RPC_URL.to_string()
+}
+
+fn default_watch_wallet_file() -> bool {
+    true
 }
 
 /// Search-related settings
@@ -57,6 +107,10 @@ pub struct WalletConfig {
     
     /// Data directory for wallet files
     pub data_dir: String,
+    
+    /// Whether deleting a wallet requires typing its name instead of a Yes/No
+    #[serde(default)]
+    pub strict_delete: bool,
 }
 
 /// Vanity wallet generation settings
@@ -89,6 +143,225 @@ pub struct LoggingConfig {
     
     /// Log file path
     pub log_file: String,
+    
+    /// Rotate the log file once it grows beyond this many bytes (0 = never)
+    #[serde(default = "default_log_max_size_bytes")]
+    pub max_size_bytes: u64,
+    
+    /// Number of rotated log files to keep
+    #[serde(default = "default_log_max_files")]
+    pub max_files: usize,
+    
+    /// Log file line format: "text" or "json"
+    #[serde(default = "default_log_format")]
+    pub format: String,
+    
+    /// Mask key-like byte arrays and long base58 strings in log messages
+    #[serde(default = "default_redact_secrets")]
+    pub redact_secrets: bool,
+}
+
+fn default_redact_secrets() -> bool {
+    true
+}
+
+fn default_log_format() -> String {
+    "text".to_string()
+}
+
+fn default_log_max_size_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_log_max_files() -> usize {
+    5
+}
+
+/// TUI color theme. Each role takes a color name ("cyan", "lightred"), an
+/// ANSI index ("208") or a hex value ("#ff8800").
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// Titles and headings
+    pub title: String,
+    
+    /// Selected items and focused input fields
+    pub selected: String,
+    
+    /// Success messages
+    pub success: String,
+    
+    /// Error messages and destructive prompts
+    pub error: String,
+    
+    /// Warning messages
+    pub warning: String,
+    
+    /// Informational messages
+    pub info: String,
+}
+
+/// Theme colors resolved from a `ThemeConfig`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub title: Color,
+    pub selected: Color,
+    pub success: Color,
+    pub error: Color,
+    pub warning: Color,
+    pub info: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            title: Color::Cyan,
+            selected: Color::Yellow,
+            success: Color::Green,
+            error: Color::Red,
+            warning: Color::Yellow,
+            info: Color::Blue,
+        }
+    }
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        ThemeConfig {
+            title: "cyan".to_string(),
+            selected: "yellow".to_string(),
+            success: "green".to_string(),
+            error: "red".to_string(),
+            warning: "yellow".to_string(),
+            info: "blue".to_string(),
+        }
+    }
+}
+
+impl ThemeConfig {
+    /// Parses every role, keeping the default color for names that don't parse
+    pub fn to_theme(&self) -> Theme {
+        let defaults = Theme::default();
+        Theme {
+            title: parse_color(&self.title, defaults.title),
+            selected: parse_color(&self.selected, defaults.selected),
+            success: parse_color(&self.success, defaults.success),
+            error: parse_color(&self.error, defaults.error),
+            warning: parse_color(&self.warning, defaults.warning),
+            info: parse_color(&self.info, defaults.info),
+        }
+    }
+}
+
+fn parse_color(value: &str, fallback: Color) -> Color {
+    Color::from_str(value.trim()).unwrap_or(fallback)
+}
+
+/// How balances are formatted in the TUI
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct DisplayConfig {
+    /// Decimal places shown for SOL balances
+    pub sol_decimals: usize,
+    
+    /// Decimal places shown for token balances
+    pub token_decimals: usize,
+    
+    /// Group the whole part of balances by thousands, e.g. "1,234,567.5"
+    pub thousands_separator: bool,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        DisplayConfig {
+            sol_decimals: 9,
+            token_decimals: 6,
+            thousands_separator: false,
+        }
+    }
+}
+
+impl DisplayConfig {
+    /// Formats a balance held in lamports as SOL with the configured precision
+    pub fn format_sol(&self, lamports: u64) -> String {
+        format_lamports_as_sol(lamports, self.sol_decimals, self.thousands_separator)
+    }
+    
+    /// Formats a balance as a whole number of lamports
+    pub fn format_lamports(&self, lamports: u64) -> String {
+        let digits = lamports.to_string();
+        if self.thousands_separator {
+            group_thousands(&digits)
+        } else {
+            digits
+        }
+    }
+    
+    /// Formats a token balance with the configured precision
+    pub fn format_token(&self, amount: f64) -> String {
+        format_amount(amount, self.token_decimals, self.thousands_separator)
+    }
+}
+
+/// Formats `amount` with `decimals` places, grouping the whole part by
+/// thousands with commas when `thousands_separator` is set
+pub fn format_amount(amount: f64, decimals: usize, thousands_separator: bool) -> String {
+    let formatted = format!("{:.*}", decimals, amount);
+    if !thousands_separator {
+        return formatted;
+    }
+    
+    let (sign, unsigned) = match formatted.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", formatted.as_str()),
+    };
+    let (whole, fraction) = match unsigned.find('.') {
+        Some(dot) => unsigned.split_at(dot),
+        None => (unsigned, ""),
+    };
+    // NaN and infinity have no digits to group
+    if !whole.bytes().all(|b| b.is_ascii_digit()) {
+        return formatted;
+    }
+    format!("{}{}{}", sign, group_thousands(whole), fraction)
+}
+
+// Decimal places of one lamport in SOL
+const SOL_DECIMALS: usize = 9;
+
+/// Formats a lamport amount as SOL with `decimals` places. Works on the
+/// integer amount throughout, so no float rounding creeps into the digits.
+pub fn format_lamports_as_sol(lamports: u64, decimals: usize, thousands_separator: bool) -> String {
+    // Lamports carry nine decimal places; round away any beyond `decimals`
+    let shown = decimals.min(SOL_DECIMALS);
+    let scale = 10u128.pow((SOL_DECIMALS - shown) as u32);
+    let units = (lamports as u128 + scale / 2) / scale;
+    let unit_scale = 10u128.pow(shown as u32);
+    
+    let whole = (units / unit_scale).to_string();
+    let whole = if thousands_separator { group_thousands(&whole) } else { whole };
+    if decimals == 0 {
+        return whole;
+    }
+    format!(
+        "{}.{:0width$}{}",
+        whole,
+        units % unit_scale,
+        "0".repeat(decimals - shown),
+        width = shown
+    )
+}
+
+// Inserts a comma between each group of three digits, counting from the right
+fn group_thousands(digits: &str) -> String {
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
 }
 
 impl Default for Config {
@@ -96,6 +369,13 @@ impl Default for Config {
         Config {
             general: GeneralConfig {
                 default_mode: "tui".to_string(),
+                status_timeout_seconds: default_status_timeout_seconds(),
+                persist_error_status: false,
+                auto_refresh_seconds: 0,
+                rpc_url: default_rpc_url(),
+                watch_wallet_file: default_watch_wallet_file(),
+                lock_timeout_minutes: 0,
+                profile: String::new(),
             },
             search: SearchConfig {
                 max_depth: 10,
@@ -106,6 +386,7 @@ impl Default for Config {
                 default_name_prefix: "wallet_".to_string(),
                 keychain_service_name: "svmai_cli_tool".to_string(),
                 data_dir: get_default_data_dir().to_string_lossy().to_string(),
+                strict_delete: false,
             },
             vanity: VanityConfig {
                 default_prefix: "ai".to_string(),
@@ -118,7 +399,13 @@ impl Default for Config {
                 level: "info".to_string(),
                 log_to_file: true,
                 log_file: get_default_log_file().to_string_lossy().to_string(),
+                max_size_bytes: default_log_max_size_bytes(),
+                max_files: default_log_max_files(),
+                format: default_log_format(),
+                redact_secrets: default_redact_secrets(),
             },
+            theme: ThemeConfig::default(),
+            display: DisplayConfig::default(),
         }
     }
 }
@@ -148,22 +435,98 @@ pub fn get_default_log_file() -> PathBuf {
     PathBuf::from("./svmai.log")
 }
 
-/// Load configuration from the default location or create if it doesn't exist
+/// Load configuration from the default location or create if it doesn't exist.
+///
+/// Settings are resolved with the precedence environment > file > default:
+/// fields missing from the file take their defaults, and the `SVMAI_*`
+/// variables handled by `apply_env_overrides` replace both.
 pub fn load_config() -> Result<Config> {
     let config_path = get_config_path();
     
     // If config file doesn't exist, create it with defaults
-    if !config_path.exists() {
-        return create_default_config().context("Failed to create default configuration");
+    let mut config = if !config_path.exists() {
+        create_default_config().context("Failed to create default configuration")?
+    } else {
+        // Read and parse the config file
+        let config_str = fs::read_to_string(&config_path)
+            .context(format!("Failed to read config file: {:?}", config_path))?;
+        
+        toml::from_str(&config_str)
+            .context("Failed to parse config file")?
+    };
+    
+    apply_env_overrides(&mut config)?;
+    Ok(config)
+}
+
+/// Overrides settings from the environment:
+///
+/// - `SVMAI_RPC_URL`: `general.rpc_url`, must be an http(s) URL
+/// - `SVMAI_VANITY_TIMEOUT`: `vanity.timeout_seconds`, a positive integer
+/// - `SVMAI_LOG_LEVEL`: `logging.level`, one of trace, debug, info, warn, error
+/// - `SVMAI_PROFILE`: `general.profile`, letters, digits, `-` and `_`
+///
+/// Unset variables leave the setting alone; invalid values are an error
+/// rather than being silently ignored.
+pub fn apply_env_overrides(config: &mut Config) -> Result<()> {
+    apply_overrides(config, |name| std::env::var(name).ok())
+}
+
+fn apply_overrides(config: &mut Config, lookup: impl Fn(&str) -> Option<String>) -> Result<()> {
+    if let Some(url) = lookup("SVMAI_RPC_URL") {
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            anyhow::bail!("SVMAI_RPC_URL must be an http:// or https:// URL, got '{}'", url);
+        }
+        config.general.rpc_url = url;
     }
     
-    // Read and parse the config file
-    let config_str = fs::read_to_string(&config_path)
-        .context(format!("Failed to read config file: {:?}", config_path))?;
+    if let Some(timeout) = lookup("SVMAI_VANITY_TIMEOUT") {
+        config.vanity.timeout_seconds = timeout.trim().parse::<u64>().ok()
+            .filter(|&seconds| seconds > 0)
+            .with_context(|| format!("SVMAI_VANITY_TIMEOUT must be a positive number of seconds, got '{}'", timeout))?;
+    }
     
-    let config: Config = toml::from_str(&config_str)
-        .context("Failed to parse config file")?;
+    if let Some(level) = lookup("SVMAI_LOG_LEVEL") {
+        let level = level.trim().to_lowercase();
+        if !["trace", "debug", "info", "warn", "error"].contains(&level.as_str()) {
+            anyhow::bail!("SVMAI_LOG_LEVEL must be one of trace, debug, info, warn, error, got '{}'", level);
+        }
+        config.logging.level = level;
+    }
+    
+    if let Some(profile) = lookup("SVMAI_PROFILE") {
+        config.general.profile = profile.trim().to_string();
+    }
+    
+    if !crate::secure_storage::is_valid_profile_name(&config.general.profile) {
+        anyhow::bail!(
+            "profile must be at most 32 letters, digits, '-' or '_', got '{}'",
+            config.general.profile
+        );
+    }
     
+    Ok(())
+}
+
+/// The profile selected by the config file or `SVMAI_PROFILE`.
+///
+/// Unlike `load_config`, this never creates the config file, so it is
+/// cheap to call before every command.
+pub fn active_profile() -> Result<String> {
+    Ok(load_existing_config()?.general.profile)
+}
+
+/// Like `load_config`, but falls back to the defaults without creating a
+/// config file when there is none.
+pub fn load_existing_config() -> Result<Config> {
+    let config_path = get_config_path();
+    let mut config = if config_path.exists() {
+        load_config_from_file(&config_path)?
+    } else {
+        Config::default()
+    };
+    
+    apply_env_overrides(&mut config)?;
     Ok(config)
 }
 
@@ -191,7 +554,12 @@ pub fn create_default_config() -> Result<Config> {
 
 /// Save configuration to file
 pub fn save_config(config: &Config) -> Result<()> {
-    let config_path = get_config_path();
+    save_config_to_file(config, get_config_path())
+}
+
+/// Save configuration to a specific file
+pub fn save_config_to_file<P: AsRef<Path>>(config: &Config, path: P) -> Result<()> {
+    let config_path = path.as_ref();
     
     // Create parent directories if they don't exist
     if let Some(parent) = config_path.parent() {
@@ -204,12 +572,28 @@ pub fn save_config(config: &Config) -> Result<()> {
         .context("Failed to serialize config")?;
     
     // Write config to file
-    fs::write(&config_path, config_str)
+    fs::write(config_path, config_str)
         .context(format!("Failed to write config file: {:?}", config_path))?;
     
     Ok(())
 }
 
+/// Applies `update` to the configuration stored at `path` and writes it back.
+///
+/// The file is read without environment overrides, so values that only came
+/// from `SVMAI_*` variables are not written to disk. A missing file starts
+/// from the defaults.
+pub fn update_config_file<P: AsRef<Path>>(path: P, update: impl FnOnce(&mut Config)) -> Result<()> {
+    let path = path.as_ref();
+    let mut config = if path.exists() {
+        load_config_from_file(path)?
+    } else {
+        Config::default()
+    };
+    update(&mut config);
+    save_config_to_file(&config, path)
+}
+
 /// Load configuration from a specific file
 pub fn load_config_from_file<P: AsRef<Path>>(path: P) -> Result<Config> {
     let config_str = fs::read_to_string(path.as_ref())
@@ -243,6 +627,141 @@ mod tests {
         assert_eq!(config.vanity.default_prefix, deserialized.vanity.default_prefix);
     }
     
+    #[test]
+    fn test_missing_status_settings_use_defaults() {
+        let mut config: toml::Value = toml::Value::try_from(Config::default()).unwrap();
+        let general = config.get_mut("general").unwrap().as_table_mut().unwrap();
+        general.remove("status_timeout_seconds");
+        general.remove("persist_error_status");
+        
+        let parsed: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(parsed.general.status_timeout_seconds, 5);
+        assert!(!parsed.general.persist_error_status);
+    }
+    
+    #[test]
+    fn test_theme_parsing_falls_back_on_unknown_colors() {
+        let theme: ThemeConfig = toml::from_str(r##"
+            title = "magenta"
+            selected = "#ff8800"
+            error = "not-a-color"
+        "##).unwrap();
+        let resolved = theme.to_theme();
+        
+        assert_eq!(resolved.title, Color::Magenta);
+        assert_eq!(resolved.selected, Color::Rgb(0xff, 0x88, 0x00));
+        // Unknown names and omitted roles keep the default look
+        assert_eq!(resolved.error, Theme::default().error);
+        assert_eq!(resolved.info, Color::Blue);
+        
+        assert_eq!(ThemeConfig::default().to_theme(), Theme::default());
+    }
+    
+    #[test]
+    fn test_format_amount_across_magnitudes() {
+        assert_eq!(format_amount(0.0, 9, false), "0.000000000");
+        assert_eq!(format_amount(1.5, 2, false), "1.50");
+        assert_eq!(format_amount(1234567.891, 2, false), "1234567.89");
+        assert_eq!(format_amount(1234567.891, 2, true), "1,234,567.89");
+        assert_eq!(format_amount(999.0, 0, true), "999");
+        assert_eq!(format_amount(1000.0, 0, true), "1,000");
+        assert_eq!(format_amount(-12345.5, 1, true), "-12,345.5");
+        assert_eq!(format_amount(0.000123, 4, true), "0.0001");
+        assert_eq!(format_amount(f64::NAN, 2, true), "NaN");
+        
+        let display: DisplayConfig = toml::from_str("sol_decimals = 3\nthousands_separator = true").unwrap();
+        assert_eq!(display.format_sol(25_000_123_450_000), "25,000.123");
+        assert_eq!(display.format_lamports(25_000_123_450_000), "25,000,123,450,000");
+        // Omitted settings keep the defaults
+        assert_eq!(display.format_token(1.0), "1.000000");
+        assert_eq!(DisplayConfig::default().format_sol(2_000_000_000), "2.000000000");
+    }
+    
+    #[test]
+    fn test_format_lamports_as_sol_is_exact() {
+        assert_eq!(format_lamports_as_sol(1, 9, false), "0.000000001");
+        assert_eq!(format_lamports_as_sol(1_000_000_000, 9, false), "1.000000000");
+        // Float division would print 0.30000000000000004-style digits here
+        assert_eq!(format_lamports_as_sol(300_000_000, 9, false), "0.300000000");
+        // Dropped places are rounded half up on the integer amount
+        assert_eq!(format_lamports_as_sol(999_949_999, 4, false), "0.9999");
+        assert_eq!(format_lamports_as_sol(999_950_000, 4, false), "1.0000");
+        assert_eq!(format_lamports_as_sol(1_500_000_000, 0, false), "2");
+        assert_eq!(format_lamports_as_sol(1, 11, false), "0.00000000100");
+        assert_eq!(format_lamports_as_sol(u64::MAX, 9, true), "18,446,744,073.709551615");
+    }
+    
+    #[test]
+    fn test_env_overrides_take_precedence_over_file() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        
+        let mut file_config = Config::default();
+        file_config.vanity.timeout_seconds = 45;
+        file_config.logging.level = "warn".to_string();
+        fs::write(&config_path, toml::to_string_pretty(&file_config).unwrap()).unwrap();
+        
+        let _env_guard = crate::secure_storage::test_env::lock();
+        std::env::set_var("SVMAI_VANITY_TIMEOUT", "300");
+        let mut config = load_config_from_file(&config_path).unwrap();
+        let result = apply_env_overrides(&mut config);
+        std::env::remove_var("SVMAI_VANITY_TIMEOUT");
+        result.unwrap();
+        
+        assert_eq!(config.vanity.timeout_seconds, 300);
+        // Unset variables keep the file's values and the defaults
+        assert_eq!(config.logging.level, "warn");
+        assert_eq!(config.general.rpc_url, rpc::DEFAULT_RPC_URL);
+    }
+    
+    #[test]
+    fn test_update_config_file_keeps_other_settings() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        
+        let mut file_config = Config::default();
+        file_config.logging.level = "warn".to_string();
+        save_config_to_file(&file_config, &config_path).unwrap();
+        
+        update_config_file(&config_path, |config| config.vanity.default_prefix = "sol".to_string()).unwrap();
+        let config = load_config_from_file(&config_path).unwrap();
+        assert_eq!(config.vanity.default_prefix, "sol");
+        assert_eq!(config.logging.level, "warn");
+        
+        // A missing file is created from the defaults
+        let new_path = temp_dir.path().join("new").join("config.toml");
+        update_config_file(&new_path, |config| config.vanity.timeout_seconds = 30).unwrap();
+        assert_eq!(load_config_from_file(&new_path).unwrap().vanity.timeout_seconds, 30);
+    }
+    
+    #[test]
+    fn test_env_override_validation() {
+        let overrides = |vars: &[(&str, &str)]| {
+            let vars: Vec<(String, String)> = vars.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            let mut config = Config::default();
+            apply_overrides(&mut config, |name| {
+                vars.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+            }).map(|_| config)
+        };
+        
+        let config = overrides(&[
+            ("SVMAI_RPC_URL", "http://localhost:8899"),
+            ("SVMAI_LOG_LEVEL", "DEBUG"),
+        ]).unwrap();
+        assert_eq!(config.general.rpc_url, "http://localhost:8899");
+        assert_eq!(config.logging.level, "debug");
+        
+        assert!(overrides(&[("SVMAI_RPC_URL", "localhost:8899")]).is_err());
+        assert!(overrides(&[("SVMAI_VANITY_TIMEOUT", "0")]).is_err());
+        assert!(overrides(&[("SVMAI_VANITY_TIMEOUT", "soon")]).is_err());
+        assert!(overrides(&[("SVMAI_LOG_LEVEL", "verbose")]).is_err());
+        
+        assert_eq!(overrides(&[("SVMAI_PROFILE", " work ")]).unwrap().general.profile, "work");
+        assert!(overrides(&[("SVMAI_PROFILE", "../work")]).is_err());
+    }
+    
     #[test]
     fn test_save_load_config() {
         let temp_dir = tempdir().unwrap();
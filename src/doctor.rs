@@ -0,0 +1,234 @@
+// doctor.rs
+
+// Setup checks behind `svmai doctor`. Each check takes what it inspects as a
+// parameter, so tests can hand it a mock keychain or RPC node.
+
+use crate::config;
+use crate::rpc::SolanaRpc;
+use crate::secure_storage::{MasterKeySource, SecureStorageError};
+use std::fs;
+use std::path::Path;
+
+// Written and removed again to prove a directory is writable
+const PROBE_FILE_NAME: &str = ".svmai_doctor_probe";
+
+/// Outcome of one check: what was checked, whether it passed, and a short
+/// explanation either way
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        CheckResult {
+            name,
+            passed: true,
+            detail: detail.into(),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>) -> Self {
+        CheckResult {
+            name,
+            passed: false,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Reads the master key from the store the app would use, without changing
+/// it, and names that store. A store without a key yet passes: one is
+/// created on first use.
+pub fn check_keychain(source: Result<MasterKeySource, SecureStorageError>) -> CheckResult {
+    let source = match source {
+        Ok(source) => source,
+        Err(e) => return CheckResult::fail("keychain", e.to_string()),
+    };
+    match source.store.get() {
+        Ok(Some(_)) => CheckResult::pass("keychain", format!("master key found in the {}", source.description)),
+        Ok(None) => CheckResult::pass(
+            "keychain",
+            format!("{} is reachable; the master key is created on first use", source.description),
+        ),
+        Err(e) => CheckResult::fail("keychain", format!("{}: {}", source.description, e)),
+    }
+}
+
+/// Parses the config file at `path`. A missing file passes, since the
+/// defaults are used then.
+pub fn check_config_file(path: &Path) -> CheckResult {
+    if !path.exists() {
+        return CheckResult::pass(
+            "config",
+            format!("{} not found; using the defaults", path.display()),
+        );
+    }
+    match config::load_config_from_file(path) {
+        Ok(_) => CheckResult::pass("config", format!("{} is valid", path.display())),
+        Err(e) => CheckResult::fail("config", format!("{:#}", e)),
+    }
+}
+
+/// Creates `dir` if needed and writes, then removes, a small file in it
+pub fn check_data_dir(dir: &Path) -> CheckResult {
+    let probe = dir.join(PROBE_FILE_NAME);
+    let result = fs::create_dir_all(dir)
+        .and_then(|()| fs::write(&probe, b"ok"))
+        .and_then(|()| fs::remove_file(&probe));
+    match result {
+        Ok(()) => CheckResult::pass("data directory", format!("{} is writable", dir.display())),
+        Err(e) => CheckResult::fail(
+            "data directory",
+            format!("{} is not writable: {}", dir.display(), e),
+        ),
+    }
+}
+
+/// Asks the node at `url` whether it is healthy
+pub fn check_rpc(rpc: &dyn SolanaRpc, url: &str) -> CheckResult {
+    match rpc.get_health() {
+        Ok(()) => CheckResult::pass("rpc", format!("{} is reachable and healthy", url)),
+        Err(e) => CheckResult::fail("rpc", format!("{}: {}", url, e)),
+    }
+}
+
+/// Whether every check passed
+pub fn all_passed(results: &[CheckResult]) -> bool {
+    results.iter().all(|result| result.passed)
+}
+
+/// One `PASS|FAIL<TAB>check<TAB>detail` line per check, followed by a
+/// summary line
+pub fn format_report(results: &[CheckResult]) -> String {
+    let mut report = String::new();
+    for result in results {
+        report.push_str(&format!(
+            "{}\t{}\t{}\n",
+            if result.passed { "PASS" } else { "FAIL" },
+            result.name,
+            result.detail
+        ));
+    }
+    let failed = results.iter().filter(|result| !result.passed).count();
+    if failed == 0 {
+        report.push_str(&format!("All {} check(s) passed\n", results.len()));
+    } else {
+        report.push_str(&format!("{} of {} check(s) failed\n", failed, results.len()));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::mock::MockRpc;
+    use crate::rpc::RpcError;
+    use crate::secure_storage::{MasterKeyStore, MockStore};
+    use std::io;
+    use tempfile::tempdir;
+
+    // A keychain that cannot be reached, as on headless machines
+    struct UnavailableStore;
+
+    impl MasterKeyStore for UnavailableStore {
+        fn get(&self) -> Result<Option<Vec<u8>>, SecureStorageError> {
+            Err(SecureStorageError::KeychainAccess("no secret service".to_string()))
+        }
+
+        fn set(&self, _bytes: &[u8]) -> Result<(), SecureStorageError> {
+            Err(SecureStorageError::KeychainAccess("no secret service".to_string()))
+        }
+
+        fn delete(&self) -> Result<(), SecureStorageError> {
+            Err(SecureStorageError::KeychainAccess("no secret service".to_string()))
+        }
+    }
+
+    fn source(store: impl MasterKeyStore + 'static, description: &str) -> Result<MasterKeySource, SecureStorageError> {
+        Ok(MasterKeySource {
+            store: Box::new(store),
+            description: description.to_string(),
+            key_file: None,
+        })
+    }
+
+    #[test]
+    fn test_keychain_check() {
+        let result = check_keychain(source(MockStore::default(), "system keychain"));
+        assert!(result.passed);
+        assert!(result.detail.starts_with("system keychain is reachable"));
+
+        let store = MockStore::default();
+        store.set(&[7u8; 32]).unwrap();
+        let result = check_keychain(source(store, "SVMAI_PASSPHRASE key file /tmp/vault.key"));
+        assert_eq!(result.detail, "master key found in the SVMAI_PASSPHRASE key file /tmp/vault.key");
+
+        let result = check_keychain(source(UnavailableStore, "system keychain"));
+        assert!(!result.passed);
+        assert_eq!(result.detail, "system keychain: Keychain access error: no secret service");
+
+        // Not even knowing where the key would live fails too
+        let result = check_keychain(Err(SecureStorageError::IoError(io::Error::other("no home directory"))));
+        assert!(!result.passed);
+    }
+
+    #[test]
+    fn test_config_and_data_dir_checks() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("config.toml");
+        assert!(check_config_file(&config_path).passed);
+
+        fs::write(&config_path, "general = [").unwrap();
+        assert!(!check_config_file(&config_path).passed);
+
+        config::save_config_to_file(&config::Config::default(), &config_path).unwrap();
+        assert!(check_config_file(&config_path).passed);
+
+        // A missing directory is created, and the probe file cleaned up
+        let data_dir = dir.path().join("data");
+        assert!(check_data_dir(&data_dir).passed);
+        assert_eq!(fs::read_dir(&data_dir).unwrap().count(), 0);
+
+        // A regular file where the directory should be cannot be written into
+        assert!(!check_data_dir(&config_path).passed);
+    }
+
+    #[test]
+    fn test_rpc_check() {
+        assert!(check_rpc(&MockRpc::default(), "http://localhost:8899").passed);
+
+        let down = MockRpc {
+            health: Err(RpcError::Transport("connection refused".to_string())),
+            ..MockRpc::default()
+        };
+        let result = check_rpc(&down, "http://localhost:8899");
+        assert!(!result.passed);
+        assert!(result.detail.contains("connection refused"));
+    }
+
+    #[test]
+    fn test_report_passes_only_when_every_check_does() {
+        let passing = vec![
+            CheckResult::pass("keychain", "master key found"),
+            CheckResult::pass("config", "valid"),
+        ];
+        assert!(all_passed(&passing));
+        assert_eq!(
+            format_report(&passing),
+            "PASS\tkeychain\tmaster key found\nPASS\tconfig\tvalid\nAll 2 check(s) passed\n"
+        );
+
+        let mut mixed = passing.clone();
+        mixed.push(CheckResult::fail("rpc", "timed out"));
+        assert!(!all_passed(&mixed));
+        let report = format_report(&mixed);
+        assert!(report.contains("FAIL\trpc\ttimed out\n"));
+        assert!(report.ends_with("1 of 3 check(s) failed\n"));
+
+        // Nothing checked is nothing failed
+        assert!(all_passed(&[]));
+    }
+}
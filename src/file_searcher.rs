@@ -1,15 +1,26 @@
+use crate::key_validator;
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use rayon::prelude::*;
-use std::fs;
-use std::io::{self, Read};
+use std::io;
 use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::SystemTime;
 use walkdir::WalkDir;
 
+// Number of scanned files between progress callbacks
+const PROGRESS_INTERVAL_FILES: usize = 100;
+
 // Configuration for file search
+#[derive(Clone, Debug)]
 pub struct SearchConfig {
     pub max_files: Option<usize>, // Maximum number of files to find before early exit
     pub max_depth: Option<usize>, // Maximum directory depth to search
     pub batch_size: usize,        // Size of batches for parallel processing
+    pub follow_symlinks: bool,    // Whether to descend into symlinked directories
+    pub extensions: Vec<String>,  // File extensions to match, with or without the leading dot
+    pub ignore_patterns: Vec<String>, // Glob patterns, relative to the search root, to skip
 }
 
 impl Default for SearchConfig {
@@ -18,99 +29,269 @@ impl Default for SearchConfig {
             max_files: None,
             max_depth: None,
             batch_size: 100,
+            follow_symlinks: false,
+            extensions: vec!["json".to_string()],
+            ignore_patterns: Vec::new(),
         }
     }
 }
 
-// Helper function to check if a DirEntry is a JSON file
-fn is_json_file_entry(entry: &walkdir::DirEntry) -> bool {
-    entry
-        .file_name()
-        .to_str()
-        .map(|s| s.ends_with(".json"))
+// A found file with the details used to choose between candidates
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileInfo {
+    pub path: String,         // Path as found by the walk
+    pub size_bytes: u64,      // File size in bytes
+    pub modified: SystemTime, // Last modification time, for sorting by recency
+}
+
+// Helper function to check if a DirEntry has one of the configured extensions,
+// optionally followed by `.gz` (e.g. `id.json.gz`)
+fn is_json_file_entry(entry: &walkdir::DirEntry, extensions: &[String]) -> bool {
+    let path = entry.path();
+    let path = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("gz") => match path.file_stem() {
+            Some(stem) => Path::new(stem),
+            None => return false,
+        },
+        _ => path,
+    };
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| {
+            extensions
+                .iter()
+                .any(|wanted| wanted.trim_start_matches('.').eq_ignore_ascii_case(ext))
+        })
         .unwrap_or(false)
 }
 
+// Compiles the configured ignore patterns into a single matcher
+fn build_ignore_set(patterns: &[String]) -> io::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid ignore pattern {}: {}", pattern, e),
+            )
+        })?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))
+}
+
+// Helper function to check if a DirEntry matches an ignore pattern. Directories
+// are also tested as if they had a child, so `**/node_modules/**` prunes the
+// whole tree instead of visiting and rejecting every file inside it.
+fn is_ignored_entry(entry: &walkdir::DirEntry, root: &Path, ignore_set: &GlobSet) -> bool {
+    if ignore_set.is_empty() || entry.depth() == 0 {
+        return false;
+    }
+    let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+    ignore_set.is_match(relative) || (entry.file_type().is_dir() && ignore_set.is_match(relative.join("_")))
+}
+
+// Create a walkdir iterator honouring the symlink and max depth settings
+fn build_walker(path: &Path, config: &SearchConfig) -> WalkDir {
+    let mut walker = WalkDir::new(path).follow_links(config.follow_symlinks);
+    if let Some(max_depth) = config.max_depth {
+        walker = walker.max_depth(max_depth);
+    }
+    walker
+}
+
 // Optimized recursive parallel search function using WalkDir and Rayon
 pub fn search_json_files_parallel_recursive(dir_path: &str) -> io::Result<Vec<String>> {
     search_json_files_parallel_recursive_with_config(dir_path, &SearchConfig::default())
 }
 
-// New version with configuration options for better performance tuning
+// New version with configuration options for better performance tuning.
+// The directory walk runs on its own thread and streams entries through a
+// channel holding at most `batch_size` entries to the parallel workers, so
+// memory stays bounded however large the tree is.
 pub fn search_json_files_parallel_recursive_with_config(
     dir_path: &str,
     config: &SearchConfig,
 ) -> io::Result<Vec<String>> {
+    walk_json_files_parallel(dir_path, config, |entry| {
+        Some(entry.path().to_string_lossy().into_owned())
+    })
+}
+
+// Like search_json_files_parallel_recursive_with_config, but returns each file's
+// size and modification time along with its path. Files whose metadata cannot
+// be read are skipped.
+pub fn search_json_files_with_metadata(
+    dir_path: &str,
+    config: &SearchConfig,
+) -> io::Result<Vec<FileInfo>> {
+    walk_json_files_parallel(dir_path, config, |entry| {
+        let metadata = entry.metadata().ok()?;
+        Some(FileInfo {
+            path: entry.path().to_string_lossy().into_owned(),
+            size_bytes: metadata.len(),
+            modified: metadata.modified().ok()?,
+        })
+    })
+}
+
+// The parallel walk behind the searches above: `describe` turns each matching
+// entry into a result, and entries it returns None for are not counted.
+fn walk_json_files_parallel<T, F>(
+    dir_path: &str,
+    config: &SearchConfig,
+    describe: F,
+) -> io::Result<Vec<T>>
+where
+    T: Send,
+    F: Fn(&walkdir::DirEntry) -> Option<T> + Sync,
+{
     let path = Path::new(dir_path);
     if !path.is_dir() {
         return Ok(Vec::new()); // Or return an error: io::Error::new(io::ErrorKind::InvalidInput, "Path is not a directory")
     }
 
-    // Create a walkdir iterator with optional max depth
-    let mut walker = WalkDir::new(path);
-    if let Some(max_depth) = config.max_depth {
-        walker = walker.max_depth(max_depth);
+    let ignore_set = build_ignore_set(&config.ignore_patterns)?;
+    let max_files = config.max_files.unwrap_or(usize::MAX);
+    let result = Mutex::new(Vec::new());
+    let done = AtomicBool::new(max_files == 0); // Set once max_files results are in
+
+    let (sender, receiver) = mpsc::sync_channel(config.batch_size.max(1));
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            let walker = build_walker(path, config)
+                .into_iter()
+                .filter_entry(|entry| !is_ignored_entry(entry, path, &ignore_set))
+                .filter_map(Result::ok) // Filter out directory read errors
+                .filter(|entry| entry.file_type().is_file());
+            for entry in walker {
+                // Stop walking once enough files are found
+                if done.load(Ordering::Relaxed) || sender.send(entry).is_err() {
+                    break;
+                }
+            }
+            drop(sender);
+        });
+
+        receiver.into_iter().par_bridge().for_each(|entry| {
+            if done.load(Ordering::Relaxed) || !is_json_file_entry(&entry, &config.extensions) {
+                return;
+            }
+            let Some(found) = describe(&entry) else {
+                return;
+            };
+
+            let mut result = result.lock().unwrap();
+            if result.len() < max_files {
+                result.push(found);
+            }
+            if result.len() >= max_files {
+                done.store(true, Ordering::Relaxed);
+            }
+        });
+    });
+
+    Ok(result.into_inner().unwrap())
+}
+
+// Search that reports how many files have been scanned so far, for live counters.
+// The callback fires every PROGRESS_INTERVAL_FILES files and once more when done.
+// Setting `cancelled` stops the walk early and returns the files found so far.
+pub fn search_json_files_with_progress<F>(
+    dir_path: &str,
+    config: &SearchConfig,
+    cancelled: &AtomicBool,
+    on_progress: F,
+) -> io::Result<Vec<String>>
+where
+    F: Fn(usize) + Send + Sync,
+{
+    let path = Path::new(dir_path);
+    if !path.is_dir() {
+        on_progress(0);
+        return Ok(Vec::new());
     }
 
-    // If max_files is set, use early exit strategy with shared counter
-    if let Some(max_files) = config.max_files {
-        let result = Arc::new(Mutex::new(Vec::with_capacity(max_files)));
-        let count = Arc::new(Mutex::new(0));
-
-        walker
-            .into_iter()
-            .filter_map(Result::ok)
-            .filter(|entry| entry.file_type().is_file() && is_json_file_entry(entry))
-            .collect::<Vec<_>>() // Collect to avoid holding the iterator lock during parallel processing
-            .chunks(config.batch_size)
-            .for_each(|chunk| {
-                let result = Arc::clone(&result);
-                let count = Arc::clone(&count);
-
-                // Process each batch in parallel
-                chunk.par_iter().for_each(|entry| {
-                    // Check if we've already found enough files
-                    let current_count = {
-                        let count = count.lock().unwrap();
-                        *count
-                    };
-
-                    if current_count >= max_files {
-                        return;
-                    }
-
-                    let path_str = entry.path().to_string_lossy().into_owned();
-
-                    // Add to results and increment counter
-                    let mut result = result.lock().unwrap();
-                    let mut count = count.lock().unwrap();
-
-                    if *count < max_files {
-                        result.push(path_str);
-                        *count += 1;
-                    }
-                });
-            });
-
-        Ok(Arc::try_unwrap(result).unwrap().into_inner().unwrap())
-    } else {
-        // Original implementation for when no max_files limit is set
-        let json_files: Vec<String> = walker
-            .into_iter()
-            .filter_map(Result::ok) // Filter out directory read errors
-            .collect::<Vec<_>>() // Collect to avoid holding the iterator lock
-            .chunks(config.batch_size)
-            .flat_map(|chunk| {
-                chunk
-                    .par_iter()
-                    .filter(|entry| entry.file_type().is_file() && is_json_file_entry(entry))
-                    .map(|entry| entry.path().to_string_lossy().into_owned())
-                    .collect::<Vec<_>>()
-            })
-            .collect();
+    let ignore_set = build_ignore_set(&config.ignore_patterns)?;
+    let max_files = config.max_files.unwrap_or(usize::MAX);
+    let mut scanned = 0;
+    let mut result = Vec::new();
+
+    for entry in build_walker(path, config)
+        .into_iter()
+        .filter_entry(|entry| !is_ignored_entry(entry, path, &ignore_set))
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+    {
+        if cancelled.load(Ordering::Relaxed) {
+            break;
+        }
+        scanned += 1;
+        if scanned % PROGRESS_INTERVAL_FILES == 0 {
+            on_progress(scanned);
+        }
 
-        Ok(json_files)
+        if is_json_file_entry(&entry, &config.extensions) {
+            result.push(entry.path().to_string_lossy().into_owned());
+            if result.len() >= max_files {
+                break;
+            }
+        }
     }
+
+    on_progress(scanned);
+    Ok(result)
+}
+
+// Like search_json_files_parallel_recursive_with_config, but only returns files that
+// key_validator accepts as Solana keypairs. max_files counts validated files.
+pub fn search_solana_wallet_files(dir_path: &str, config: &SearchConfig) -> io::Result<Vec<String>> {
+    search_solana_wallet_files_with_progress(dir_path, config, &AtomicBool::new(false), |_| {})
+}
+
+// search_solana_wallet_files with the scanned-file counter and cancel flag of
+// search_json_files_with_progress. A cancelled search returns the keys validated so far.
+pub fn search_solana_wallet_files_with_progress<F>(
+    dir_path: &str,
+    config: &SearchConfig,
+    cancelled: &AtomicBool,
+    on_progress: F,
+) -> io::Result<Vec<String>>
+where
+    F: Fn(usize) + Send + Sync,
+{
+    let candidate_config = SearchConfig {
+        max_files: None,
+        ..config.clone()
+    };
+    let candidates = search_json_files_with_progress(dir_path, &candidate_config, cancelled, on_progress)?;
+    let max_files = config.max_files.unwrap_or(usize::MAX);
+    let result = Mutex::new(Vec::new());
+
+    for chunk in candidates.chunks(config.batch_size) {
+        // Validate each batch in parallel
+        chunk.par_iter().for_each(|candidate| {
+            if result.lock().unwrap().len() >= max_files {
+                return;
+            }
+
+            if let Ok(true) = key_validator::is_solana_wallet_json_file(candidate) {
+                let mut result = result.lock().unwrap();
+                if result.len() < max_files {
+                    result.push(candidate.clone());
+                }
+            }
+        });
+
+        // Stop reading files once enough keys have been found
+        if result.lock().unwrap().len() >= max_files || cancelled.load(Ordering::Relaxed) {
+            break;
+        }
+    }
+
+    Ok(result.into_inner().unwrap())
 }
 
 // This is a placeholder for Solana key validation logic
@@ -120,14 +301,10 @@ pub fn is_solana_wallet_json(file_path: &str) -> bool {
     // For this example, let's assume any .json file found is a potential candidate
     // and actual validation will happen in a later module.
     // We can simulate some basic check, e.g. if it contains "privateKey" or similar common field.
-    let mut file = match fs::File::open(file_path) {
-        Ok(f) => f,
+    let contents = match key_validator::read_key_file(file_path) {
+        Ok(contents) => contents,
         Err(_) => return false,
     };
-    let mut contents = String::new();
-    if file.read_to_string(&mut contents).is_err() {
-        return false;
-    }
     // A very simplistic check, actual validation would be more robust
     // Check if it's a JSON array and contains 64 numbers (typical for Solana secret keys)
     let is_likely_solana_key_format = contents.trim().starts_with("[")
@@ -144,7 +321,7 @@ pub fn is_solana_wallet_json(file_path: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::io::Write;
     use tempfile::tempdir;
 
@@ -198,6 +375,35 @@ mod tests {
         assert_eq!(actual_files, expected_files);
     }
 
+    #[test]
+    fn test_search_json_files_with_metadata() {
+        let dir = tempdir().unwrap();
+        let dir_path = dir.path();
+        fs::create_dir(dir_path.join("nested")).unwrap();
+        fs::write(dir_path.join("small.json"), "[]").unwrap();
+        fs::write(dir_path.join("nested").join("large.json"), vec![b' '; 2048]).unwrap();
+        fs::write(dir_path.join("notes.txt"), "not a key").unwrap();
+
+        let mut found =
+            search_json_files_with_metadata(dir_path.to_str().unwrap(), &SearchConfig::default()).unwrap();
+        found.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let expected: Vec<_> = [dir_path.join("nested").join("large.json"), dir_path.join("small.json")]
+            .iter()
+            .map(|path| {
+                let metadata = fs::metadata(path).unwrap();
+                FileInfo {
+                    path: path.to_string_lossy().into_owned(),
+                    size_bytes: metadata.len(),
+                    modified: metadata.modified().unwrap(),
+                }
+            })
+            .collect();
+        assert_eq!(found, expected);
+        assert_eq!(found[0].size_bytes, 2048);
+        assert_eq!(found[1].size_bytes, 2);
+    }
+
     #[test]
     fn test_search_with_max_files_limit() {
         let dir = tempdir().unwrap();
@@ -213,6 +419,7 @@ mod tests {
             max_files: Some(5),
             max_depth: None,
             batch_size: 2, // Small batch size to test batching
+            ..SearchConfig::default()
         };
 
         let result =
@@ -221,6 +428,45 @@ mod tests {
 
         assert_eq!(result.len(), 5);
     }
+    #[test]
+    fn test_search_streams_large_trees_completely() {
+        let dir = tempdir().unwrap();
+        let mut expected = Vec::new();
+        for dir_index in 0..20 {
+            let sub_dir = dir.path().join(format!("dir{}", dir_index));
+            std::fs::create_dir_all(&sub_dir).unwrap();
+            for file_index in 0..60 {
+                let json_path = sub_dir.join(format!("key{}.json", file_index));
+                File::create(&json_path).unwrap();
+                expected.push(json_path.to_string_lossy().into_owned());
+                File::create(sub_dir.join(format!("notes{}.txt", file_index))).unwrap();
+            }
+        }
+        expected.sort();
+
+        // A tiny channel forces the walker to wait on the workers many times
+        let config = SearchConfig {
+            batch_size: 4,
+            ..SearchConfig::default()
+        };
+        let mut result =
+            search_json_files_parallel_recursive_with_config(dir.path().to_str().unwrap(), &config)
+                .unwrap();
+        result.sort();
+        assert_eq!(result, expected);
+
+        let limited = SearchConfig {
+            max_files: Some(25),
+            batch_size: 4,
+            ..SearchConfig::default()
+        };
+        let result =
+            search_json_files_parallel_recursive_with_config(dir.path().to_str().unwrap(), &limited)
+                .unwrap();
+        assert_eq!(result.len(), 25);
+        assert!(result.iter().all(|path| expected.binary_search(path).is_ok()));
+    }
+
     #[test]
     fn test_search_with_max_depth() {
         let dir = tempdir().unwrap();
@@ -242,6 +488,7 @@ mod tests {
             max_files: None,
             max_depth: Some(1),
             batch_size: 100,
+            ..SearchConfig::default()
         };
 
         let result =
@@ -258,6 +505,209 @@ mod tests {
         }
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_search_follows_symlinks_only_when_enabled() {
+        let dir = tempdir().unwrap();
+        let target = tempdir().unwrap();
+        File::create(target.path().join("linked.json")).unwrap();
+        std::os::unix::fs::symlink(target.path(), dir.path().join("link")).unwrap();
+        let dir_path = dir.path().to_str().unwrap();
+
+        let result = search_json_files_parallel_recursive(dir_path).unwrap();
+        assert!(result.is_empty(), "Symlinks should not be followed by default");
+
+        let config = SearchConfig {
+            follow_symlinks: true,
+            ..SearchConfig::default()
+        };
+        let result = search_json_files_parallel_recursive_with_config(dir_path, &config).unwrap();
+        assert_eq!(result.len(), 1);
+        assert!(result[0].ends_with("linked.json"));
+    }
+
+    #[test]
+    fn test_search_with_custom_extensions() {
+        let dir = tempdir().unwrap();
+        let dir_path = dir.path();
+        File::create(dir_path.join("wallet.json")).unwrap();
+        File::create(dir_path.join("id.key")).unwrap();
+        File::create(dir_path.join("notes.txt")).unwrap();
+
+        let config = SearchConfig {
+            extensions: vec!["json".to_string(), ".key".to_string()],
+            ..SearchConfig::default()
+        };
+        let mut result =
+            search_json_files_parallel_recursive_with_config(dir_path.to_str().unwrap(), &config)
+                .unwrap();
+        result.sort();
+
+        let expected = vec![
+            dir_path.join("id.key").to_string_lossy().into_owned(),
+            dir_path.join("wallet.json").to_string_lossy().into_owned(),
+        ];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_search_skips_ignored_directories() {
+        let dir = tempdir().unwrap();
+        let dir_path = dir.path();
+        File::create(dir_path.join("keep.json")).unwrap();
+
+        let ignored_dir = dir_path.join("project").join("node_modules").join("pkg");
+        std::fs::create_dir_all(&ignored_dir).unwrap();
+        File::create(ignored_dir.join("package.json")).unwrap();
+        File::create(dir_path.join("project").join("sibling.json")).unwrap();
+
+        let config = SearchConfig {
+            ignore_patterns: vec!["**/node_modules/**".to_string()],
+            ..SearchConfig::default()
+        };
+        let mut result =
+            search_json_files_parallel_recursive_with_config(dir_path.to_str().unwrap(), &config)
+                .unwrap();
+        result.sort();
+
+        let expected = vec![
+            dir_path.join("keep.json").to_string_lossy().into_owned(),
+            dir_path.join("project").join("sibling.json").to_string_lossy().into_owned(),
+        ];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_search_rejects_invalid_ignore_pattern() {
+        let dir = tempdir().unwrap();
+        let config = SearchConfig {
+            ignore_patterns: vec!["[unclosed".to_string()],
+            ..SearchConfig::default()
+        };
+        let result =
+            search_json_files_parallel_recursive_with_config(dir.path().to_str().unwrap(), &config);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_search_with_progress_reports_scanned_files() {
+        let dir = tempdir().unwrap();
+        let dir_path = dir.path();
+        for i in 0..250 {
+            let extension = if i % 2 == 0 { "json" } else { "txt" };
+            File::create(dir_path.join(format!("file{}.{}", i, extension))).unwrap();
+        }
+
+        let reports = Mutex::new(Vec::new());
+        let result = search_json_files_with_progress(
+            dir_path.to_str().unwrap(),
+            &SearchConfig::default(),
+            &AtomicBool::new(false),
+            |scanned| reports.lock().unwrap().push(scanned),
+        )
+        .unwrap();
+
+        assert_eq!(result.len(), 125);
+        let reports = reports.into_inner().unwrap();
+        assert!(!reports.is_empty(), "Progress callback should be called");
+        assert!(reports.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(*reports.last().unwrap(), 250);
+    }
+
+    #[test]
+    fn test_cancelled_search_returns_partial_results() {
+        let dir = tempdir().unwrap();
+        let dir_path = dir.path();
+        for i in 0..250 {
+            File::create(dir_path.join(format!("file{}.json", i))).unwrap();
+        }
+
+        // Cancel from the first progress report, as the TUI's Esc would mid-scan
+        let cancelled = AtomicBool::new(false);
+        let result = search_json_files_with_progress(
+            dir_path.to_str().unwrap(),
+            &SearchConfig::default(),
+            &cancelled,
+            |_| cancelled.store(true, Ordering::Relaxed),
+        )
+        .unwrap();
+        assert_eq!(result.len(), PROGRESS_INTERVAL_FILES);
+        assert!(result.iter().all(|path| Path::new(path).is_file()));
+
+        // Already cancelled: nothing is scanned
+        let result = search_json_files_with_progress(
+            dir_path.to_str().unwrap(),
+            &SearchConfig::default(),
+            &cancelled,
+            |_| {},
+        )
+        .unwrap();
+        assert!(result.is_empty());
+    }
+
+    // Writes a freshly generated keypair in the Solana CLI format
+    fn write_key_file(path: &Path) {
+        let keypair = solana_sdk::signer::keypair::Keypair::new();
+        let bytes = keypair.to_bytes().to_vec();
+        std::fs::write(path, serde_json::to_string(&bytes).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_search_finds_gzip_compressed_key_files() {
+        let dir = tempdir().unwrap();
+        let dir_path = dir.path();
+        let key_bytes = solana_sdk::signer::keypair::Keypair::new().to_bytes().to_vec();
+        key_validator::test_fixtures::write_gzip(
+            &dir_path.join("backup.json.gz"),
+            serde_json::to_string(&key_bytes).unwrap().as_bytes(),
+        );
+        File::create(dir_path.join("logs.tar.gz")).unwrap();
+
+        let result =
+            search_solana_wallet_files(dir_path.to_str().unwrap(), &SearchConfig::default())
+                .unwrap();
+        assert_eq!(
+            result,
+            vec![dir_path.join("backup.json.gz").to_string_lossy().into_owned()]
+        );
+    }
+
+    #[test]
+    fn test_search_solana_wallet_files_skips_unrelated_json() {
+        let dir = tempdir().unwrap();
+        let dir_path = dir.path();
+        write_key_file(&dir_path.join("id.json"));
+        let sub_dir = dir_path.join("keys");
+        std::fs::create_dir_all(&sub_dir).unwrap();
+        write_key_file(&sub_dir.join("deployer.json"));
+        std::fs::write(dir_path.join("package.json"), "{\"name\": \"app\"}").unwrap();
+        std::fs::write(dir_path.join("numbers.json"), "[1, 2, 3]").unwrap();
+        // 32 bytes could be a bare secret key, but just as well any hash
+        std::fs::write(dir_path.join("hash.json"), format!("[{}]", vec!["7"; 32].join(","))).unwrap();
+
+        let mut result =
+            search_solana_wallet_files(dir_path.to_str().unwrap(), &SearchConfig::default())
+                .unwrap();
+        result.sort();
+
+        let mut expected = vec![
+            dir_path.join("id.json").to_string_lossy().into_owned(),
+            sub_dir.join("deployer.json").to_string_lossy().into_owned(),
+        ];
+        expected.sort();
+        assert_eq!(result, expected);
+
+        // max_files limits validated keys, not raw candidates
+        let config = SearchConfig {
+            max_files: Some(1),
+            batch_size: 1,
+            ..SearchConfig::default()
+        };
+        let limited = search_solana_wallet_files(dir_path.to_str().unwrap(), &config).unwrap();
+        assert_eq!(limited.len(), 1);
+        assert!(expected.contains(&limited[0]));
+    }
+
     #[test]
     fn test_is_solana_wallet_json_positive_case_array() {
         // Create a dummy file with some content that might resemble a Solana key file part
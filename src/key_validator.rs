@@ -1,62 +1,195 @@
+use flate2::read::GzDecoder;
 use serde_json::Value;
 use solana_sdk::signer::keypair::Keypair;
 use std::fs;
-use std::fs::File;
-use std::io;
+use std::io::{self, Read};
+use std::path::Path;
+use zeroize::Zeroizing;
 
 // Solana keypair constants
 const KEYPAIR_BYTES: usize = 64; // Full keypair: 32 bytes secret + 32 bytes public
 const SECRET_KEY_BYTES: usize = 32; // Just the secret key portion
 
+// Upper bound on decompressed or piped key data; real keys are a few hundred bytes
+const MAX_KEY_DATA_BYTES: u64 = 1024 * 1024;
+
+// Whether the path ends in `.gz`
+fn is_gzip_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
+
+/// Reads a key file as text, decompressing it first when it ends in `.gz`.
+pub fn read_key_file(file_path: &str) -> io::Result<String> {
+    if !is_gzip_path(Path::new(file_path)) {
+        return fs::read_to_string(file_path);
+    }
+
+    read_key_data(GzDecoder::new(fs::File::open(file_path)?))
+}
+
+/// Reads key text from any source, such as stdin, refusing anything larger
+/// than a key file could reasonably be.
+pub fn read_key_data(reader: impl Read) -> io::Result<String> {
+    let mut contents = String::new();
+    reader
+        .take(MAX_KEY_DATA_BYTES + 1)
+        .read_to_string(&mut contents)?;
+    if contents.len() as u64 > MAX_KEY_DATA_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Key data is too large",
+        ));
+    }
+    Ok(contents)
+}
+
+/// The file name of a key file without its `.json` (and `.gz`) extension,
+/// used as the default wallet name when importing it.
+pub fn key_file_stem(file_path: &str) -> Option<String> {
+    let path = Path::new(file_path);
+    let path = if is_gzip_path(path) {
+        Path::new(path.file_stem()?)
+    } else {
+        path
+    };
+    path.file_stem().map(|stem| stem.to_string_lossy().into_owned())
+}
+
 /// Validates if the content of a given JSON file represents a Solana private key.
 /// A Solana private key is typically represented as a JSON array of 64 u8 values.
+/// Files ending in `.gz` are decompressed first.
 pub fn is_solana_wallet_json_file(file_path: &str) -> io::Result<bool> {
-    let contents = fs::read_to_string(file_path)?;
+    let contents = Zeroizing::new(read_key_file(file_path)?);
+    Ok(is_solana_wallet_json(&contents))
+}
+
+/// Validates key text already read into memory, as `is_solana_wallet_json_file` does for a file.
+pub fn is_solana_wallet_json(contents: &str) -> bool {
+    is_key_byte_array(contents, KEYPAIR_BYTES)
+}
+
+/// Whether key text is a JSON array of 32 u8 values: a bare secret key whose
+/// public half can be derived. Only accepted where the user names the key to
+/// import, since any 32-byte hash looks the same; scans look for full keypairs.
+pub fn is_secret_key_json(contents: &str) -> bool {
+    is_key_byte_array(contents, SECRET_KEY_BYTES)
+}
 
+// Whether `contents` is a JSON array of exactly `len` u8 values
+fn is_key_byte_array(contents: &str, len: usize) -> bool {
     // Attempt to parse the string as JSON
-    let parsed_json: Result<Value, _> = serde_json::from_str(&contents);
+    let parsed_json: Result<Value, _> = serde_json::from_str(contents);
 
     match parsed_json {
         Ok(Value::Array(arr)) => {
-            // Check if the array contains 64 numbers (u8 values for a private key)
-            if arr.len() == KEYPAIR_BYTES {
-                let mut key_bytes: Vec<u8> = Vec::with_capacity(KEYPAIR_BYTES);
+            if arr.len() == len {
+                let mut key_bytes: Zeroizing<Vec<u8>> = Zeroizing::new(Vec::with_capacity(arr.len()));
                 for val in arr {
                     if let Value::Number(num) = val {
                         if let Some(byte_val) = num.as_u64() {
                             if byte_val <= u8::MAX as u64 {
                                 key_bytes.push(byte_val as u8);
                             } else {
-                                return Ok(false); // Number out of u8 range
+                                return false; // Number out of u8 range
                             }
                         } else {
-                            return Ok(false); // Not a valid u64 number
+                            return false; // Not a valid u64 number
                         }
                     } else {
-                        return Ok(false); // Element is not a number
+                        return false; // Element is not a number
                     }
                 }
-                // If we successfully collected 64 bytes, try to create a Keypair from it.
+                // If we successfully collected the bytes, try to create a Keypair from them.
                 // This is the definitive check for a valid Solana secret key.
                 // new_from_array expects only the 32-byte secret key
-                let mut secret_key = [0u8; SECRET_KEY_BYTES];
+                let mut secret_key = Zeroizing::new([0u8; SECRET_KEY_BYTES]);
                 secret_key.copy_from_slice(&key_bytes[0..SECRET_KEY_BYTES]);
-                let _keypair = Keypair::new_from_array(secret_key);
+                let _keypair = Keypair::new_from_array(*secret_key);
                 // Successfully created a keypair, this is a valid Solana secret key
-                Ok(true)
+                true
             } else {
-                Ok(false) // Array length is not 64
+                false // Array length does not match
             }
         }
-        Ok(_) => Ok(false), // JSON is valid, but not an array (which is expected for Solana keys)
-        Err(_) => Ok(false), // Failed to parse as JSON
+        Ok(_) => false, // JSON is valid, but not an array (which is expected for Solana keys)
+        Err(_) => false, // Failed to parse as JSON
+    }
+}
+
+/// Describes what a key file contains, for telling users why an import was
+/// refused, e.g. "array of 31 numbers — expected 64 or 32" or "not JSON".
+pub fn describe_key_file(file_path: &str) -> String {
+    // The file may well hold a real key, so its text is wiped once described
+    match read_key_file(file_path).map(Zeroizing::new) {
+        Ok(contents) => describe_key_json(&contents),
+        Err(e) => format!("could not be read: {}", e),
+    }
+}
+
+/// Describes key text already read into memory, as `describe_key_file` does for a file.
+pub fn describe_key_json(contents: &str) -> String {
+    if contents.trim().is_empty() {
+        return "empty file".to_string();
+    }
+
+    match serde_json::from_str::<Value>(contents) {
+        Ok(Value::Array(arr)) => describe_key_array(&arr),
+        Ok(Value::Object(map)) => match map.get("secretKey").or_else(|| map.get("privateKey")) {
+            Some(_) => "object with a secretKey field — expected a plain array of 64 numbers".to_string(),
+            None => "object without secretKey".to_string(),
+        },
+        Ok(Value::String(_)) => "JSON string — expected an array of 64 numbers".to_string(),
+        Ok(Value::Number(_)) => "JSON number — expected an array of 64 numbers".to_string(),
+        Ok(Value::Bool(_)) => "JSON boolean — expected an array of 64 numbers".to_string(),
+        Ok(Value::Null) => "JSON null — expected an array of 64 numbers".to_string(),
+        Err(_) => "not JSON".to_string(),
+    }
+}
+
+fn describe_key_array(arr: &[Value]) -> String {
+    if let Some(index) = arr.iter().position(|val| !val.is_number()) {
+        return format!("array with a non-number at position {}", index);
+    }
+    if let Some(index) = arr
+        .iter()
+        .position(|val| val.as_u64().is_none_or(|byte| byte > u8::MAX as u64))
+    {
+        return format!("array with a value outside 0-255 at position {}", index);
+    }
+    match arr.len() {
+        KEYPAIR_BYTES => format!("array of {} numbers (a Solana keypair)", KEYPAIR_BYTES),
+        SECRET_KEY_BYTES => format!(
+            "array of {} numbers (a secret key; the public half is derived from it)",
+            SECRET_KEY_BYTES
+        ),
+        len => format!(
+            "array of {} numbers — expected {} (keypair) or {} (secret key)",
+            len, KEYPAIR_BYTES, SECRET_KEY_BYTES
+        ),
+    }
+}
+
+// Fixtures shared by the tests of every module that reads key files
+#[cfg(test)]
+pub(crate) mod test_fixtures {
+    use std::io::Write;
+    use std::path::Path;
+
+    // Writes `contents` gzip-compressed to `path`, as a `.json.gz` backup would be
+    pub(crate) fn write_gzip(path: &Path, contents: &[u8]) {
+        let mut encoder = flate2::write::GzEncoder::new(std::fs::File::create(path).unwrap(), flate2::Compression::default());
+        encoder.write_all(contents).unwrap();
+        encoder.finish().unwrap();
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use solana_sdk::signer::Signer;
+    use super::test_fixtures::write_gzip;
+    use std::fs::File;
     use std::io::Write;
     use tempfile::tempdir;
 
@@ -145,9 +278,87 @@ mod tests {
         assert!(!is_solana_wallet_json_file(file_path.to_str().unwrap()).unwrap());
     }
 
+    #[test]
+    fn test_bare_secret_key_is_not_a_wallet_file() {
+        // 32 numbers look like any hash; only explicit imports accept them
+        let secret = number_array(32);
+        assert!(!is_solana_wallet_json(&secret));
+        assert!(is_secret_key_json(&secret));
+        assert!(!is_secret_key_json(&number_array(64)));
+        assert!(!is_secret_key_json("[1, 256]"));
+    }
+
     #[test]
     fn test_file_not_found() {
         let result = is_solana_wallet_json_file("non_existent_file.json");
         assert!(result.is_err());
     }
+
+    fn describe(contents: &str) -> String {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("key.json");
+        fs::write(&file_path, contents).unwrap();
+        describe_key_file(file_path.to_str().unwrap())
+    }
+
+    fn number_array(len: usize) -> String {
+        format!("[{}]", vec!["7"; len].join(","))
+    }
+
+    #[test]
+    fn test_describe_key_file() {
+        assert_eq!(describe(&number_array(64)), "array of 64 numbers (a Solana keypair)");
+        assert_eq!(
+            describe(&number_array(32)),
+            "array of 32 numbers (a secret key; the public half is derived from it)"
+        );
+        assert_eq!(describe(&number_array(5)), "array of 5 numbers — expected 64 (keypair) or 32 (secret key)");
+        assert_eq!(describe("[1, 2, \"x\"]"), "array with a non-number at position 2");
+        assert_eq!(describe("[1, 256]"), "array with a value outside 0-255 at position 1");
+        assert_eq!(describe("[1, -1]"), "array with a value outside 0-255 at position 1");
+        assert_eq!(describe("{\"key\": \"not an array\"}"), "object without secretKey");
+        assert_eq!(
+            describe("{\"secretKey\": [1, 2, 3]}"),
+            "object with a secretKey field — expected a plain array of 64 numbers"
+        );
+        assert_eq!(describe("\"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi\""), "JSON string — expected an array of 64 numbers");
+        assert_eq!(describe("42"), "JSON number — expected an array of 64 numbers");
+        assert_eq!(describe("This is not JSON content at all."), "not JSON");
+        assert_eq!(describe("  \n"), "empty file");
+        assert!(describe_key_file("non_existent_file.json").starts_with("could not be read: "));
+    }
+
+    #[test]
+    fn test_gzip_compressed_key_file() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("backup.json.gz");
+        let keypair = Keypair::new();
+        write_gzip(&file_path, serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap().as_bytes());
+        let file_path = file_path.to_str().unwrap();
+
+        assert!(is_solana_wallet_json_file(file_path).unwrap());
+        assert_eq!(describe_key_file(file_path), "array of 64 numbers (a Solana keypair)");
+    }
+
+    #[test]
+    fn test_gzip_file_with_invalid_contents() {
+        let dir = tempdir().unwrap();
+        let short = dir.path().join("short.json.gz");
+        write_gzip(&short, b"[1, 2, 3]");
+        assert!(!is_solana_wallet_json_file(short.to_str().unwrap()).unwrap());
+        assert_eq!(describe_key_file(short.to_str().unwrap()), "array of 3 numbers — expected 64 (keypair) or 32 (secret key)");
+
+        // A .gz name on a file that is not gzip is a read error, not a silent miss
+        let fake = dir.path().join("fake.json.gz");
+        fs::write(&fake, "[1, 2, 3]").unwrap();
+        assert!(is_solana_wallet_json_file(fake.to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_key_file_stem() {
+        assert_eq!(key_file_stem("/keys/id.json").as_deref(), Some("id"));
+        assert_eq!(key_file_stem("/keys/id.json.gz").as_deref(), Some("id"));
+        assert_eq!(key_file_stem("/keys/ID.JSON.GZ").as_deref(), Some("ID"));
+        assert_eq!(key_file_stem("deployer").as_deref(), Some("deployer"));
+    }
 }
@@ -0,0 +1,14 @@
+// src/lib.rs
+
+pub mod cli;
+pub mod config;
+pub mod doctor;
+pub mod file_searcher;
+pub mod key_validator;
+pub mod logging;
+pub mod rpc;
+pub mod secure_storage;
+pub mod transaction_handler;
+pub mod tui;
+pub mod vanity_wallet;
+pub mod wallet_manager;
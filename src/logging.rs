@@ -1,19 +1,141 @@
 // src/logging.rs
 
 use log::{LevelFilter, Record, Level, Metadata, SetLoggerError};
-use chrono::Local;
+use chrono::{Local, SecondsFormat};
 use std::fs::{File, OpenOptions};
-use std::io::Write;
-use std::path::Path;
-use std::sync::Mutex;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
 use std::fs;
-use anyhow::{Result, Context};
+use regex::Regex;
 use crate::config;
 
 /// Custom logger implementation
 struct SvmaiLogger {
     level: LevelFilter,
     file: Option<Mutex<File>>,
+    rotation: Option<RotationPolicy>,
+    /// Line format for the log file; stderr is always human-readable text
+    format: LogFormat,
+    /// Whether messages pass through `redact` before being written
+    redact_secrets: bool,
+    /// Whether messages are echoed to stderr; off while the TUI owns the terminal
+    to_stderr: bool,
+}
+
+// Set once a logger has been installed for the process
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+/// Whether `init_logger` or `init_logger_with` has installed the logger
+pub fn is_initialized() -> bool {
+    INITIALIZED.load(Ordering::SeqCst)
+}
+
+// Placeholder written in place of anything that looks like key material
+const REDACTED: &str = "[REDACTED]";
+
+// A JSON-style byte array of 32 or more numbers, as secret keys and keypairs are printed
+static BYTE_ARRAY_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[\s*\d{1,3}(?:\s*,\s*\d{1,3}){31,}\s*,?\s*\]").unwrap()
+});
+
+// A base58 run longer than any public key (at most 44 characters). Encoded
+// keypairs look like this, but so do transaction signatures.
+static BASE58_SECRET_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b[1-9A-HJ-NP-Za-km-z]{64,}\b").unwrap()
+});
+
+// Whether a base58 string encodes a 64-byte keypair. A signature has the same
+// length, but its second half is not the public key of its first.
+fn is_encoded_keypair(candidate: &str) -> bool {
+    use solana_sdk::signature::{Keypair, Signature};
+    use std::str::FromStr;
+    let Ok(decoded) = Signature::from_str(candidate) else {
+        return false;
+    };
+    let bytes = zeroize::Zeroizing::new(<[u8; 64]>::from(decoded));
+    Keypair::try_from(&bytes[..]).is_ok()
+}
+
+/// Masks anything in `msg` that looks like key material: byte arrays of 32 or
+/// more numbers and base58-encoded keypairs. Public keys, signatures and
+/// ordinary text are left untouched.
+pub fn redact(msg: &str) -> String {
+    let msg = BYTE_ARRAY_PATTERN.replace_all(msg, REDACTED);
+    BASE58_SECRET_PATTERN
+        .replace_all(&msg, |found: &regex::Captures| {
+            if is_encoded_keypair(&found[0]) {
+                REDACTED.to_string()
+            } else {
+                found[0].to_string()
+            }
+        })
+        .into_owned()
+}
+
+/// Log file line formats
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LogFormat {
+    /// `[timestamp] LEVEL [module:line] message`
+    Text,
+    /// One JSON object per line, for log aggregators
+    Json,
+}
+
+impl LogFormat {
+    /// Parses `logging.format`, falling back to text for unknown values
+    fn from_config(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "json" => LogFormat::Json,
+            "text" => LogFormat::Text,
+            other => {
+                eprintln!("Warning: Unknown log format '{}', using text", other);
+                LogFormat::Text
+            }
+        }
+    }
+}
+
+/// When and how the log file is rotated
+struct RotationPolicy {
+    /// Path of the active log file
+    path: PathBuf,
+    /// Rotate once the active file grows beyond this many bytes
+    max_size_bytes: u64,
+    /// Number of rotated files (`.1` to `.N`) to keep
+    max_files: usize,
+}
+
+impl RotationPolicy {
+    /// Returns the path of the `index`th rotated file, e.g. `svmai.log.1`
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        PathBuf::from(path)
+    }
+
+    /// Shifts `.1`..`.N-1` up by one, dropping the oldest, moves the active
+    /// file to `.1` and returns a fresh active file
+    fn rotate(&self) -> io::Result<File> {
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let oldest = self.rotated_path(self.max_files);
+            if oldest.exists() {
+                fs::remove_file(&oldest)?;
+            }
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        OpenOptions::new().create(true).append(true).open(&self.path)
+    }
 }
 
 impl log::Log for SvmaiLogger {
@@ -23,7 +145,12 @@ impl log::Log for SvmaiLogger {
 
     fn log(&self, record: &Record) {
         if self.enabled(record.metadata()) {
-            let now = Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
+            let now = Local::now();
+            let message = if self.redact_secrets {
+                redact(&record.args().to_string())
+            } else {
+                record.args().to_string()
+            };
             let level_str = match record.level() {
                 Level::Error => "ERROR",
                 Level::Warn => "WARN ",
@@ -34,20 +161,46 @@ impl log::Log for SvmaiLogger {
             
             let log_message = format!(
                 "[{}] {} [{}:{}] {}\n",
-                now,
+                now.format("%Y-%m-%d %H:%M:%S%.3f"),
                 level_str,
                 record.module_path().unwrap_or("unknown"),
                 record.line().unwrap_or(0),
-                record.args()
+                message
             );
             
             // Print to stderr
-            eprintln!("{}", log_message.trim());
+            if self.to_stderr {
+                eprintln!("{}", log_message.trim());
+            }
             
             // Write to file if configured
             if let Some(file) = &self.file {
+                let file_message = match self.format {
+                    LogFormat::Text => log_message,
+                    LogFormat::Json => {
+                        let entry = serde_json::json!({
+                            "timestamp": now.to_rfc3339_opts(SecondsFormat::Millis, false),
+                            "level": record.level().as_str(),
+                            "module": record.module_path().unwrap_or("unknown"),
+                            "line": record.line().unwrap_or(0),
+                            "message": message,
+                        });
+                        format!("{}\n", entry)
+                    }
+                };
+
                 if let Ok(mut file) = file.lock() {
-                    let _ = file.write_all(log_message.as_bytes());
+                    // Rotate under the lock so concurrent writers never race the rename
+                    if let Some(policy) = &self.rotation {
+                        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+                        if policy.max_size_bytes > 0 && size > policy.max_size_bytes {
+                            match policy.rotate() {
+                                Ok(fresh) => *file = fresh,
+                                Err(e) => eprintln!("Warning: Could not rotate log file: {}", e),
+                            }
+                        }
+                    }
+                    let _ = file.write_all(file_message.as_bytes());
                     let _ = file.flush();
                 }
             }
@@ -67,7 +220,12 @@ impl log::Log for SvmaiLogger {
 pub fn init_logger() -> Result<(), SetLoggerError> {
     // Load configuration
     let config = config::load_config().unwrap_or_else(|_| config::Config::default());
-    
+    init_logger_with(&config, true)
+}
+
+/// Initialize the logger from an already loaded configuration. With
+/// `to_stderr` off, messages only go to the log file.
+pub fn init_logger_with(config: &config::Config, to_stderr: bool) -> Result<(), SetLoggerError> {
     // Determine log level
     let level = match config.logging.level.to_lowercase().as_str() {
         "trace" => LevelFilter::Trace,
@@ -102,14 +260,25 @@ pub fn init_logger() -> Result<(), SetLoggerError> {
         None
     };
     
+    let rotation = file.as_ref().map(|_| RotationPolicy {
+        path: PathBuf::from(&config.logging.log_file),
+        max_size_bytes: config.logging.max_size_bytes,
+        max_files: config.logging.max_files,
+    });
+    
     // Create and set the logger
     let logger = Box::new(SvmaiLogger {
         level,
         file,
+        rotation,
+        format: LogFormat::from_config(&config.logging.format),
+        redact_secrets: config.logging.redact_secrets,
+        to_stderr,
     });
     
     log::set_boxed_logger(logger)?;
     log::set_max_level(level);
+    INITIALIZED.store(true, Ordering::SeqCst);
     
     Ok(())
 }
@@ -133,6 +302,7 @@ pub fn log_error<E: std::fmt::Display>(error: E, context: &str) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use log::Log;
     use tempfile::tempdir;
     use std::io::Read;
     
@@ -151,6 +321,10 @@ mod tests {
         let logger = SvmaiLogger {
             level: LevelFilter::Debug,
             file: Some(Mutex::new(file)),
+            rotation: None,
+            format: LogFormat::Text,
+            redact_secrets: true,
+            to_stderr: false,
         };
         
         // Log a test message
@@ -172,4 +346,143 @@ mod tests {
         assert!(contents.contains("Test message"));
         assert!(contents.contains("test_module:42"));
     }
+    
+    #[test]
+    fn test_log_rotation_by_size() {
+        let temp_dir = tempdir().unwrap();
+        let log_path = temp_dir.path().join("svmai.log");
+        
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)
+            .unwrap();
+        
+        let logger = SvmaiLogger {
+            level: LevelFilter::Debug,
+            file: Some(Mutex::new(file)),
+            rotation: Some(RotationPolicy {
+                path: log_path.clone(),
+                max_size_bytes: 200,
+                max_files: 2,
+            }),
+            format: LogFormat::Text,
+            redact_secrets: true,
+            to_stderr: false,
+        };
+        
+        // Each line is well over 50 bytes, so this rotates several times
+        for i in 0..20 {
+            logger.log(&log::Record::builder()
+                .args(format_args!("Rotation test message {}", i))
+                .level(Level::Info)
+                .module_path(Some("test_module"))
+                .line(Some(1))
+                .build());
+        }
+        
+        let rotated = temp_dir.path().join("svmai.log.1");
+        assert!(rotated.exists());
+        assert!(temp_dir.path().join("svmai.log.2").exists());
+        // Only max_files rotated files are kept
+        assert!(!temp_dir.path().join("svmai.log.3").exists());
+        
+        // The newest message is in the fresh active file, which stays small
+        let contents = fs::read_to_string(&log_path).unwrap();
+        assert!(contents.contains("Rotation test message 19"));
+        assert!(fs::metadata(&log_path).unwrap().len() <= 200 + 100);
+    }
+    
+    #[test]
+    fn test_json_log_format() {
+        let temp_dir = tempdir().unwrap();
+        let log_path = temp_dir.path().join("json.log");
+        
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)
+            .unwrap();
+        
+        let logger = SvmaiLogger {
+            level: LevelFilter::Debug,
+            file: Some(Mutex::new(file)),
+            rotation: None,
+            format: LogFormat::Json,
+            redact_secrets: true,
+            to_stderr: false,
+        };
+        
+        logger.log(&log::Record::builder()
+            .args(format_args!("Wallet \"main\" loaded"))
+            .level(Level::Warn)
+            .module_path(Some("svmai::tui"))
+            .line(Some(7))
+            .build());
+        
+        let contents = fs::read_to_string(&log_path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 1);
+        
+        let entry: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(entry["level"], "WARN");
+        assert_eq!(entry["module"], "svmai::tui");
+        assert_eq!(entry["line"], 7);
+        assert_eq!(entry["message"], "Wallet \"main\" loaded");
+        assert!(chrono::DateTime::parse_from_rfc3339(entry["timestamp"].as_str().unwrap()).is_ok());
+    }
+    
+    #[test]
+    fn test_log_format_from_config() {
+        assert_eq!(LogFormat::from_config("json"), LogFormat::Json);
+        assert_eq!(LogFormat::from_config("JSON"), LogFormat::Json);
+        assert_eq!(LogFormat::from_config("text"), LogFormat::Text);
+        assert_eq!(LogFormat::from_config("yaml"), LogFormat::Text);
+    }
+    
+    #[test]
+    fn test_redact_masks_key_arrays() {
+        let keypair_bytes: Vec<String> = (0..64).map(|i| (i * 3 % 256).to_string()).collect();
+        let line = format!("Decrypted key: [{}] for wallet main", keypair_bytes.join(", "));
+        
+        let redacted = redact(&line);
+        assert_eq!(redacted, "Decrypted key: [REDACTED] for wallet main");
+        
+        // Debug-formatted Vec<u8> output is caught too
+        let debug_line = format!("bytes={:?}", vec![7u8; 64]);
+        assert_eq!(redact(&debug_line), "bytes=[REDACTED]");
+    }
+    
+    #[test]
+    fn test_redact_masks_base58_secrets() {
+        let keypair = solana_sdk::signature::Keypair::new();
+        let line = format!("Imported {} ok", keypair.to_base58_string());
+        assert_eq!(redact(&line), "Imported [REDACTED] ok");
+    }
+    
+    #[test]
+    fn test_redact_leaves_transaction_signatures() {
+        use solana_sdk::signature::Signer;
+        // A signature is as long as an encoded keypair, but not a secret
+        let keypair = solana_sdk::signature::Keypair::new();
+        let signature = keypair.sign_message(b"transfer").to_string();
+        let line = format!("Sent transaction {}", signature);
+        assert_eq!(redact(&line), line);
+        
+        let both = format!("{} signed {}", keypair.to_base58_string(), signature);
+        assert_eq!(redact(&both), format!("[REDACTED] signed {}", signature));
+    }
+    
+    #[test]
+    fn test_redact_leaves_ordinary_text() {
+        let lines = [
+            "Nonce length: 12, ciphertext length: 96",
+            "Loaded 3 wallets: [1, 2, 3]",
+            "Wallet pubkey 5vftMkHL72JaJG6ExQfGAsT2uGVHpRR7oTNUPMs68Y2N stored",
+            "",
+        ];
+        for line in lines {
+            assert_eq!(redact(line), line);
+        }
+    }
 }
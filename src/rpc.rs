@@ -0,0 +1,671 @@
+// src/rpc.rs
+
+// Minimal blocking client for the Solana JSON-RPC API.
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde_json::{json, Value};
+use solana_message::Message;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction::Transaction;
+use std::time::Duration;
+
+/// RPC endpoint used when none is configured
+pub const DEFAULT_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+
+// Upper bound on a single RPC round trip, so callers never hang on a dead node
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Program that owns SPL token accounts
+const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+/// Error types for RPC requests
+#[derive(Debug, Clone, PartialEq)]
+pub enum RpcError {
+    /// The request could not be sent or the response could not be read
+    Transport(String),
+    /// The node answered with a JSON-RPC error object
+    Rpc { code: i64, message: String },
+    /// The response did not have the expected shape
+    InvalidResponse(String),
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RpcError::Transport(msg) => write!(f, "RPC request failed: {}", msg),
+            RpcError::Rpc { code, message } => write!(f, "RPC error {}: {}", code, message),
+            RpcError::InvalidResponse(msg) => write!(f, "Invalid RPC response: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+pub type Result<T> = std::result::Result<T, RpcError>;
+
+/// Summary of a transaction touching an address, as listed by `getSignaturesForAddress`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxSummary {
+    pub signature: String,
+    pub slot: u64,
+    /// Unix timestamp, when the node still knows it
+    pub block_time: Option<i64>,
+    /// Whether the transaction executed without error
+    pub success: bool,
+}
+
+/// Confirmation state of a submitted transaction
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignatureStatus {
+    /// Not yet seen, or seen but not confirmed
+    Pending,
+    /// Confirmed by a supermajority of the cluster without error
+    Confirmed,
+    /// Executed with the given error
+    Failed(String),
+}
+
+// Host name of `url`, lowercased, without scheme, port, path or query
+fn url_host(url: &str) -> String {
+    url.split("://")
+        .nth(1)
+        .unwrap_or(url)
+        .split(['/', ':', '?'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Whether `url` points at mainnet-beta, judged by its host name
+pub fn is_mainnet_url(url: &str) -> bool {
+    url_host(url).contains("mainnet")
+}
+
+/// Short name of the cluster `url` points at, judged by its host name:
+/// `mainnet-beta`, `devnet`, `testnet`, `localnet`, or else the host itself
+pub fn cluster_name(url: &str) -> String {
+    let host = url_host(url);
+    if host.contains("mainnet") {
+        "mainnet-beta".to_string()
+    } else if host.contains("devnet") {
+        "devnet".to_string()
+    } else if host.contains("testnet") {
+        "testnet".to_string()
+    } else if host == "localhost" || host == "127.0.0.1" || host == "0.0.0.0" {
+        "localnet".to_string()
+    } else {
+        host
+    }
+}
+
+/// SPL token account held by a wallet
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenAccount {
+    pub address: Pubkey,
+    pub mint: Pubkey,
+    /// Balance in the token's base units
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+impl TokenAccount {
+    /// Balance in whole tokens
+    pub fn ui_amount(&self) -> f64 {
+        self.amount as f64 / 10f64.powi(self.decimals as i32)
+    }
+}
+
+/// The parts of the Solana JSON-RPC API that svmai uses, so code that talks
+/// to the network can be tested against canned answers
+pub trait SolanaRpc {
+    /// Lamports held by `pubkey`
+    fn get_balance(&self, pubkey: &Pubkey) -> Result<u64>;
+    /// Token accounts of `mint` owned by `owner`
+    fn get_token_accounts(&self, owner: &Pubkey, mint: &Pubkey) -> Result<Vec<TokenAccount>>;
+    /// Every SPL token account owned by `owner`, whatever its mint
+    fn get_owned_token_accounts(&self, owner: &Pubkey) -> Result<Vec<TokenAccount>>;
+    /// The blockhash that new transactions should reference
+    fn get_latest_blockhash(&self) -> Result<Hash>;
+    /// Fee in lamports for `message`, or None once its blockhash has expired
+    fn get_fee_for_message(&self, message: &Message) -> Result<Option<u64>>;
+    /// The most recent transactions involving `pubkey`, newest first
+    fn get_recent_signatures(&self, pubkey: &Pubkey, limit: usize) -> Result<Vec<TxSummary>>;
+    /// Submits a signed transaction, returning its signature
+    fn send_transaction(&self, transaction: &Transaction) -> Result<Signature>;
+    /// Asks the cluster's faucet for `lamports`, returning the airdrop's signature
+    fn request_airdrop(&self, pubkey: &Pubkey, lamports: u64) -> Result<Signature>;
+    /// How far a submitted transaction has got
+    fn get_signature_status(&self, signature: &Signature) -> Result<SignatureStatus>;
+    /// Ok when the node reports itself healthy, i.e. caught up with the cluster
+    fn get_health(&self) -> Result<()>;
+}
+
+/// [`SolanaRpc`] over HTTP against a JSON-RPC node
+pub struct HttpRpc {
+    url: String,
+}
+
+impl HttpRpc {
+    pub fn new(url: impl Into<String>) -> Self {
+        HttpRpc { url: url.into() }
+    }
+}
+
+impl SolanaRpc for HttpRpc {
+    fn get_balance(&self, pubkey: &Pubkey) -> Result<u64> {
+        let result = call(
+            &self.url,
+            "getBalance",
+            json!([pubkey.to_string(), { "commitment": "confirmed" }]),
+        )?;
+        result
+            .get("value")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| RpcError::InvalidResponse("balance without a value".to_string()))
+    }
+
+    fn get_token_accounts(&self, owner: &Pubkey, mint: &Pubkey) -> Result<Vec<TokenAccount>> {
+        let result = call(
+            &self.url,
+            "getTokenAccountsByOwner",
+            json!([
+                owner.to_string(),
+                { "mint": mint.to_string() },
+                { "encoding": "jsonParsed", "commitment": "confirmed" }
+            ]),
+        )?;
+        parse_token_accounts(&result)
+    }
+
+    fn get_owned_token_accounts(&self, owner: &Pubkey) -> Result<Vec<TokenAccount>> {
+        let result = call(
+            &self.url,
+            "getTokenAccountsByOwner",
+            json!([
+                owner.to_string(),
+                { "programId": TOKEN_PROGRAM_ID },
+                { "encoding": "jsonParsed", "commitment": "confirmed" }
+            ]),
+        )?;
+        parse_token_accounts(&result)
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        let result = call(&self.url, "getLatestBlockhash", json!([{ "commitment": "confirmed" }]))?;
+        parse_blockhash(&result)
+    }
+
+    fn get_fee_for_message(&self, message: &Message) -> Result<Option<u64>> {
+        let encoded = BASE64_STANDARD.encode(message.serialize());
+        let result = call(
+            &self.url,
+            "getFeeForMessage",
+            json!([encoded, { "commitment": "confirmed" }]),
+        )?;
+        match result.get("value") {
+            Some(Value::Null) => Ok(None),
+            Some(value) => value
+                .as_u64()
+                .map(Some)
+                .ok_or_else(|| RpcError::InvalidResponse(format!("fee is not a lamport amount: {}", value))),
+            None => Err(RpcError::InvalidResponse("fee without a value".to_string())),
+        }
+    }
+
+    fn get_recent_signatures(&self, pubkey: &Pubkey, limit: usize) -> Result<Vec<TxSummary>> {
+        let result = call(
+            &self.url,
+            "getSignaturesForAddress",
+            json!([pubkey.to_string(), { "limit": limit }]),
+        )?;
+        parse_signatures(&result)
+    }
+
+    fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        let wire = bincode::serialize(transaction)
+            .map_err(|e| RpcError::InvalidResponse(format!("could not encode transaction: {}", e)))?;
+        let result = call(
+            &self.url,
+            "sendTransaction",
+            json!([BASE64_STANDARD.encode(wire), { "encoding": "base64" }]),
+        )?;
+        parse_signature(&result)
+    }
+
+    fn request_airdrop(&self, pubkey: &Pubkey, lamports: u64) -> Result<Signature> {
+        let result = call(&self.url, "requestAirdrop", json!([pubkey.to_string(), lamports]))?;
+        parse_signature(&result)
+    }
+
+    fn get_signature_status(&self, signature: &Signature) -> Result<SignatureStatus> {
+        let result = call(&self.url, "getSignatureStatuses", json!([[signature.to_string()]]))?;
+        parse_signature_status(&result)
+    }
+
+    fn get_health(&self) -> Result<()> {
+        // An unhealthy node answers with an RPC error rather than another result
+        match call(&self.url, "getHealth", json!([]))? {
+            Value::String(status) if status == "ok" => Ok(()),
+            other => Err(RpcError::InvalidResponse(format!("unexpected health status: {}", other))),
+        }
+    }
+}
+
+// Parses a transaction signature returned as a base58 string
+fn parse_signature(result: &Value) -> Result<Signature> {
+    result
+        .as_str()
+        .and_then(|signature| signature.parse().ok())
+        .ok_or_else(|| RpcError::InvalidResponse(format!("not a transaction signature: {}", result)))
+}
+
+// Parses the result object of `getSignatureStatuses` for a single signature
+fn parse_signature_status(result: &Value) -> Result<SignatureStatus> {
+    let status = result
+        .get("value")
+        .and_then(|value| value.get(0))
+        .ok_or_else(|| RpcError::InvalidResponse("no status in response".to_string()))?;
+    if status.is_null() {
+        return Ok(SignatureStatus::Pending);
+    }
+    if let Some(error) = status.get("err").filter(|err| !err.is_null()) {
+        return Ok(SignatureStatus::Failed(error.to_string()));
+    }
+    match status.get("confirmationStatus").and_then(Value::as_str) {
+        Some("confirmed") | Some("finalized") => Ok(SignatureStatus::Confirmed),
+        _ => Ok(SignatureStatus::Pending),
+    }
+}
+
+/// Sends a JSON-RPC request and returns its `result` value
+pub fn call(rpc_url: &str, method: &str, params: Value) -> Result<Value> {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    });
+
+    let response: Value = ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .post(rpc_url)
+        .send_json(request)
+        .map_err(|e| RpcError::Transport(e.to_string()))?
+        .into_json()
+        .map_err(|e| RpcError::Transport(e.to_string()))?;
+
+    extract_result(response)
+}
+
+// Unwraps a JSON-RPC response envelope into its result or error
+fn extract_result(mut response: Value) -> Result<Value> {
+    if let Some(error) = response.get("error") {
+        return Err(RpcError::Rpc {
+            code: error.get("code").and_then(Value::as_i64).unwrap_or_default(),
+            message: error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error")
+                .to_string(),
+        });
+    }
+
+    match response.get_mut("result") {
+        Some(result) => Ok(result.take()),
+        None => Err(RpcError::InvalidResponse(
+            "missing both result and error".to_string(),
+        )),
+    }
+}
+
+// Parses the result array of `getSignaturesForAddress`
+fn parse_signatures(result: &Value) -> Result<Vec<TxSummary>> {
+    let entries = result
+        .as_array()
+        .ok_or_else(|| RpcError::InvalidResponse("expected an array of signatures".to_string()))?;
+
+    entries
+        .iter()
+        .map(|entry| {
+            let signature = entry
+                .get("signature")
+                .and_then(Value::as_str)
+                .ok_or_else(|| RpcError::InvalidResponse("signature entry without a signature".to_string()))?;
+            let slot = entry
+                .get("slot")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| RpcError::InvalidResponse(format!("no slot for {}", signature)))?;
+
+            Ok(TxSummary {
+                signature: signature.to_string(),
+                slot,
+                block_time: entry.get("blockTime").and_then(Value::as_i64),
+                success: entry.get("err").is_none_or(Value::is_null),
+            })
+        })
+        .collect()
+}
+
+// Parses the jsonParsed result object of `getTokenAccountsByOwner`
+fn parse_token_accounts(result: &Value) -> Result<Vec<TokenAccount>> {
+    let entries = result
+        .get("value")
+        .and_then(Value::as_array)
+        .ok_or_else(|| RpcError::InvalidResponse("expected an array of token accounts".to_string()))?;
+
+    let pubkey_field = |value: Option<&Value>, what: &str| -> Result<Pubkey> {
+        value
+            .and_then(Value::as_str)
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| RpcError::InvalidResponse(format!("token account without a valid {}", what)))
+    };
+
+    entries
+        .iter()
+        .map(|entry| {
+            let info = entry.pointer("/account/data/parsed/info");
+            let token_amount = info.and_then(|info| info.get("tokenAmount"));
+            let amount = token_amount
+                .and_then(|amount| amount.get("amount"))
+                .and_then(Value::as_str)
+                .and_then(|amount| amount.parse().ok())
+                .ok_or_else(|| RpcError::InvalidResponse("token account without an amount".to_string()))?;
+            let decimals = token_amount
+                .and_then(|amount| amount.get("decimals"))
+                .and_then(Value::as_u64)
+                .and_then(|decimals| u8::try_from(decimals).ok())
+                .ok_or_else(|| RpcError::InvalidResponse("token account without decimals".to_string()))?;
+
+            Ok(TokenAccount {
+                address: pubkey_field(entry.get("pubkey"), "address")?,
+                mint: pubkey_field(info.and_then(|info| info.get("mint")), "mint")?,
+                amount,
+                decimals,
+            })
+        })
+        .collect()
+}
+
+// Parses the result object of `getLatestBlockhash`
+fn parse_blockhash(result: &Value) -> Result<Hash> {
+    let blockhash = result
+        .get("value")
+        .and_then(|value| value.get("blockhash"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::InvalidResponse("no blockhash in response".to_string()))?;
+    blockhash
+        .parse()
+        .map_err(|e| RpcError::InvalidResponse(format!("invalid blockhash {}: {}", blockhash, e)))
+}
+
+/// Canned [`SolanaRpc`] for unit tests
+#[cfg(test)]
+pub(crate) mod mock {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// Answers every query with the configured values and records what
+    /// was sent, without touching the network
+    pub struct MockRpc {
+        pub lamports: Result<u64>,
+        pub token_accounts: Result<Vec<TokenAccount>>,
+        pub blockhash: Result<Hash>,
+        pub fee: Result<Option<u64>>,
+        pub signatures: Result<Vec<TxSummary>>,
+        pub sent: RefCell<Vec<Transaction>>,
+        pub airdrop: Result<Signature>,
+        pub signature_status: Result<SignatureStatus>,
+        pub health: Result<()>,
+    }
+
+    impl Default for MockRpc {
+        fn default() -> Self {
+            MockRpc {
+                lamports: Ok(0),
+                token_accounts: Ok(Vec::new()),
+                blockhash: Ok(Hash::new_unique()),
+                fee: Ok(Some(5000)),
+                signatures: Ok(Vec::new()),
+                sent: RefCell::new(Vec::new()),
+                airdrop: Ok(Signature::default()),
+                signature_status: Ok(SignatureStatus::Confirmed),
+                health: Ok(()),
+            }
+        }
+    }
+
+    impl MockRpc {
+        pub fn with_lamports(lamports: u64) -> Self {
+            MockRpc {
+                lamports: Ok(lamports),
+                ..MockRpc::default()
+            }
+        }
+    }
+
+    impl SolanaRpc for MockRpc {
+        fn get_balance(&self, _pubkey: &Pubkey) -> Result<u64> {
+            self.lamports.clone()
+        }
+
+        fn get_token_accounts(&self, _owner: &Pubkey, _mint: &Pubkey) -> Result<Vec<TokenAccount>> {
+            self.token_accounts.clone()
+        }
+
+        fn get_owned_token_accounts(&self, _owner: &Pubkey) -> Result<Vec<TokenAccount>> {
+            self.token_accounts.clone()
+        }
+
+        fn get_latest_blockhash(&self) -> Result<Hash> {
+            self.blockhash.clone()
+        }
+
+        fn get_fee_for_message(&self, _message: &Message) -> Result<Option<u64>> {
+            self.fee.clone()
+        }
+
+        fn get_recent_signatures(&self, _pubkey: &Pubkey, limit: usize) -> Result<Vec<TxSummary>> {
+            self.signatures
+                .clone()
+                .map(|signatures| signatures.into_iter().take(limit).collect())
+        }
+
+        fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+            self.sent.borrow_mut().push(transaction.clone());
+            Ok(transaction.signatures.first().copied().unwrap_or_default())
+        }
+
+        fn request_airdrop(&self, _pubkey: &Pubkey, _lamports: u64) -> Result<Signature> {
+            self.airdrop.clone()
+        }
+
+        fn get_signature_status(&self, _signature: &Signature) -> Result<SignatureStatus> {
+            self.signature_status.clone()
+        }
+
+        fn get_health(&self) -> Result<()> {
+            self.health.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Captured from api.devnet.solana.com, trimmed to two entries
+    const SIGNATURES_RESPONSE: &str = r#"{
+        "jsonrpc": "2.0",
+        "result": [
+            {
+                "blockTime": 1718035524,
+                "confirmationStatus": "finalized",
+                "err": null,
+                "memo": null,
+                "signature": "5h6xBEauJ3PK6SWCZ1PGjBvj8vDdWG3KpwATGy1ARAXFSDwt8GFXM7W5Ncn16wmqokgpiKRLuS83KUxyZyv2sUYv",
+                "slot": 114
+            },
+            {
+                "blockTime": null,
+                "confirmationStatus": "finalized",
+                "err": {"InstructionError": [0, {"Custom": 1}]},
+                "memo": null,
+                "signature": "kwjd8xK4bqRxQzkrrrZ3sdbZ7twgm4PcTbS56fMbnERAJWiwjV9DHoGrsu4wH9f6cBvUwHH7uPaASdUX7yn1qgc",
+                "slot": 112
+            }
+        ],
+        "id": 1
+    }"#;
+
+    #[test]
+    fn test_parse_signatures_response() {
+        let response: Value = serde_json::from_str(SIGNATURES_RESPONSE).unwrap();
+        let summaries = parse_signatures(&extract_result(response).unwrap()).unwrap();
+
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].slot, 114);
+        assert_eq!(summaries[0].block_time, Some(1718035524));
+        assert!(summaries[0].success);
+        assert!(summaries[0].signature.starts_with("5h6xBEau"));
+
+        // A failed transaction with an unknown block time
+        assert_eq!(summaries[1].slot, 112);
+        assert_eq!(summaries[1].block_time, None);
+        assert!(!summaries[1].success);
+    }
+
+    #[test]
+    fn test_parse_token_accounts_response() {
+        let result = json!({
+            "context": {"slot": 1114},
+            "value": [{
+                "pubkey": "C2gJg6tKpQs41PRS1nC8aw3ZKNZK3HQQZGVrDFDup5nx",
+                "account": {
+                    "data": {
+                        "program": "spl-token",
+                        "parsed": {
+                            "type": "account",
+                            "info": {
+                                "isNative": false,
+                                "mint": "3wyAj7Rt1TWVPZVteFJPLa26JmLvdb1CAKEFZm3NY75E",
+                                "owner": "4Qkev8aNZcqFNSRhQzwyLMFSsi94jHqE8WNVTJzTP99F",
+                                "state": "initialized",
+                                "tokenAmount": {
+                                    "amount": "1500000",
+                                    "decimals": 6,
+                                    "uiAmount": 1.5,
+                                    "uiAmountString": "1.5"
+                                }
+                            }
+                        },
+                        "space": 165
+                    },
+                    "executable": false,
+                    "lamports": 1726080,
+                    "owner": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "rentEpoch": 4
+                }
+            }]
+        });
+        let accounts = parse_token_accounts(&result).unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].amount, 1_500_000);
+        assert_eq!(accounts[0].decimals, 6);
+        assert_eq!(accounts[0].ui_amount(), 1.5);
+        assert_eq!(accounts[0].mint.to_string(), "3wyAj7Rt1TWVPZVteFJPLa26JmLvdb1CAKEFZm3NY75E");
+
+        assert_eq!(parse_token_accounts(&json!({"value": []})), Ok(Vec::new()));
+        assert!(matches!(
+            parse_token_accounts(&json!({"value": [{"pubkey": "abc"}]})),
+            Err(RpcError::InvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_blockhash_response() {
+        let result = json!({
+            "context": {"slot": 2792},
+            "value": {
+                "blockhash": "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N",
+                "lastValidBlockHeight": 3090
+            }
+        });
+        assert_eq!(
+            parse_blockhash(&result).unwrap().to_string(),
+            "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N"
+        );
+        assert!(matches!(parse_blockhash(&json!({"value": {}})), Err(RpcError::InvalidResponse(_))));
+        assert!(matches!(
+            parse_blockhash(&json!({"value": {"blockhash": "not-base58!"}})),
+            Err(RpcError::InvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_signature_status_response() {
+        let status = |value: Value| parse_signature_status(&json!({"context": {"slot": 82}, "value": [value]}));
+
+        assert_eq!(status(Value::Null), Ok(SignatureStatus::Pending));
+        assert_eq!(
+            status(json!({"slot": 72, "confirmations": 10, "err": null, "confirmationStatus": "confirmed"})),
+            Ok(SignatureStatus::Confirmed)
+        );
+        assert_eq!(
+            status(json!({"slot": 72, "confirmations": 0, "err": null, "confirmationStatus": "processed"})),
+            Ok(SignatureStatus::Pending)
+        );
+        assert!(matches!(
+            status(json!({"slot": 48, "err": {"InstructionError": [0, "Custom"]}, "confirmationStatus": "finalized"})),
+            Ok(SignatureStatus::Failed(_))
+        ));
+    }
+
+    #[test]
+    fn test_is_mainnet_url() {
+        assert!(is_mainnet_url(DEFAULT_RPC_URL));
+        assert!(is_mainnet_url("https://mainnet.helius-rpc.com/?api-key=abc"));
+        assert!(!is_mainnet_url("https://api.devnet.solana.com"));
+        assert!(!is_mainnet_url("http://127.0.0.1:8899"));
+        // Only the host counts, not the path or query
+        assert!(!is_mainnet_url("https://api.testnet.solana.com/mainnet"));
+    }
+
+    #[test]
+    fn test_cluster_name() {
+        assert_eq!(cluster_name(DEFAULT_RPC_URL), "mainnet-beta");
+        assert_eq!(cluster_name("https://api.devnet.solana.com"), "devnet");
+        assert_eq!(cluster_name("https://api.testnet.solana.com/mainnet"), "testnet");
+        assert_eq!(cluster_name("http://127.0.0.1:8899"), "localnet");
+        assert_eq!(cluster_name("http://LOCALHOST:8899"), "localnet");
+        assert_eq!(cluster_name("https://rpc.example.com:443/key"), "rpc.example.com");
+    }
+
+    #[test]
+    fn test_rpc_error_response() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "error": {"code": -32602, "message": "Invalid param: WrongSize"},
+            "id": 1
+        });
+        assert_eq!(
+            extract_result(response),
+            Err(RpcError::Rpc {
+                code: -32602,
+                message: "Invalid param: WrongSize".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_malformed_signatures_result() {
+        assert!(matches!(parse_signatures(&json!({})), Err(RpcError::InvalidResponse(_))));
+        assert!(matches!(
+            parse_signatures(&json!([{"signature": "abc"}])),
+            Err(RpcError::InvalidResponse(_))
+        ));
+        assert_eq!(parse_signatures(&json!([])), Ok(Vec::new()));
+    }
+}
@@ -1,11 +1,24 @@
 // transaction_handler.rs
 
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use solana_message::Message;
+use solana_sdk::hash::Hash;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signer;
 use solana_sdk::signer::keypair::Keypair;
-use std::io::{self, Error, ErrorKind};
+use solana_system_interface::instruction as system_instruction;
+use solana_sdk::signature::Signature;
+use spl_associated_token_account_interface::address::get_associated_token_address;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
 
+use crate::rpc::{self, HttpRpc, RpcError, SignatureStatus, SolanaRpc};
 use crate::wallet_manager;
 
 // Custom error type for transaction operations
@@ -15,6 +28,9 @@ pub enum TransactionError {
     InvalidAddress(String),
     InvalidAmount(String),
     TransactionFailed(String),
+    InsufficientFunds(String),
+    UnsafeRecipient(String),
+    Rpc(RpcError),
     IoError(io::Error),
 }
 
@@ -25,6 +41,9 @@ impl std::fmt::Display for TransactionError {
             TransactionError::InvalidAddress(msg) => write!(f, "Invalid address: {}", msg),
             TransactionError::InvalidAmount(msg) => write!(f, "Invalid amount: {}", msg),
             TransactionError::TransactionFailed(msg) => write!(f, "Transaction failed: {}", msg),
+            TransactionError::InsufficientFunds(msg) => write!(f, "Insufficient funds: {}", msg),
+            TransactionError::UnsafeRecipient(msg) => write!(f, "Unsafe recipient: {}", msg),
+            TransactionError::Rpc(e) => write!(f, "{}", e),
             TransactionError::IoError(e) => write!(f, "I/O error: {}", e),
         }
     }
@@ -38,38 +57,110 @@ impl From<io::Error> for TransactionError {
     }
 }
 
+impl From<RpcError> for TransactionError {
+    fn from(error: RpcError) -> Self {
+        TransactionError::Rpc(error)
+    }
+}
+
 impl From<TransactionError> for io::Error {
     fn from(error: TransactionError) -> Self {
         match error {
             TransactionError::IoError(e) => e,
-            _ => io::Error::new(io::ErrorKind::Other, error.to_string()),
+            _ => io::Error::other(error.to_string()),
         }
     }
 }
 
 // Batch transaction structure
+#[derive(Debug)]
 pub struct BatchTransaction {
     pub source_wallet: String,
     pub recipients: Vec<(String, f64)>, // (recipient_address, amount)
     pub token_mint: Option<String>,     // None for SOL, Some(mint_address) for SPL tokens
+    pub allow_unsafe_recipients: bool,  // Warn about flagged recipients instead of refusing them
 }
 
 // Token mixing structure
+#[derive(Debug)]
 pub struct TokenMixingPlan {
     pub source_wallets: Vec<String>,
     pub destination_addresses: Vec<String>,
     pub total_amount: f64,
     pub steps: usize,
     pub token_mint: Option<String>, // None for SOL, Some(mint_address) for SPL tokens
+    pub seed: Option<u64>,          // Fixed RNG seed for a reproducible plan; None picks a fresh one
+    pub weights: Option<Vec<f64>>,  // Share of the total for each destination; None splits it evenly by step
 }
 
-// Validate a Solana address
-fn validate_solana_address(address: &str) -> Result<Pubkey, TransactionError> {
+// How far the weights of a mixing plan may sum away from 1.0
+const MIXING_WEIGHT_TOLERANCE: f64 = 1e-6;
+
+/// Parses `address` as a Solana public key
+pub fn validate_solana_address(address: &str) -> Result<Pubkey, TransactionError> {
     Pubkey::from_str(address).map_err(|e| {
         TransactionError::InvalidAddress(format!("Invalid Solana address '{}': {}", address, e))
     })
 }
 
+// Programs, sysvars and burn addresses that cannot usefully receive a transfer
+const KNOWN_UNSAFE_RECIPIENTS: [(&str, &str); 12] = [
+    ("11111111111111111111111111111111", "the System Program"),
+    ("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", "the SPL Token program"),
+    ("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb", "the Token-2022 program"),
+    ("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL", "the Associated Token Account program"),
+    ("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr", "the Memo program"),
+    ("ComputeBudget111111111111111111111111111111", "the Compute Budget program"),
+    ("Stake11111111111111111111111111111111111111", "the Stake program"),
+    ("Vote111111111111111111111111111111111111111", "the Vote program"),
+    ("BPFLoaderUpgradeab1e11111111111111111111111", "the upgradeable BPF loader"),
+    ("SysvarRent111111111111111111111111111111111", "the Rent sysvar"),
+    ("SysvarC1ock11111111111111111111111111111111", "the Clock sysvar"),
+    ("1nc1nerator11111111111111111111111111111111", "the incinerator burn address"),
+];
+
+/// Why sending to `recipient` is almost certainly a mistake: it is a
+/// well-known program or burn address, the token's mint, or the source
+/// itself. None for an ordinary address.
+pub fn unsafe_recipient_reason(recipient: &Pubkey, source: &Pubkey, token_mint: Option<&Pubkey>) -> Option<String> {
+    if recipient == source {
+        return Some(format!("{} is the sending wallet itself", recipient));
+    }
+    if token_mint == Some(recipient) {
+        return Some(format!("{} is the token's mint, not a wallet", recipient));
+    }
+    let address = recipient.to_string();
+    KNOWN_UNSAFE_RECIPIENTS
+        .iter()
+        .find(|(known, _)| *known == address)
+        .map(|(_, name)| format!("{} is {}", recipient, name))
+}
+
+/// Where a simulation log of `kind` (e.g. "batch" or "mixing") made at `at`
+/// is kept under `dir`, as `{kind}-YYYYMMDD-HHMMSS.log`
+pub fn simulation_log_path(dir: &Path, kind: &str, at: chrono::DateTime<chrono::Local>) -> PathBuf {
+    dir.join(format!("{}-{}.log", kind, at.format("%Y%m%d-%H%M%S")))
+}
+
+/// Writes a simulation log to `path`, creating its directory if needed
+pub fn write_simulation_log(path: &Path, log: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, log)
+}
+
+/// Address of `owner`'s associated token account for `mint` under the SPL Token program
+pub fn derive_ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(owner, mint)
+}
+
+/// Address of a stored wallet's associated token account for `mint`
+pub fn wallet_ata(wallet_name: &str, mint: &str) -> Result<Pubkey, TransactionError> {
+    let mint = validate_solana_address(mint)?;
+    Ok(derive_ata(&get_wallet_keypair(wallet_name)?.pubkey(), &mint))
+}
+
 // Validate a token amount
 fn validate_amount(amount: f64) -> Result<(), TransactionError> {
     if amount <= 0.0 {
@@ -96,7 +187,7 @@ fn get_wallet_keypair(wallet_name: &str) -> Result<Keypair, TransactionError> {
 }
 
 // Execute a batch transaction (simulation for now)
-pub fn execute_batch_transaction(batch: &BatchTransaction) -> Result<String, TransactionError> {
+pub fn execute_batch_transaction(batch: &BatchTransaction, rpc: &dyn SolanaRpc) -> Result<String, TransactionError> {
     // Validate source wallet
     let source_keypair = get_wallet_keypair(&batch.source_wallet)?;
     let source_pubkey = source_keypair.pubkey();
@@ -112,6 +203,16 @@ pub fn execute_batch_transaction(batch: &BatchTransaction) -> Result<String, Tra
         validated_recipients.push((recipient_pubkey, *amount));
     }
 
+    // Sending to a program, the mint or back to the source loses or wastes funds
+    let mint_pubkey = batch.token_mint.as_deref().map(validate_solana_address).transpose()?;
+    let recipient_warnings: Vec<String> = validated_recipients
+        .iter()
+        .filter_map(|(pubkey, _)| unsafe_recipient_reason(pubkey, &source_pubkey, mint_pubkey.as_ref()))
+        .collect();
+    if !recipient_warnings.is_empty() && !batch.allow_unsafe_recipients {
+        return Err(TransactionError::UnsafeRecipient(recipient_warnings.join("; ")));
+    }
+
     // Log the transaction details
     let token_type = match &batch.token_mint {
         Some(mint) => format!("SPL token (mint: {})", mint),
@@ -126,6 +227,9 @@ pub fn execute_batch_transaction(batch: &BatchTransaction) -> Result<String, Tra
     let mut detailed_log = transaction_log;
     detailed_log.push_str(&format!("Token type: {}\n", token_type));
     detailed_log.push_str(&format!("Total amount: {} {}\n", total_amount, token_type));
+    for warning in &recipient_warnings {
+        detailed_log.push_str(&format!("Warning: {}\n", warning));
+    }
     detailed_log.push_str("Recipients:\n");
 
     for (i, (pubkey, amount)) in validated_recipients.iter().enumerate() {
@@ -138,6 +242,29 @@ pub fn execute_batch_transaction(batch: &BatchTransaction) -> Result<String, Tra
         ));
     }
 
+    let fee = estimate_batch_fee(
+        rpc,
+        &source_pubkey,
+        &validated_recipients,
+        batch.token_mint.as_deref(),
+    )?;
+    match &fee.source {
+        FeeSource::Rpc => {
+            detailed_log.push_str(&format!("Estimated fee: {} SOL (quoted by the RPC node)\n", fee.sol));
+        }
+        FeeSource::Heuristic(reason) => {
+            detailed_log.push_str(&format!("Estimated fee: {} SOL (offline estimate: {})\n", fee.sol, reason));
+        }
+    }
+
+    // Nothing is submitted yet, so an unreachable node only leaves the
+    // balance unchecked; a known shortfall still stops the batch
+    match check_balance(rpc, &source_pubkey, total_amount, fee.sol, batch.token_mint.as_deref()) {
+        Ok(BalanceCheck::Sufficient) => detailed_log.push_str("Balance check: sufficient funds\n"),
+        Ok(shortfall) => return Err(TransactionError::InsufficientFunds(shortfall.to_string())),
+        Err(e) => detailed_log.push_str(&format!("Balance check skipped: {}\n", e)),
+    }
+
     // In a real implementation, this would construct and send the actual transaction
     // For now, we just return the simulation log
     Ok(detailed_log)
@@ -159,6 +286,10 @@ pub fn generate_token_mixing_plan(plan: &TokenMixingPlan) -> Result<String, Tran
         validated_destinations.push(pubkey);
     }
 
+    if let Some(weights) = &plan.weights {
+        validate_mixing_weights(weights, validated_destinations.len())?;
+    }
+
     // Validate total amount
     validate_amount(plan.total_amount)?;
 
@@ -182,22 +313,33 @@ pub fn generate_token_mixing_plan(plan: &TokenMixingPlan) -> Result<String, Tran
     }
 
     mixing_log.push_str("\nDestination addresses:\n");
-    for pubkey in &validated_destinations {
-        mixing_log.push_str(&format!("  - {}\n", pubkey));
+    for (index, pubkey) in validated_destinations.iter().enumerate() {
+        match &plan.weights {
+            Some(weights) => mixing_log.push_str(&format!(
+                "  - {} ({}%: {} {})\n",
+                pubkey,
+                weights[index] * 100.0,
+                plan.total_amount * weights[index],
+                token_type
+            )),
+            None => mixing_log.push_str(&format!("  - {}\n", pubkey)),
+        }
     }
 
     mixing_log.push_str("\nSimulated mixing steps:\n");
 
-    // Generate some random mixing steps for simulation
-    use rand::{seq::SliceRandom, Rng};
-    let mut rng = rand::thread_rng();
-
-    let amount_per_step = plan.total_amount / (plan.steps as f64);
-
-    for step in 1..=plan.steps {
-        let source_idx = rng.gen_range(0..source_keypairs.len());
-        let dest_idx = rng.gen_range(0..validated_destinations.len());
+    let mut steps = mixing_steps(
+        source_keypairs.len(),
+        validated_destinations.len(),
+        plan.steps,
+        plan.seed,
+    );
+    if let Some(weights) = &plan.weights {
+        assign_weighted_destinations(&mut steps, weights)?;
+    }
+    let amounts = mixing_amounts(plan.total_amount, &steps, plan.weights.as_deref());
 
+    for (step, ((source_idx, dest_idx), amount)) in (1..).zip(steps.into_iter().zip(amounts)) {
         let (source_name, source_keypair) = &source_keypairs[source_idx];
         let dest_pubkey = validated_destinations[dest_idx];
 
@@ -207,7 +349,7 @@ pub fn generate_token_mixing_plan(plan: &TokenMixingPlan) -> Result<String, Tran
             source_name,
             source_keypair.pubkey(),
             dest_pubkey,
-            amount_per_step,
+            amount,
             token_type
         ));
     }
@@ -215,33 +357,314 @@ pub fn generate_token_mixing_plan(plan: &TokenMixingPlan) -> Result<String, Tran
     Ok(mixing_log)
 }
 
-// Function to check if a wallet has sufficient balance for a transaction
+/// Checks that mixing `weights` give one non-negative share per destination
+/// and add up to 1.0.
+pub fn validate_mixing_weights(weights: &[f64], destination_count: usize) -> Result<(), TransactionError> {
+    if weights.len() != destination_count {
+        return Err(TransactionError::InvalidAmount(format!(
+            "{} weights given for {} destinations",
+            weights.len(),
+            destination_count
+        )));
+    }
+    if let Some(weight) = weights.iter().find(|w| !w.is_finite() || **w < 0.0) {
+        return Err(TransactionError::InvalidAmount(format!(
+            "Weight {} is not a non-negative number",
+            weight
+        )));
+    }
+    let sum: f64 = weights.iter().sum();
+    if (sum - 1.0).abs() > MIXING_WEIGHT_TOLERANCE {
+        return Err(TransactionError::InvalidAmount(format!(
+            "Weights must add up to 1.0, got {}",
+            sum
+        )));
+    }
+    Ok(())
+}
+
+// Points the steps of a weighted plan at the destinations with a non-zero
+// weight in turn, so each of them receives its share
+fn assign_weighted_destinations(steps: &mut [(usize, usize)], weights: &[f64]) -> Result<(), TransactionError> {
+    let funded: Vec<usize> = (0..weights.len()).filter(|&index| weights[index] > 0.0).collect();
+    if steps.len() < funded.len() {
+        return Err(TransactionError::InvalidAmount(format!(
+            "{} steps cannot reach {} weighted destinations",
+            steps.len(),
+            funded.len()
+        )));
+    }
+    for (index, step) in steps.iter_mut().enumerate() {
+        step.1 = funded[index % funded.len()];
+    }
+    Ok(())
+}
+
+// Amount moved by each step: an equal split of the total, or with weights,
+// each destination's share split evenly across the steps that reach it
+fn mixing_amounts(total_amount: f64, steps: &[(usize, usize)], weights: Option<&[f64]>) -> Vec<f64> {
+    let Some(weights) = weights else {
+        return vec![total_amount / steps.len() as f64; steps.len()];
+    };
+    let mut steps_per_destination = vec![0usize; weights.len()];
+    for &(_, dest) in steps {
+        steps_per_destination[dest] += 1;
+    }
+    steps
+        .iter()
+        .map(|&(_, dest)| total_amount * weights[dest] / steps_per_destination[dest] as f64)
+        .collect()
+}
+
+// Picks a random (source index, destination index) pair for each step,
+// seeded from `seed` when given so the same plan can be regenerated
+fn mixing_steps(
+    source_count: usize,
+    destination_count: usize,
+    steps: usize,
+    seed: Option<u64>,
+) -> Vec<(usize, usize)> {
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    (0..steps)
+        .map(|_| {
+            (
+                rng.gen_range(0..source_count),
+                rng.gen_range(0..destination_count),
+            )
+        })
+        .collect()
+}
+
+/// Outcome of comparing a wallet's funds with what a transfer needs
+#[derive(Debug, Clone, PartialEq)]
+pub enum BalanceCheck {
+    Sufficient,
+    /// Amounts are in whole units of `asset`, which is "SOL" or a token mint
+    Insufficient {
+        asset: String,
+        available: f64,
+        required: f64,
+        shortfall: f64,
+    },
+}
+
+impl std::fmt::Display for BalanceCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BalanceCheck::Sufficient => write!(f, "sufficient funds"),
+            BalanceCheck::Insufficient {
+                asset,
+                available,
+                required,
+                shortfall,
+            } => write!(
+                f,
+                "{} {} available but {} {} needed ({} {} short)",
+                available, asset, required, asset, shortfall, asset
+            ),
+        }
+    }
+}
+
+/// SOL balance of `pubkey`
+pub fn get_sol_balance(rpc: &dyn SolanaRpc, pubkey: &Pubkey) -> Result<f64, TransactionError> {
+    Ok(rpc.get_balance(pubkey)? as f64 / LAMPORTS_PER_SOL as f64)
+}
+
+/// Balance of the `mint` token across all of `owner`'s token accounts, in whole tokens
+pub fn get_token_balance(rpc: &dyn SolanaRpc, owner: &Pubkey, mint: &Pubkey) -> Result<f64, TransactionError> {
+    Ok(rpc
+        .get_token_accounts(owner, mint)?
+        .iter()
+        .map(rpc::TokenAccount::ui_amount)
+        .sum())
+}
+
+/// Checks that `pubkey` can send `required_amount` (SOL, or tokens of
+/// `token_mint`) and also pay `estimated_fee` SOL
+pub fn check_balance(
+    rpc: &dyn SolanaRpc,
+    pubkey: &Pubkey,
+    required_amount: f64,
+    estimated_fee: f64,
+    token_mint: Option<&str>,
+) -> Result<BalanceCheck, TransactionError> {
+    let shortfall = |asset: &str, available: f64, required: f64| BalanceCheck::Insufficient {
+        asset: asset.to_string(),
+        available,
+        required,
+        shortfall: required - available,
+    };
+
+    // SOL is compared in lamports so rounding cannot hide a shortfall
+    let lamports = rpc.get_balance(pubkey)?;
+    let sol_needed = match token_mint {
+        Some(mint) => {
+            let mint_pubkey = validate_solana_address(mint)?;
+            let available = get_token_balance(rpc, pubkey, &mint_pubkey)?;
+            if available < required_amount {
+                return Ok(shortfall(mint, available, required_amount));
+            }
+            sol_to_lamports(estimated_fee)
+        }
+        None => sol_to_lamports(required_amount) + sol_to_lamports(estimated_fee),
+    };
+
+    if lamports < sol_needed {
+        let to_sol = |lamports: u64| lamports as f64 / LAMPORTS_PER_SOL as f64;
+        return Ok(shortfall("SOL", to_sol(lamports), to_sol(sol_needed)));
+    }
+    Ok(BalanceCheck::Sufficient)
+}
+
+/// Checks a stored wallet's balance against live RPC, see [`check_balance`]
 pub fn check_wallet_balance(
+    rpc: &dyn SolanaRpc,
     wallet_name: &str,
     required_amount: f64,
+    estimated_fee: f64,
     token_mint: Option<&str>,
-) -> Result<bool, TransactionError> {
-    // In a real implementation, this would query the Solana network for the wallet's balance
-    // For now, we'll simulate it
-    
-    // First, check if the wallet exists
+) -> Result<BalanceCheck, TransactionError> {
     let keypair = get_wallet_keypair(wallet_name)?;
-    let pubkey = keypair.pubkey();
-    
-    // Simulate balance check
-    // In a real implementation, this would use the Solana RPC API
-    let simulated_balance = 10.0; // Placeholder value
-    
-    if token_mint.is_some() {
-        // For SPL tokens, we would need to query the token account
-        // This is just a placeholder
-        println!("Checking SPL token balance for wallet {} ({})", wallet_name, pubkey);
-    } else {
-        // For SOL, we would query the account's lamports
-        println!("Checking SOL balance for wallet {} ({})", wallet_name, pubkey);
+    check_balance(rpc, &keypair.pubkey(), required_amount, estimated_fee, token_mint)
+}
+
+/// Where a fee estimate came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeeSource {
+    /// Quoted by the node through `getFeeForMessage`
+    Rpc,
+    /// Offline heuristic, with the reason the node was not used
+    Heuristic(String),
+}
+
+/// Fee for a transaction, in SOL
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeEstimate {
+    pub sol: f64,
+    pub source: FeeSource,
+}
+
+// Converts a SOL amount to lamports, rounding to the nearest lamport
+fn sol_to_lamports(sol: f64) -> u64 {
+    (sol * LAMPORTS_PER_SOL as f64).round() as u64
+}
+
+/// Builds the unsigned message paying each recipient its SOL amount from `payer`
+pub fn build_transfer_message(payer: &Pubkey, recipients: &[(Pubkey, f64)], recent_blockhash: Hash) -> Message {
+    let instructions: Vec<_> = recipients
+        .iter()
+        .map(|(recipient, amount)| system_instruction::transfer(payer, recipient, sol_to_lamports(*amount)))
+        .collect();
+    Message::new_with_blockhash(&instructions, Some(payer), &recent_blockhash)
+}
+
+/// Asks the node what `message` would cost, in lamports, via `getFeeForMessage`
+pub fn estimate_fee_via_rpc(message: &Message, rpc: &dyn SolanaRpc) -> Result<u64, TransactionError> {
+    rpc.get_fee_for_message(message)?.ok_or_else(|| {
+        TransactionError::TransactionFailed("the node no longer knows the message's blockhash".to_string())
+    })
+}
+
+/// Estimates the fee for paying `recipients` from `payer`. SOL transfers are
+/// quoted by the node; SPL transfers, or any RPC failure, fall back to the
+/// offline heuristic of [`estimate_transaction_fees`].
+pub fn estimate_batch_fee(
+    rpc: &dyn SolanaRpc,
+    payer: &Pubkey,
+    recipients: &[(Pubkey, f64)],
+    token_mint: Option<&str>,
+) -> Result<FeeEstimate, TransactionError> {
+    let quoted = match token_mint {
+        Some(_) => Err("SPL token transfers are not quoted over RPC yet".to_string()),
+        None => rpc
+            .get_latest_blockhash()
+            .map_err(TransactionError::from)
+            .and_then(|blockhash| {
+                estimate_fee_via_rpc(&build_transfer_message(payer, recipients, blockhash), rpc)
+            })
+            .map_err(|e| e.to_string()),
+    };
+
+    match quoted {
+        Ok(lamports) => Ok(FeeEstimate {
+            sol: lamports as f64 / LAMPORTS_PER_SOL as f64,
+            source: FeeSource::Rpc,
+        }),
+        Err(reason) => Ok(FeeEstimate {
+            sol: estimate_transaction_fees(recipients.len(), token_mint)?,
+            source: FeeSource::Heuristic(reason),
+        }),
+    }
+}
+
+/// Signs an arbitrary message with a stored wallet, proving ownership of its address
+pub fn sign_message(wallet_name: &str, message: &[u8]) -> Result<Signature, TransactionError> {
+    Ok(get_wallet_keypair(wallet_name)?.sign_message(message))
+}
+
+/// Whether `signature` is `pubkey`'s signature over `message`
+pub fn verify_message(pubkey: &Pubkey, message: &[u8], signature: &Signature) -> bool {
+    signature.verify(pubkey.as_ref(), message)
+}
+
+// How long an airdrop may take to confirm, and how often to ask
+const AIRDROP_CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Requests `sol` from the cluster's faucet for a stored wallet and waits for
+/// it to confirm. Refuses mainnet URLs, where there is no faucet, before
+/// making any request.
+pub fn request_airdrop(wallet_name: &str, sol: f64, rpc_url: &str) -> Result<Signature, TransactionError> {
+    if rpc::is_mainnet_url(rpc_url) {
+        return Err(TransactionError::TransactionFailed(format!(
+            "airdrops are only available on devnet, testnet or a local validator, not {}",
+            rpc_url
+        )));
+    }
+    validate_amount(sol)?;
+    let keypair = get_wallet_keypair(wallet_name)?;
+    airdrop_to(&HttpRpc::new(rpc_url), &keypair.pubkey(), sol, AIRDROP_CONFIRM_TIMEOUT)
+}
+
+/// Requests an airdrop of `sol` to `pubkey` and waits up to `timeout` for it to confirm
+pub fn airdrop_to(
+    rpc: &dyn SolanaRpc,
+    pubkey: &Pubkey,
+    sol: f64,
+    timeout: Duration,
+) -> Result<Signature, TransactionError> {
+    let signature = rpc.request_airdrop(pubkey, sol_to_lamports(sol))?;
+    confirm_signature(rpc, &signature, timeout)?;
+    Ok(signature)
+}
+
+/// Polls until `signature` is confirmed, failing if it errors or `timeout` passes
+pub fn confirm_signature(
+    rpc: &dyn SolanaRpc,
+    signature: &Signature,
+    timeout: Duration,
+) -> Result<(), TransactionError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match rpc.get_signature_status(signature)? {
+            SignatureStatus::Confirmed => return Ok(()),
+            SignatureStatus::Failed(error) => {
+                return Err(TransactionError::TransactionFailed(format!("{} failed: {}", signature, error)));
+            }
+            SignatureStatus::Pending if Instant::now() >= deadline => {
+                return Err(TransactionError::TransactionFailed(format!(
+                    "{} was not confirmed within {} seconds",
+                    signature,
+                    timeout.as_secs()
+                )));
+            }
+            SignatureStatus::Pending => thread::sleep(CONFIRM_POLL_INTERVAL),
+        }
     }
-    
-    Ok(simulated_balance >= required_amount)
 }
 
 // Function to estimate transaction fees
@@ -269,6 +692,8 @@ pub fn estimate_transaction_fees(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rpc::mock::MockRpc;
+    use crate::rpc::TokenAccount;
     
     #[test]
     fn test_validate_solana_address() {
@@ -281,6 +706,78 @@ mod tests {
         assert!(validate_solana_address(invalid_address).is_err());
     }
     
+    #[test]
+    fn test_unsafe_recipients_are_flagged() {
+        let source = Keypair::new().pubkey();
+        let mint = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+        
+        for address in [
+            "11111111111111111111111111111111",
+            "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+            "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+            "SysvarRent111111111111111111111111111111111",
+            "1nc1nerator11111111111111111111111111111111",
+        ] {
+            let recipient = validate_solana_address(address).unwrap();
+            let reason = unsafe_recipient_reason(&recipient, &source, None);
+            assert!(reason.is_some(), "{} should be flagged", address);
+        }
+        // Every entry in the list is a real address
+        for (address, _) in KNOWN_UNSAFE_RECIPIENTS {
+            assert!(validate_solana_address(address).is_ok(), "{}", address);
+        }
+        
+        // The source itself, and the mint when sending that token
+        assert!(unsafe_recipient_reason(&source, &source, None).unwrap().contains("sending wallet"));
+        assert!(unsafe_recipient_reason(&mint, &source, Some(&mint)).unwrap().contains("mint"));
+        assert!(unsafe_recipient_reason(&mint, &source, None).is_none());
+    }
+    
+    #[test]
+    fn test_ordinary_recipients_pass() {
+        let source = Keypair::new().pubkey();
+        let mint = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+        for address in [
+            "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin",
+            "F4YA4H7HeXLCvjLRKdh56FgE4cyHpPqLP1VCM6fEqEmX",
+        ] {
+            let recipient = validate_solana_address(address).unwrap();
+            assert_eq!(unsafe_recipient_reason(&recipient, &source, Some(&mint)), None);
+        }
+        assert_eq!(unsafe_recipient_reason(&Keypair::new().pubkey(), &source, Some(&mint)), None);
+    }
+    
+    #[test]
+    fn test_write_simulation_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let at = chrono::TimeZone::with_ymd_and_hms(&chrono::Local, 2024, 6, 10, 15, 30, 5).unwrap();
+        let path = simulation_log_path(&dir.path().join("simulations"), "batch", at);
+        assert_eq!(path, dir.path().join("simulations").join("batch-20240610-153005.log"));
+        
+        // The directory is created on first use
+        let log = "SIMULATION: Batch transaction from payer:\nRecipients:\n  1. abc - 1 SOL\n";
+        write_simulation_log(&path, log).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), log);
+    }
+    
+    #[test]
+    fn test_derive_ata_matches_reference() {
+        let owner = Pubkey::from_str("9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin").unwrap();
+        let usdc = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+        let ata = derive_ata(&owner, &usdc);
+        assert_eq!(ata.to_string(), "F4YA4H7HeXLCvjLRKdh56FgE4cyHpPqLP1VCM6fEqEmX");
+        
+        // The ATA program's PDA over [owner, token program, mint]
+        let token_program = Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+        let ata_program = Pubkey::from_str("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL").unwrap();
+        let (expected, _) = Pubkey::find_program_address(
+            &[owner.as_ref(), token_program.as_ref(), usdc.as_ref()],
+            &ata_program,
+        );
+        assert_eq!(ata, expected);
+        assert_ne!(derive_ata(&usdc, &owner), ata);
+    }
+    
     #[test]
     fn test_validate_amount() {
         // Valid amount
@@ -291,6 +788,244 @@ mod tests {
         assert!(validate_amount(-1.0).is_err());
     }
     
+    #[test]
+    fn test_mixing_steps_are_reproducible_with_a_seed() {
+        let first = mixing_steps(5, 7, 50, Some(42));
+        assert_eq!(first, mixing_steps(5, 7, 50, Some(42)));
+        assert_ne!(first, mixing_steps(5, 7, 50, Some(43)));
+        
+        // Every step stays within the given wallets and destinations
+        assert_eq!(first.len(), 50);
+        assert!(first.iter().all(|&(source, dest)| source < 5 && dest < 7));
+    }
+    
+    #[test]
+    fn test_weighted_mixing_distribution() {
+        let weights = [0.5, 0.0, 0.3, 0.2];
+        let mut steps = mixing_steps(3, weights.len(), 7, Some(7));
+        assign_weighted_destinations(&mut steps, &weights).unwrap();
+        
+        // Every step goes to a destination with a share, and each gets its share
+        assert!(steps.iter().all(|&(_, dest)| dest != 1));
+        let amounts = mixing_amounts(10.0, &steps, Some(&weights));
+        for (dest, weight) in weights.iter().enumerate() {
+            let received: f64 = steps.iter().zip(&amounts)
+                .filter(|((_, d), _)| *d == dest)
+                .map(|(_, amount)| amount)
+                .sum();
+            assert!((received - 10.0 * weight).abs() < 1e-9, "destination {} got {}", dest, received);
+        }
+        
+        // Too few steps to reach every weighted destination
+        let mut steps = mixing_steps(3, weights.len(), 2, Some(7));
+        assert!(assign_weighted_destinations(&mut steps, &weights).is_err());
+        
+        // Without weights the total is split evenly
+        assert_eq!(mixing_amounts(10.0, &[(0, 0); 4], None), vec![2.5; 4]);
+    }
+    
+    #[test]
+    fn test_mixing_weights_must_match_destinations() {
+        assert!(validate_mixing_weights(&[0.25, 0.75], 2).is_ok());
+        assert!(validate_mixing_weights(&[0.5, 0.5], 3).is_err());
+        assert!(validate_mixing_weights(&[0.5, 0.4], 2).is_err());
+        assert!(validate_mixing_weights(&[1.5, -0.5], 2).is_err());
+        assert!(validate_mixing_weights(&[f64::NAN, 1.0], 2).is_err());
+        
+        // The plan is refused before anything is simulated
+        let plan = TokenMixingPlan {
+            source_wallets: Vec::new(),
+            destination_addresses: vec![Pubkey::new_unique().to_string(), Pubkey::new_unique().to_string()],
+            total_amount: 1.0,
+            steps: 4,
+            token_mint: None,
+            seed: Some(1),
+            weights: Some(vec![1.0]),
+        };
+        assert!(matches!(generate_token_mixing_plan(&plan), Err(TransactionError::InvalidAmount(_))));
+    }
+    
+    #[test]
+    fn test_build_transfer_message() {
+        let payer = Pubkey::new_unique();
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+        let message = build_transfer_message(&payer, &[(first, 1.5), (second, 0.000000001)], blockhash);
+
+        // The payer signs and pays; each recipient gets one system transfer
+        assert_eq!(message.account_keys[0], payer);
+        assert_eq!(message.header.num_required_signatures, 1);
+        assert_eq!(message.recent_blockhash, blockhash);
+        assert_eq!(message.instructions.len(), 2);
+        assert!(message.account_keys.contains(&first));
+        assert!(message.account_keys.contains(&second));
+
+        let single = build_transfer_message(&payer, &[(first, 1.5)], blockhash);
+        assert_eq!(
+            single.instructions[0].data,
+            system_instruction::transfer(&payer, &first, 1_500_000_000).data
+        );
+        assert_eq!(sol_to_lamports(0.000000001), 1);
+    }
+
+    #[test]
+    fn test_fee_estimate_quoted_by_rpc() {
+        let payer = Pubkey::new_unique();
+        let recipients = [(Pubkey::new_unique(), 1.0)];
+        let rpc = MockRpc {
+            fee: Ok(Some(10_000)),
+            ..MockRpc::default()
+        };
+        let estimate = estimate_batch_fee(&rpc, &payer, &recipients, None).unwrap();
+        assert_eq!(estimate, FeeEstimate { sol: 0.00001, source: FeeSource::Rpc });
+    }
+
+    #[test]
+    fn test_fee_estimate_falls_back_without_rpc() {
+        let payer = Pubkey::new_unique();
+        let recipients = [(Pubkey::new_unique(), 1.0), (Pubkey::new_unique(), 2.0)];
+
+        let unreachable = MockRpc {
+            blockhash: Err(RpcError::Transport("connection refused".to_string())),
+            ..MockRpc::default()
+        };
+        let estimate = estimate_batch_fee(&unreachable, &payer, &recipients, None).unwrap();
+        assert_eq!(estimate.sol, estimate_transaction_fees(2, None).unwrap());
+        assert!(matches!(estimate.source, FeeSource::Heuristic(_)));
+
+        // An expired blockhash also falls back
+        let expired = MockRpc {
+            fee: Ok(None),
+            ..MockRpc::default()
+        };
+        let estimate = estimate_batch_fee(&expired, &payer, &recipients, None).unwrap();
+        assert!(matches!(estimate.source, FeeSource::Heuristic(_)));
+
+        // SPL transfers use the heuristic without contacting the node
+        let estimate = estimate_batch_fee(&MockRpc::default(), &payer, &recipients, Some("TokenMint")).unwrap();
+        assert_eq!(estimate.sol, estimate_transaction_fees(2, Some("TokenMint")).unwrap());
+    }
+
+    #[test]
+    fn test_balance_check_sufficient() {
+        let pubkey = Pubkey::new_unique();
+        let rpc = MockRpc::with_lamports(2 * LAMPORTS_PER_SOL);
+        assert_eq!(
+            check_balance(&rpc, &pubkey, 1.5, 0.000005, None).unwrap(),
+            BalanceCheck::Sufficient
+        );
+        // Exactly enough still passes
+        assert_eq!(
+            check_balance(&rpc, &pubkey, 1.999995, 0.000005, None).unwrap(),
+            BalanceCheck::Sufficient
+        );
+    }
+
+    #[test]
+    fn test_balance_check_reports_shortfall() {
+        let pubkey = Pubkey::new_unique();
+        let rpc = MockRpc::with_lamports(LAMPORTS_PER_SOL);
+
+        // The fee counts towards what is needed
+        match check_balance(&rpc, &pubkey, 1.0, 0.5, None).unwrap() {
+            BalanceCheck::Insufficient {
+                asset,
+                available,
+                required,
+                shortfall,
+            } => {
+                assert_eq!(asset, "SOL");
+                assert_eq!(available, 1.0);
+                assert_eq!(required, 1.5);
+                assert_eq!(shortfall, 0.5);
+            }
+            other => panic!("Expected a shortfall, got {:?}", other),
+        }
+
+        // Enough tokens but no SOL left for the fee
+        let mint = Pubkey::new_unique();
+        let rpc = MockRpc {
+            token_accounts: Ok(vec![TokenAccount {
+                address: Pubkey::new_unique(),
+                mint,
+                amount: 5_000_000,
+                decimals: 6,
+            }]),
+            ..MockRpc::with_lamports(0)
+        };
+        let mint = mint.to_string();
+        assert!(matches!(
+            check_balance(&rpc, &pubkey, 5.0, 0.000005, Some(&mint)).unwrap(),
+            BalanceCheck::Insufficient { asset, .. } if asset == "SOL"
+        ));
+        assert!(matches!(
+            check_balance(&rpc, &pubkey, 5.5, 0.0, Some(&mint)).unwrap(),
+            BalanceCheck::Insufficient { asset, shortfall, .. } if asset == mint && shortfall == 0.5
+        ));
+    }
+
+    #[test]
+    fn test_balance_check_rpc_error() {
+        let rpc = MockRpc {
+            lamports: Err(RpcError::Transport("connection refused".to_string())),
+            ..MockRpc::default()
+        };
+        assert!(matches!(
+            check_balance(&rpc, &Pubkey::new_unique(), 1.0, 0.0, None),
+            Err(TransactionError::Rpc(RpcError::Transport(_)))
+        ));
+    }
+
+    #[test]
+    fn test_signed_message_verifies_only_for_signer() {
+        let keypair = Keypair::new();
+        let message = b"I own this wallet";
+        let signature = keypair.sign_message(message);
+
+        assert!(verify_message(&keypair.pubkey(), message, &signature));
+        assert!(!verify_message(&Keypair::new().pubkey(), message, &signature));
+        assert!(!verify_message(&keypair.pubkey(), b"I own that wallet", &signature));
+    }
+
+    #[test]
+    fn test_airdrop_refuses_mainnet() {
+        // The guard fires before the wallet lookup, so no keyring access or
+        // request happens for a wallet that does not even exist
+        let result = request_airdrop("no_such_wallet", 1.0, rpc::DEFAULT_RPC_URL);
+        match result {
+            Err(TransactionError::TransactionFailed(msg)) => assert!(msg.contains("devnet")),
+            other => panic!("Expected the mainnet guard, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_airdrop_waits_for_confirmation() {
+        let pubkey = Pubkey::new_unique();
+        let signature = Signature::from([7; 64]);
+        let rpc = MockRpc {
+            airdrop: Ok(signature),
+            ..MockRpc::default()
+        };
+        assert_eq!(airdrop_to(&rpc, &pubkey, 1.0, Duration::ZERO).unwrap(), signature);
+
+        let failed = MockRpc {
+            signature_status: Ok(SignatureStatus::Failed("faucet dry".to_string())),
+            ..MockRpc::default()
+        };
+        assert!(airdrop_to(&failed, &pubkey, 1.0, Duration::ZERO).is_err());
+
+        // Still pending once the timeout passes
+        let pending = MockRpc {
+            signature_status: Ok(SignatureStatus::Pending),
+            ..MockRpc::default()
+        };
+        assert!(matches!(
+            airdrop_to(&pending, &pubkey, 1.0, Duration::ZERO),
+            Err(TransactionError::TransactionFailed(msg)) if msg.contains("not confirmed")
+        ));
+    }
+
     #[test]
     fn test_estimate_transaction_fees() {
         // Test SOL transfer fee estimation
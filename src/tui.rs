@@ -6,38 +6,523 @@
 use crossterm::{
     event::{
         self, DisableMouseCapture, EnableMouseCapture, Event as CrosstermEvent, KeyCode,
-        KeyEventKind,
+        KeyEventKind, KeyModifiers,
     },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
 use ratatui::{prelude::*, widgets::*};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signer;
+use std::cmp;
+use std::collections::HashSet;
 use std::io::{self, stdout, Stdout};
-use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::thread;
 use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
 
+use crate::config::{self, Config, DisplayConfig, Theme};
+use crate::file_searcher::{self, SearchConfig}; // For scanning the filesystem for key files
+use crate::key_validator; // For explaining why a key file was refused
+use crate::rpc::{self, HttpRpc, SolanaRpc, TxSummary}; // For fetching transaction history
 use crate::secure_storage;
-use crate::wallet_manager; // To interact with wallet data
+use crate::transaction_handler::{self, BatchTransaction, TokenMixingPlan}; // For simulating transfers
+use crate::wallet_manager::{self, WalletManagerError}; // To interact with wallet data
 use crate::vanity_wallet::{self, VanityConfig, VanityStatus}; // For vanity wallet creation
 
 // Solana keypair constants
 const SECRET_KEY_BYTES: usize = 32; // Just the secret key portion
 
+// Text the user has to type before a wallet's secret key is shown
+const REVEAL_CONFIRMATION: &str = "REVEAL";
+
+// Most errors kept for the error log view; older ones are dropped
+const ERROR_LOG_CAPACITY: usize = 200;
+
 // Define different views for the TUI
 enum View {
     WalletList,
     WalletDetail,
     Help,
     AddWallet,
+    ImportSeed,
     ConfirmDelete,
+    ConfirmBatchDelete,
     SearchWallets,
     BatchOperations,
     CreateVanityWallet,
     VanityProgress,
+    RenameWallet,
+    ScanWallets,
+    TokenMixing,
+    VaultRecovery,
+    ExportAddressBook,
+    RevealSecret,
+    Errors,
+    Locked,
+}
+
+// Directories skipped when scanning, since they are large and never hold wallets
+const SCAN_IGNORE_PATTERNS: [&str; 3] = ["**/node_modules/**", "**/.cache/**", "**/.git/**"];
+
+// Number of recent transactions fetched for the detail view
+const HISTORY_LIMIT: usize = 20;
+
+// Vanity searches expected to run longer than this ask for a second Enter
+const VANITY_CONFIRM_SECONDS: f64 = 10.0;
+
+// Longest vanity timeout the form accepts; larger values are clamped to it
+const MAX_VANITY_TIMEOUT_SECONDS: u64 = 86_400;
+
+// Highest account index the seed import form accepts. Wallets rarely use
+// more than a handful, so a larger number is most likely a typo.
+const MAX_SEED_ACCOUNT_INDEX: u32 = 1_000;
+
+// SOL requested by the detail view's airdrop action
+const AIRDROP_SOL: f64 = 1.0;
+
+// How often the title bar's cluster indicator asks the RPC node for its health
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+// Editable fields of the vanity creation form, in Tab order
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum VanityField {
+    WalletName,
+    Prefix,
+    Suffix,
+    Timeout,
+    ThreadCount,
+}
+
+impl VanityField {
+    fn next(self) -> Self {
+        match self {
+            VanityField::WalletName => VanityField::Prefix,
+            VanityField::Prefix => VanityField::Suffix,
+            VanityField::Suffix => VanityField::Timeout,
+            VanityField::Timeout => VanityField::ThreadCount,
+            VanityField::ThreadCount => VanityField::WalletName,
+        }
+    }
+
+    fn prev(self) -> Self {
+        match self {
+            VanityField::WalletName => VanityField::ThreadCount,
+            VanityField::Prefix => VanityField::WalletName,
+            VanityField::Suffix => VanityField::Prefix,
+            VanityField::Timeout => VanityField::Suffix,
+            VanityField::ThreadCount => VanityField::Timeout,
+        }
+    }
+}
+
+// Fields of the batch transfer form, in Tab order
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BatchField {
+    Source,
+    TokenMint,
+    Address(usize), // Recipient row index
+    Amount(usize),
+}
+
+// One recipient line of the batch transfer form, as typed
+#[derive(Debug, Default, PartialEq)]
+struct RecipientRow {
+    address: String,
+    amount: String,
+    address_error: Option<String>, // Set while the typed address is not a valid public key
+}
+
+// State of the batch transfer form
+struct BatchForm {
+    source: usize,                 // Index into App::wallets of the paying wallet
+    token_mint: String,            // Empty for SOL
+    recipients: Vec<RecipientRow>, // Always holds at least one row
+    focus: BatchField,
+    log: Option<String>,           // Output of the last simulation
+    log_scroll: u16,
+    allow_unsafe_recipients: bool, // Set once flagged recipients were reported; Enter again simulates anyway
+}
+
+impl BatchForm {
+    fn new(source: usize) -> Self {
+        BatchForm {
+            source,
+            token_mint: String::new(),
+            recipients: vec![RecipientRow::default()],
+            focus: BatchField::Source,
+            log: None,
+            log_scroll: 0,
+            allow_unsafe_recipients: false,
+        }
+    }
+    
+    // Appends an empty recipient row and focuses its address
+    fn add_recipient(&mut self) {
+        self.allow_unsafe_recipients = false;
+        self.recipients.push(RecipientRow::default());
+        self.focus = BatchField::Address(self.recipients.len() - 1);
+    }
+    
+    // Removes the focused recipient row; the last remaining row is cleared instead
+    fn remove_recipient(&mut self) {
+        let row = match self.focus {
+            BatchField::Address(row) | BatchField::Amount(row) => row,
+            BatchField::Source | BatchField::TokenMint => return,
+        };
+        self.allow_unsafe_recipients = false;
+        if self.recipients.len() == 1 {
+            self.recipients[0] = RecipientRow::default();
+            self.focus = BatchField::Address(0);
+            return;
+        }
+        self.recipients.remove(row);
+        self.focus = BatchField::Address(row.min(self.recipients.len() - 1));
+    }
+    
+    fn focus_next(&mut self) {
+        let last = self.recipients.len() - 1;
+        self.focus = match self.focus {
+            BatchField::Source => BatchField::TokenMint,
+            BatchField::TokenMint => BatchField::Address(0),
+            BatchField::Address(row) => BatchField::Amount(row),
+            BatchField::Amount(row) if row < last => BatchField::Address(row + 1),
+            BatchField::Amount(_) => BatchField::Source,
+        };
+    }
+    
+    fn focus_prev(&mut self) {
+        self.focus = match self.focus {
+            BatchField::Source => BatchField::Amount(self.recipients.len() - 1),
+            BatchField::TokenMint => BatchField::Source,
+            BatchField::Address(0) => BatchField::TokenMint,
+            BatchField::Address(row) => BatchField::Amount(row - 1),
+            BatchField::Amount(row) => BatchField::Address(row),
+        };
+    }
+    
+    // Applies a typed character or Backspace to the focused text field,
+    // revalidating recipient addresses as they change
+    fn edit(&mut self, key_code: KeyCode) {
+        let (field, is_amount) = match self.focus {
+            BatchField::Source => return,
+            BatchField::TokenMint => (&mut self.token_mint, false),
+            BatchField::Address(row) => (&mut self.recipients[row].address, false),
+            BatchField::Amount(row) => (&mut self.recipients[row].amount, true),
+        };
+        self.allow_unsafe_recipients = false;
+        match key_code {
+            KeyCode::Backspace => {
+                field.pop();
+            },
+            KeyCode::Char(c) if is_amount && !(c.is_ascii_digit() || c == '.') => {},
+            KeyCode::Char(c) if !c.is_whitespace() => {
+                field.push(c);
+            },
+            _ => {}
+        }
+        
+        if let BatchField::Address(row) = self.focus {
+            let recipient = &mut self.recipients[row];
+            recipient.address_error = if recipient.address.is_empty() {
+                None
+            } else {
+                transaction_handler::validate_solana_address(&recipient.address)
+                    .err()
+                    .map(|e| e.to_string())
+            };
+        }
+    }
+    
+    // Builds the transaction described by the form, skipping blank rows
+    fn to_transaction(&self, source_wallet: &str) -> Result<BatchTransaction, String> {
+        let token_mint = if self.token_mint.is_empty() {
+            None
+        } else {
+            transaction_handler::validate_solana_address(&self.token_mint)
+                .map_err(|e| e.to_string())?;
+            Some(self.token_mint.clone())
+        };
+        
+        let mut recipients = Vec::new();
+        for (index, row) in self.recipients.iter().enumerate() {
+            if row.address.is_empty() && row.amount.is_empty() {
+                continue;
+            }
+            if let Some(error) = &row.address_error {
+                return Err(format!("Recipient {}: {}", index + 1, error));
+            }
+            let amount = row.amount.parse::<f64>()
+                .map_err(|_| format!("Recipient {}: enter an amount", index + 1))?;
+            recipients.push((row.address.clone(), amount));
+        }
+        if recipients.is_empty() {
+            return Err("Add at least one recipient".to_string());
+        }
+        
+        Ok(BatchTransaction {
+            source_wallet: source_wallet.to_string(),
+            recipients,
+            token_mint,
+            allow_unsafe_recipients: self.allow_unsafe_recipients,
+        })
+    }
+}
+
+// Fields of the token mixing form, in Tab order
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MixingField {
+    Sources,
+    Destinations,
+    TotalAmount,
+    Steps,
+    Seed,
+}
+
+impl MixingField {
+    fn next(self) -> Self {
+        match self {
+            MixingField::Sources => MixingField::Destinations,
+            MixingField::Destinations => MixingField::TotalAmount,
+            MixingField::TotalAmount => MixingField::Steps,
+            MixingField::Steps => MixingField::Seed,
+            MixingField::Seed => MixingField::Sources,
+        }
+    }
+
+    fn prev(self) -> Self {
+        match self {
+            MixingField::Sources => MixingField::Seed,
+            MixingField::Destinations => MixingField::Sources,
+            MixingField::TotalAmount => MixingField::Destinations,
+            MixingField::Steps => MixingField::TotalAmount,
+            MixingField::Seed => MixingField::Steps,
+        }
+    }
+}
+
+// State of the token mixing simulation form
+struct MixingForm {
+    sources: HashSet<usize>,       // Indices into App::wallets of the wallets to mix from
+    source_cursor: usize,          // Highlighted row of the source wallet list
+    destinations: String,          // Comma-separated destination addresses, as typed
+    total_amount: String,
+    steps: String,
+    seed: String,                  // Optional RNG seed; empty for a fresh random plan
+    focus: MixingField,
+    plan: Option<String>,          // Output of the last generated plan
+    plan_scroll: u16,
+}
+
+impl MixingForm {
+    fn new(sources: HashSet<usize>, source_cursor: usize) -> Self {
+        MixingForm {
+            sources,
+            source_cursor,
+            destinations: String::new(),
+            total_amount: String::new(),
+            steps: "5".to_string(),
+            seed: String::new(),
+            focus: MixingField::Sources,
+            plan: None,
+            plan_scroll: 0,
+        }
+    }
+    
+    // Applies a typed character or Backspace to the focused text field
+    fn edit(&mut self, key_code: KeyCode) {
+        let (field, allowed): (&mut String, fn(char) -> bool) = match self.focus {
+            MixingField::Sources => return,
+            MixingField::Destinations => (&mut self.destinations, |c| c == ',' || c == ' ' || c.is_ascii_alphanumeric()),
+            MixingField::TotalAmount => (&mut self.total_amount, |c| c.is_ascii_digit() || c == '.'),
+            MixingField::Steps => (&mut self.steps, |c| c.is_ascii_digit()),
+            MixingField::Seed => (&mut self.seed, |c| c.is_ascii_digit()),
+        };
+        match key_code {
+            KeyCode::Backspace => {
+                field.pop();
+            },
+            KeyCode::Char(c) if allowed(c) => {
+                field.push(c);
+            },
+            _ => {}
+        }
+    }
+    
+    fn destination_list(&self) -> Vec<String> {
+        self.destinations
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+    
+    // Builds the plan described by the form, naming what is missing otherwise
+    fn to_plan(&self, wallets: &[String]) -> Result<TokenMixingPlan, String> {
+        let mut source_indices: Vec<usize> = self.sources.iter()
+            .copied()
+            .filter(|&index| index < wallets.len())
+            .collect();
+        source_indices.sort_unstable();
+        if source_indices.is_empty() {
+            return Err("Select at least one source wallet with Space".to_string());
+        }
+        
+        let destination_addresses = self.destination_list();
+        if destination_addresses.is_empty() {
+            return Err("Enter at least one destination address".to_string());
+        }
+        
+        let total_amount = self.total_amount.parse::<f64>()
+            .map_err(|_| "Enter the total amount to mix".to_string())?;
+        let steps = match self.steps.parse::<usize>() {
+            Ok(steps) if steps > 0 => steps,
+            _ => return Err("Steps must be a whole number above 0".to_string()),
+        };
+        let seed = if self.seed.is_empty() {
+            None
+        } else {
+            Some(self.seed.parse::<u64>().map_err(|_| "Seed must be a whole number".to_string())?)
+        };
+        
+        Ok(TokenMixingPlan {
+            source_wallets: source_indices.into_iter().map(|index| wallets[index].clone()).collect(),
+            destination_addresses,
+            total_amount,
+            steps,
+            token_mint: None,
+            seed,
+            weights: None,
+        })
+    }
+}
+
+// Fields of the seed phrase import form, in Tab order
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SeedField {
+    Phrase,
+    Account,
+    NamePrefix,
+}
+
+impl SeedField {
+    fn next(self) -> Self {
+        match self {
+            SeedField::Phrase => SeedField::Account,
+            SeedField::Account => SeedField::NamePrefix,
+            SeedField::NamePrefix => SeedField::Phrase,
+        }
+    }
+
+    fn prev(self) -> Self {
+        match self {
+            SeedField::Phrase => SeedField::NamePrefix,
+            SeedField::Account => SeedField::Phrase,
+            SeedField::NamePrefix => SeedField::Account,
+        }
+    }
+}
+
+// State of the seed phrase import form
+struct SeedImportForm {
+    phrase: Zeroizing<String>,     // Mnemonic as typed; only ever shown masked
+    account: String,               // Account index, as typed
+    name_prefix: String,           // The wallet is stored as `{name_prefix}_{account}`
+    focus: SeedField,
+    preview: Option<Pubkey>,       // Address of the chosen account; a second Enter imports it
+}
+
+impl SeedImportForm {
+    fn new() -> Self {
+        SeedImportForm {
+            phrase: Zeroizing::new(String::new()),
+            account: "0".to_string(),
+            name_prefix: "seed".to_string(),
+            focus: SeedField::Phrase,
+            preview: None,
+        }
+    }
+    
+    // Applies a typed character or Backspace to the focused field. Any edit
+    // drops the preview, so what is imported is always what was shown.
+    fn edit(&mut self, key_code: KeyCode) {
+        let (field, allowed): (&mut String, fn(char) -> bool) = match self.focus {
+            SeedField::Phrase => (&mut self.phrase, |c| c == ' ' || c.is_ascii_alphabetic()),
+            SeedField::Account => (&mut self.account, |c| c.is_ascii_digit()),
+            SeedField::NamePrefix => (&mut self.name_prefix, |c| c == '_' || c == '-' || c.is_ascii_alphanumeric()),
+        };
+        match key_code {
+            KeyCode::Backspace => {
+                field.pop();
+            },
+            KeyCode::Char(c) if allowed(c) => {
+                field.push(c);
+            },
+            _ => return,
+        }
+        self.preview = None;
+    }
+    
+    // The account index as a number, refusing blanks and implausibly large values
+    fn account_index(&self) -> Result<u32, String> {
+        match self.account.parse::<u32>() {
+            Ok(account) if account <= MAX_SEED_ACCOUNT_INDEX => Ok(account),
+            _ => Err(format!("Account index must be a whole number from 0 to {}", MAX_SEED_ACCOUNT_INDEX)),
+        }
+    }
+}
+
+// Ordering of the wallet list, cycled with 's'
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SortMode {
+    NameAsc,
+    NameDesc,
+    BalanceDesc,
+    CreatedDesc,
+}
+
+impl SortMode {
+    fn next(self) -> Self {
+        match self {
+            SortMode::NameAsc => SortMode::NameDesc,
+            SortMode::NameDesc => SortMode::BalanceDesc,
+            SortMode::BalanceDesc => SortMode::CreatedDesc,
+            SortMode::CreatedDesc => SortMode::NameAsc,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortMode::NameAsc => "Name (A-Z)",
+            SortMode::NameDesc => "Name (Z-A)",
+            SortMode::BalanceDesc => "Balance (highest first)",
+            SortMode::CreatedDesc => "Created (newest first)",
+        }
+    }
+}
+
+// Stages of recovering a wallet file that does not open with the current key
+#[derive(Clone, Debug, PartialEq)]
+enum RecoveryStep {
+    Choose,
+    BackupPath,
+    Passphrase(PathBuf), // Backup file chosen; waiting for its passphrase
+    ConfirmFresh,
+}
+
+// Settings the TUI writes back to config.toml when the user changes them
+#[derive(Clone, Debug, PartialEq)]
+struct SessionSettings {
+    vanity_prefix: String,
+    vanity_timeout_seconds: u64,
+    vanity_threads: usize,
+    rpc_url: String,
 }
 
 // Define possible status messages
@@ -59,6 +544,7 @@ struct App {
     wallets: Vec<String>,           // List of wallet names
     wallet_details: Vec<WalletDetail>, // Details for each wallet
     selected_wallet: Option<usize>, // Index of the selected wallet
+    marked_wallets: HashSet<usize>, // Indices of wallets marked for batch actions
     should_quit: bool,
     current_view: View,
     status_message: Option<StatusMessage>,
@@ -66,38 +552,203 @@ struct App {
     confirm_action: bool,           // For confirmation dialogs
     search_query: String,           // For wallet search functionality
     filtered_wallets: Vec<usize>,   // Indices of wallets matching search
+    sort_mode: SortMode,            // Ordering applied to filtered_wallets
     scroll_offset: usize,           // For scrolling in long lists
+    list_page_size: usize,          // Wallet rows visible in the list, updated on render
     last_refresh: Instant,          // Track when wallet data was last refreshed
+    status_timeout_seconds: u64,    // From general.status_timeout_seconds; 0 keeps messages
+    persist_error_status: bool,     // From general.persist_error_status
+    theme: Theme,                   // Colors for titles, selection and status messages
+    display: DisplayConfig,         // Decimal places and grouping for balances
+    show_lamports: bool,            // Balances shown in lamports rather than SOL; toggled with u
+    settings_path: Option<PathBuf>, // config.toml that changed settings are saved to; None disables saving
+    saved_settings: SessionSettings, // Settings as last loaded or saved
+    auto_refresh_seconds: u64,      // From general.auto_refresh_seconds; 0 disables
+    refresh_rx: Option<mpsc::Receiver<RefreshResult>>, // Pending background refresh, if any
+    rpc_url: String,                // From general.rpc_url
+    tx_selected: usize,             // Selected row in the detail view's transaction list
+    history_rx: Option<mpsc::Receiver<(String, rpc::Result<Vec<TxSummary>>)>>, // Pending history fetch
+    balance_rx: Option<mpsc::Receiver<BalanceUpdate>>, // Balances still arriving from the last refresh
+    selected_balance_rx: Option<mpsc::Receiver<BalanceUpdate>>, // Pending balance of a single-wallet refresh
+    airdrop_rx: Option<mpsc::Receiver<(String, Result<String, String>)>>, // Pending airdrop and its signature
+    cluster_health: Option<rpc::Result<()>>, // Last getHealth answer; None until the first arrives
+    health_rx: Option<mpsc::Receiver<rpc::Result<()>>>, // Pending health check
+    last_health_check: Option<Instant>, // When the last health check started
+    simulation_log_dir: PathBuf,    // Where F2 saves simulation logs: wallet.data_dir/simulations
+    batch_form: BatchForm,          // Batch transfer form state
+    batch_rx: Option<mpsc::Receiver<(usize, Result<String, transaction_handler::TransactionError>)>>, // Pending batch simulation: recipient count and log
+    mixing_form: MixingForm,        // Token mixing form state
+    seed_form: SeedImportForm,      // Seed phrase import form state
+    recovery_step: RecoveryStep,    // Progress through the vault recovery view
     vanity_config: VanityConfig,    // Configuration for vanity wallet generation
     vanity_status: Option<VanityStatus>, // Status of vanity wallet generation
+    vanity_shared_status: Option<Arc<Mutex<VanityStatus>>>, // Status written by the generation thread
     vanity_cancelled: Arc<AtomicBool>, // Flag to cancel vanity generation
+    vanity_paused: Arc<AtomicBool>, // Set while the vanity search is paused
     vanity_wallet_name: String,     // Name for the new vanity wallet
+    vanity_focus: VanityField,      // Focused field in the vanity creation form
+    vanity_timeout_input: String,   // Timeout as typed, applied to vanity_config when valid
+    vanity_threads_input: String,   // Thread count as typed, applied to vanity_config when valid
+    vanity_confirming: bool,        // Estimate shown for a long search; Enter again starts it
     vanity_thread: Option<thread::JoinHandle<()>>, // Handle to vanity generation thread
     vanity_result: Arc<Mutex<Option<solana_sdk::signer::keypair::Keypair>>>, // Result of vanity generation
+    vanity_stats_path: PathBuf,     // Lifetime vanity statistics: wallet.data_dir/vanity_stats.json
+    vanity_lifetime_attempts: u64,  // Attempts recorded for the current prefix before this run
+    vanity_run_recorded: bool,      // Whether the current run was added to the statistics
+    scan_results: Vec<String>,      // Wallet files found by the last filesystem scan
+    scan_selected: usize,           // Index of the selected scan result
+    scan_scanned: Arc<AtomicUsize>, // Files scanned so far by the running scan
+    scan_thread: Option<thread::JoinHandle<()>>, // Handle to the scan thread
+    scan_cancelled: Arc<AtomicBool>, // Flag to stop the running scan
+    scan_outcome: Arc<Mutex<Option<io::Result<Vec<String>>>>>, // Result of the running scan
+    revealed_secret: Option<Zeroizing<Vec<u8>>>, // Secret key bytes, only while the reveal view shows them
+    secret_visible: bool,           // Whether the revealed secret is shown unmasked
+    vault_watcher: Option<VaultWatcher>, // Reports changes to the wallet file; None when disabled
+    error_log: Vec<(Instant, String)>, // Recent errors, oldest first, at most ERROR_LOG_CAPACITY
+    error_log_scroll: usize,        // First row shown in the error log view
+    lock_timeout_minutes: u64,      // From general.lock_timeout_minutes; 0 disables
+    last_input: Instant,            // Last key press, for the idle lock
+    unlock_passphrase: Zeroizing<String>, // Passphrase typed on the lock screen
+    unlock_needs_passphrase: bool,  // Whether the master key is in the SVMAI_PASSPHRASE key file
+    strict_delete: bool,            // From wallet.strict_delete; deleting needs the name typed
+}
+
+// How long the wallet file has to stay quiet before a change is acted on, so
+// a burst of events from one save causes a single reload
+const VAULT_WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+// Watches the directory holding the wallet file and forwards events that
+// touch the file itself
+struct VaultWatcher {
+    _watcher: notify::RecommendedWatcher, // Stops watching when dropped
+    events: mpsc::Receiver<()>,
+    last_event: Option<Instant>,    // Latest event of the burst not yet acted on
+    own_writes: fn() -> u64,        // Counts this process's writes; secure_storage::own_write_generation
+    seen_writes: u64,               // own_writes() when last checked
+}
+
+impl VaultWatcher {
+    fn new(vault_path: &Path) -> notify::Result<Self> {
+        Self::with_write_counter(vault_path, secure_storage::own_write_generation)
+    }
+    
+    fn with_write_counter(vault_path: &Path, own_writes: fn() -> u64) -> notify::Result<Self> {
+        use notify::Watcher;
+        
+        let (tx, events) = mpsc::channel();
+        let file_name = vault_path.file_name().map(|name| name.to_os_string());
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            if let Ok(event) = result {
+                let touches_vault = event.paths.iter().any(|path| path.file_name() == file_name.as_deref());
+                if touches_vault && !matches!(event.kind, notify::EventKind::Access(_)) {
+                    let _ = tx.send(());
+                }
+            }
+        })?;
+        // The directory may not exist until the first wallet is stored
+        let dir = vault_path.parent().unwrap_or(Path::new("."));
+        std::fs::create_dir_all(dir).map_err(notify::Error::io)?;
+        watcher.watch(dir, notify::RecursiveMode::NonRecursive)?;
+        
+        Ok(VaultWatcher {
+            _watcher: watcher,
+            events,
+            last_event: None,
+            own_writes,
+            seen_writes: own_writes(),
+        })
+    }
+    
+    // Whether the wallet file was changed by someone else, once a burst of
+    // events has settled: no event for VAULT_WATCH_DEBOUNCE since the last one
+    fn external_change_settled(&mut self) -> bool {
+        while self.events.try_recv().is_ok() {
+            self.last_event = Some(Instant::now());
+        }
+        match self.last_event {
+            Some(since) if since.elapsed() >= VAULT_WATCH_DEBOUNCE => {
+                self.last_event = None;
+                let own_writes = (self.own_writes)();
+                let external = is_external_change(own_writes, self.seen_writes);
+                self.seen_writes = own_writes;
+                external
+            },
+            _ => false,
+        }
+    }
+}
+
+// A change to the wallet file is someone else's when this process has not
+// written it since the last change was handled
+fn is_external_change(own_writes: u64, seen_writes: u64) -> bool {
+    own_writes == seen_writes
 }
 
 // Wallet detail information
 struct WalletDetail {
     name: String,
     pubkey: Option<Pubkey>,
-    balance: Option<f64>,
-    last_transaction: Option<String>,
+    balance: Option<u64>,           // Lamports; None until fetched
+    balance_error: Option<String>,  // Why the balance could not be fetched
+    transactions: Option<Vec<TxSummary>>, // Recent transactions, None until fetched
+    history_error: Option<String>,  // Why the transaction history could not be fetched
     token_balances: Vec<TokenBalance>, // Added for SPL token balances
+    label: Option<String>,
+    created_at: Option<i64>, // Unix timestamp; None when the import date is unknown
+}
+
+impl WalletDetail {
+    // Whether the balance is still on its way from the RPC node
+    fn balance_pending(&self) -> bool {
+        self.pubkey.is_some() && self.balance.is_none() && self.balance_error.is_none()
+    }
+}
+
+// A wallet's balance in lamports and its token accounts, or why they could not
+// be fetched, sent by the balance thread as each wallet's arrive
+struct BalanceUpdate {
+    name: String,
+    balance: Result<u64, String>,
+    tokens: Result<Vec<rpc::TokenAccount>, String>,
 }
 
+// Wallet names with their details and any per-wallet errors, or the error
+// that prevented listing the wallets at all
+type RefreshResult = Result<(Vec<String>, Vec<WalletDetail>, Vec<String>), secure_storage::SecureStorageError>;
+
 // Structure to hold token balance information
 struct TokenBalance {
-    token_name: String,
     mint_address: String,
-    amount: f64,
+    amount: f64, // In whole tokens
+    token_account: Pubkey, // The account holding the tokens
+}
+
+impl TokenBalance {
+    fn from_account(account: &rpc::TokenAccount) -> Self {
+        TokenBalance {
+            mint_address: account.mint.to_string(),
+            amount: account.ui_amount(),
+            token_account: account.address,
+        }
+    }
 }
 
 impl App {
+    #[cfg(test)]
     fn new() -> Self {
+        Self::with_config(Config::default())
+    }
+    
+    // Builds the initial state, seeding form defaults from the user's settings
+    fn with_config(config: Config) -> Self {
+        let vanity_prefix = config.vanity.default_prefix.clone();
+        let vanity_timeout = config.vanity.timeout_seconds;
+        let vanity_threads = vanity_wallet::configured_thread_count(config.vanity.max_threads);
         App {
             wallets: Vec::new(),
             wallet_details: Vec::new(),
             selected_wallet: None,
+            marked_wallets: HashSet::new(),
             should_quit: false,
             current_view: View::WalletList,
             status_message: None,
@@ -105,33 +756,156 @@ impl App {
             confirm_action: false,
             search_query: String::new(),
             filtered_wallets: Vec::new(),
+            sort_mode: SortMode::NameAsc,
             scroll_offset: 0,
+            list_page_size: 1,
             last_refresh: Instant::now(),
+            status_timeout_seconds: config.general.status_timeout_seconds,
+            persist_error_status: config.general.persist_error_status,
+            theme: config.theme.to_theme(),
+            display: config.display.clone(),
+            show_lamports: false,
+            settings_path: None,
+            saved_settings: SessionSettings {
+                vanity_prefix: vanity_prefix.clone(),
+                vanity_timeout_seconds: vanity_timeout,
+                vanity_threads,
+                rpc_url: config.general.rpc_url.clone(),
+            },
+            auto_refresh_seconds: config.general.auto_refresh_seconds,
+            refresh_rx: None,
+            rpc_url: config.general.rpc_url.clone(),
+            tx_selected: 0,
+            history_rx: None,
+            balance_rx: None,
+            selected_balance_rx: None,
+            airdrop_rx: None,
+            cluster_health: None,
+            health_rx: None,
+            last_health_check: None,
+            simulation_log_dir: PathBuf::from(&config.wallet.data_dir).join("simulations"),
+            batch_form: BatchForm::new(0),
+            batch_rx: None,
+            mixing_form: MixingForm::new(HashSet::new(), 0),
+            seed_form: SeedImportForm::new(),
+            recovery_step: RecoveryStep::Choose,
+            vanity_wallet_name: format!("{}_wallet", vanity_prefix),
             vanity_config: VanityConfig {
-                prefix: "ai".to_string(), // Default prefix as requested
-                timeout_seconds: 120,     // 2 minutes default timeout
-                thread_count: num_cpus::get().min(8), // Use up to 8 threads to avoid excessive CPU usage
+                prefix: vanity_prefix,    // Default prefix from vanity.default_prefix
+                prefixes: Vec::new(),
+                match_mode: vanity_wallet::Match::Prefix,
+                suffix: String::new(),
+                timeout_seconds: vanity_timeout,
+                thread_count: vanity_threads,
                 progress_interval_ms: 250, // More frequent updates for responsive UI
             },
             vanity_status: None,
+            vanity_shared_status: None,
             vanity_cancelled: Arc::new(AtomicBool::new(false)),
-            vanity_wallet_name: "ai_wallet".to_string(),
+            vanity_paused: Arc::new(AtomicBool::new(false)),
+            vanity_focus: VanityField::WalletName,
+            vanity_timeout_input: String::new(),
+            vanity_threads_input: String::new(),
+            vanity_confirming: false,
             vanity_thread: None,
             vanity_result: Arc::new(Mutex::new(None)),
+            vanity_stats_path: vanity_wallet::vanity_stats_path(Path::new(&config.wallet.data_dir)),
+            vanity_lifetime_attempts: 0,
+            vanity_run_recorded: true,
+            scan_results: Vec::new(),
+            scan_selected: 0,
+            scan_scanned: Arc::new(AtomicUsize::new(0)),
+            scan_thread: None,
+            scan_cancelled: Arc::new(AtomicBool::new(false)),
+            scan_outcome: Arc::new(Mutex::new(None)),
+            revealed_secret: None,
+            secret_visible: false,
+            vault_watcher: None,
+            error_log: Vec::new(),
+            error_log_scroll: 0,
+            lock_timeout_minutes: config.general.lock_timeout_minutes,
+            last_input: Instant::now(),
+            unlock_passphrase: Zeroizing::new(String::new()),
+            unlock_needs_passphrase: false,
+            strict_delete: config.wallet.strict_delete,
         }
     }
 
     fn load_wallets(&mut self) {
-        match secure_storage::list_wallet_names() {
-            Ok(names) => {
+        // A synchronous load supersedes any background refresh still running
+        self.refresh_rx = None;
+        let result = secure_storage::list_wallet_names()
+            .map(|names| {
+                let (details, errors) = fetch_wallet_details(&names);
+                (names, details, errors)
+            });
+        self.apply_refresh(result);
+    }
+    
+    // Stores freshly loaded wallets, keeping the selected wallet by name
+    fn apply_refresh(&mut self, result: RefreshResult) {
+        let selected_name = self.selected_wallet
+            .and_then(|index| self.wallets.get(index).cloned());
+        let marked_names: Vec<String> = self.marked_wallets.iter()
+            .filter_map(|&index| self.wallets.get(index).cloned())
+            .collect();
+        self.marked_wallets.clear();
+        
+        match result {
+            Ok((names, details, error_messages)) => {
                 self.wallets = names;
-                self.load_wallet_details();
+                self.wallet_details = details;
                 self.last_refresh = Instant::now();
+                self.start_balance_fetch();
+                // Marks follow their wallets by name; removed wallets drop out
+                self.marked_wallets = self.wallets.iter()
+                    .enumerate()
+                    .filter(|(_, name)| marked_names.contains(name))
+                    .map(|(index, _)| index)
+                    .collect();
+                // A wallet that disappeared hands the selection to its neighbour
+                let previous_index = self.selected_wallet
+                    .map(|index| index.min(self.wallets.len().saturating_sub(1)))
+                    .filter(|_| !self.wallets.is_empty());
+                self.selected_wallet = selected_name
+                    .and_then(|name| self.wallets.iter().position(|n| *n == name))
+                    .or(previous_index);
                 self.update_filtered_wallets();
+                
+                // Keep each error in the log and point to it from the status bar
+                if !error_messages.is_empty() {
+                    let count = error_messages.len();
+                    for message in error_messages {
+                        self.log_error(message);
+                    }
+                    self.set_status(
+                        format!("Errors loading details for {} wallet(s). Press E to see them", count), 
+                        StatusType::Error
+                    );
+                }
+            },
+            Err(secure_storage::SecureStorageError::Tampered(_)) => {
+                self.set_status(
+                    "Wallet file integrity check failed — it may have been modified.".to_string(),
+                    StatusType::Error,
+                );
+                self.clear_wallet_list();
+            },
+            Err(secure_storage::SecureStorageError::KeyMismatch(_)) => {
+                self.set_status(
+                    "Wallet file was encrypted with a different key — restore a backup or start fresh.".to_string(),
+                    StatusType::Error,
+                );
+                self.clear_wallet_list();
+                if !matches!(self.current_view, View::VaultRecovery) {
+                    self.current_view = View::VaultRecovery;
+                    self.recovery_step = RecoveryStep::Choose;
+                    self.input_buffer.clear();
+                }
             },
             Err(e) => {
                 self.set_status(format!("Error loading wallets: {}", e), StatusType::Error);
-                self.wallets = Vec::new();
+                self.clear_wallet_list();
             }
         }
         
@@ -140,120 +914,477 @@ impl App {
         }
     }
     
-    fn load_wallet_details(&mut self) {
-        self.wallet_details.clear();
-        let mut error_messages = Vec::new();
-        
-        for wallet_name in &self.wallets.clone() {
-            let mut detail = WalletDetail {
-                name: wallet_name.clone(),
-                pubkey: None,
-                balance: None,
-                last_transaction: None,
-                token_balances: Vec::new(), // Initialize empty token balances
-            };
-            
-            // Try to get the keypair to extract public key
-            match wallet_manager::get_wallet_keypair(wallet_name) {
-                Ok(Some(keypair)) => {
-                    detail.pubkey = Some(keypair.pubkey());
-                    // In a real implementation, we would fetch balance and transaction history
-                    // For now, we'll use placeholder values
-                    detail.balance = Some(0.0); // Placeholder
-                    detail.last_transaction = Some("No transactions yet".to_string());
-                    
-                    // Add some example token balances for demonstration
-                    detail.token_balances.push(TokenBalance {
-                        token_name: "USDC".to_string(),
-                        mint_address: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
-                        amount: 100.0,
-                    });
-                    detail.token_balances.push(TokenBalance {
-                        token_name: "RAY".to_string(),
-                        mint_address: "4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R".to_string(),
-                        amount: 25.5,
-                    });
-                },
-                Ok(None) => {
-                    // Wallet exists but couldn't get keypair
-                    detail.pubkey = None;
-                },
-                Err(e) => {
-                    error_messages.push(format!("Error loading wallet details for {}: {}", wallet_name, e));
-                }
-            }
-            
-            self.wallet_details.push(detail);
+    // Empties the wallet list after a failed reload. Everything indexing into
+    // it goes too, so nothing points past the end of the now-empty list.
+    fn clear_wallet_list(&mut self) {
+        self.wallets = Vec::new();
+        self.wallet_details = Vec::new();
+        self.marked_wallets.clear();
+        self.selected_wallet = None;
+        self.update_filtered_wallets();
+    }
+    
+    // Reloads wallets on a background thread so the UI stays responsive
+    fn start_background_refresh(&mut self) {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let result = secure_storage::list_wallet_names()
+                .map(|names| {
+                    let (details, errors) = fetch_wallet_details(&names);
+                    (names, details, errors)
+                });
+            // The receiver is gone if a manual refresh superseded this one
+            let _ = tx.send(result);
+        });
+        self.refresh_rx = Some(rx);
+    }
+    
+    // Fetches the SOL balance of every loaded wallet on a background thread,
+    // sending each one as it arrives so the list fills in without blocking input
+    fn start_balance_fetch(&mut self) {
+        let wallets: Vec<(String, Pubkey)> = self.wallet_details.iter()
+            .filter_map(|detail| detail.pubkey.map(|pubkey| (detail.name.clone(), pubkey)))
+            .collect();
+        if wallets.is_empty() {
+            self.balance_rx = None;
+            return;
         }
         
-        // Set status message if there were errors
-        if !error_messages.is_empty() {
-            self.set_status(
-                format!("Errors loading some wallet details: {}", error_messages.join("; ")), 
-                StatusType::Error
-            );
-        }
+        // Dropping the previous receiver stops its thread at the next send
+        self.balance_rx = Some(spawn_balance_fetch(self.rpc_url.clone(), wallets));
     }
     
-    fn set_status(&mut self, message: String, status_type: StatusType) {
-        self.status_message = Some(StatusMessage {
-            message,
-            status_type,
-            timestamp: Instant::now(),
-        });
+    // Re-fetches the balance of the selected wallet only, leaving every other
+    // entry as it is; much quicker than a full refresh with many wallets
+    fn refresh_selected_balance(&mut self) {
+        let Some(detail) = self.selected_wallet.and_then(|i| self.wallet_details.get_mut(i)) else {
+            return;
+        };
+        let Some(pubkey) = detail.pubkey else {
+            self.set_status("This wallet has no valid address to refresh".to_string(), StatusType::Warning);
+            return;
+        };
+        
+        detail.balance = None;
+        detail.balance_error = None;
+        let name = detail.name.clone();
+        self.selected_balance_rx = Some(spawn_balance_fetch(self.rpc_url.clone(), vec![(name.clone(), pubkey)]));
+        self.set_status(format!("Refreshing balance of '{}'", name), StatusType::Info);
     }
     
-    fn clear_status_if_expired(&mut self) {
-        if let Some(status) = &self.status_message {
-            if status.timestamp.elapsed() > Duration::from_secs(5) {
-                self.status_message = None;
-            }
+    // A balance in the unit picked with `u`; SOL uses `sol_decimals` places
+    fn format_balance(&self, lamports: u64, sol_decimals: usize) -> String {
+        if self.show_lamports {
+            format!("{} lamports", self.display.format_lamports(lamports))
+        } else {
+            format!(
+                "{} SOL",
+                config::format_lamports_as_sol(lamports, sol_decimals, self.display.thousands_separator)
+            )
         }
     }
     
-    fn add_wallet(&mut self, file_path: String) {
-        if file_path.is_empty() {
-            self.set_status("Please enter a valid file path".to_string(), StatusType::Warning);
+    // Applies every balance that has arrived since the last frame
+    fn poll_balance_updates(&mut self) {
+        drain_balance_updates(&mut self.balance_rx, &mut self.wallet_details);
+        drain_balance_updates(&mut self.selected_balance_rx, &mut self.wallet_details);
+    }
+    
+    // Fetches the selected wallet's transaction history in the background
+    // unless it is already loaded, failed, or being fetched
+    fn ensure_transaction_history(&mut self) {
+        if self.history_rx.is_some() {
             return;
         }
-        
-        // Generate a wallet name from the file path
-        let file_name = match std::path::Path::new(&file_path).file_stem() {
-            Some(stem) => stem.to_string_lossy().to_string(),
-            None => "new_wallet".to_string(),
+        let Some(detail) = self.selected_wallet.and_then(|i| self.wallet_details.get(i)) else {
+            return;
+        };
+        let Some(pubkey) = detail.pubkey else {
+            return;
         };
+        if detail.transactions.is_some() || detail.history_error.is_some() {
+            return;
+        }
         
-        match wallet_manager::add_wallet_from_file(&file_name, &file_path) {
-            Ok(_) => {
-                self.set_status(format!("Wallet '{}' added successfully", file_name), StatusType::Success);
-                self.load_wallets(); // Refresh wallet list
-                self.current_view = View::WalletList;
-                self.input_buffer.clear();
+        let (tx, rx) = mpsc::channel();
+        let name = detail.name.clone();
+        let rpc_url = self.rpc_url.clone();
+        thread::spawn(move || {
+            let _ = tx.send((name, HttpRpc::new(rpc_url).get_recent_signatures(&pubkey, HISTORY_LIMIT)));
+        });
+        self.history_rx = Some(rx);
+    }
+    
+    fn poll_transaction_history(&mut self) {
+        let received = match &self.history_rx {
+            Some(rx) => rx.try_recv(),
+            None => return,
+        };
+        match received {
+            Ok((name, result)) => {
+                self.history_rx = None;
+                // The wallet may have been renamed or removed meanwhile
+                if let Some(detail) = self.wallet_details.iter_mut().find(|d| d.name == name) {
+                    match result {
+                        Ok(transactions) => detail.transactions = Some(transactions),
+                        Err(e) => detail.history_error = Some(e.to_string()),
+                    }
+                }
             },
-            Err(e) => {
-                // Provide more helpful error message with suggestions
-                let error_msg = if e.to_string().contains("not found") || e.to_string().contains("No such file") {
-                    format!("Failed to add wallet: File not found. Please check the file path and try again. Error: {}", e)
-                } else if e.to_string().contains("permission") {
-                    format!("Failed to add wallet: Permission denied. Please check file permissions. Error: {}", e)
-                } else if e.to_string().contains("not a valid") || e.to_string().contains("Invalid") {
-                    format!("Failed to add wallet: Invalid wallet file format. Please ensure it's a valid Solana wallet JSON file. Error: {}", e)
-                } else {
-                    format!("Failed to add wallet: {}. Press 'h' for help or try a different file.", e)
-                };
-                self.set_status(error_msg, StatusType::Error);
-            }
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.history_rx = None;
+            },
+            Err(mpsc::TryRecvError::Empty) => {}
         }
     }
     
-    fn remove_selected_wallet(&mut self) {
-        if let Some(selected) = self.selected_wallet {
-            if selected < self.wallets.len() {
-                let wallet_name = &self.wallets[selected];
-                match wallet_manager::remove_wallet(wallet_name) {
-                    Ok(_) => {
-                        self.set_status(format!("Wallet '{}' removed successfully", wallet_name), StatusType::Success);
-                        self.load_wallets(); // Refresh wallet list
+    // Requests an airdrop for the selected wallet in the background; only
+    // offered off mainnet, where a faucet exists
+    fn request_airdrop(&mut self) {
+        if rpc::is_mainnet_url(&self.rpc_url) {
+            self.set_status(
+                "Airdrops need a devnet or testnet RPC URL (general.rpc_url or SVMAI_RPC_URL)".to_string(),
+                StatusType::Warning,
+            );
+            return;
+        }
+        if self.airdrop_rx.is_some() {
+            self.set_status("An airdrop is already in progress".to_string(), StatusType::Warning);
+            return;
+        }
+        let Some(name) = self.selected_wallet.and_then(|i| self.wallets.get(i)).cloned() else {
+            return;
+        };
+        
+        let (tx, rx) = mpsc::channel();
+        let rpc_url = self.rpc_url.clone();
+        self.set_status(format!("Requesting {} SOL for '{}'…", AIRDROP_SOL, name), StatusType::Info);
+        thread::spawn(move || {
+            let result = transaction_handler::request_airdrop(&name, AIRDROP_SOL, &rpc_url)
+                .map(|signature| signature.to_string())
+                .map_err(|e| e.to_string());
+            let _ = tx.send((name, result));
+        });
+        self.airdrop_rx = Some(rx);
+    }
+    
+    fn poll_airdrop(&mut self) {
+        let received = match &self.airdrop_rx {
+            Some(rx) => rx.try_recv(),
+            None => return,
+        };
+        match received {
+            Ok((name, result)) => {
+                self.airdrop_rx = None;
+                match result {
+                    Ok(signature) => {
+                        self.set_status(format!("Airdropped {} SOL to '{}': {}", AIRDROP_SOL, name, signature), StatusType::Success);
+                        // Show the airdrop in the transaction list
+                        if let Some(detail) = self.wallet_details.iter_mut().find(|d| d.name == name) {
+                            detail.transactions = None;
+                            detail.history_error = None;
+                        }
+                    },
+                    Err(e) => self.set_status(format!("Airdrop to '{}' failed: {}", name, e), StatusType::Error),
+                }
+            },
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.airdrop_rx = None;
+            },
+            Err(mpsc::TryRecvError::Empty) => {}
+        }
+    }
+    
+    // Collects a finished health check and starts the next one when due, so
+    // rendering only ever reads the cached answer
+    fn poll_cluster_health(&mut self) {
+        if let Some(rx) = &self.health_rx {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.health_rx = None;
+                    self.cluster_health = Some(result);
+                },
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.health_rx = None;
+                },
+                Err(mpsc::TryRecvError::Empty) => return,
+            }
+        }
+        
+        let due = self.last_health_check.is_none_or(|at| at.elapsed() >= HEALTH_CHECK_INTERVAL);
+        if !due {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        let rpc_url = self.rpc_url.clone();
+        thread::spawn(move || {
+            let _ = tx.send(HttpRpc::new(rpc_url).get_health());
+        });
+        self.health_rx = Some(rx);
+        self.last_health_check = Some(Instant::now());
+    }
+    
+    fn poll_vault_watcher(&mut self) {
+        let changed = self.vault_watcher.as_mut().is_some_and(VaultWatcher::external_change_settled);
+        if changed {
+            self.set_status("Wallet file changed outside svmai, reloading wallets".to_string(), StatusType::Info);
+            self.start_background_refresh();
+        }
+    }
+    
+    fn select_transaction(&mut self, forward: bool) {
+        let count = self.selected_wallet
+            .and_then(|i| self.wallet_details.get(i))
+            .and_then(|detail| detail.transactions.as_ref())
+            .map_or(0, Vec::len);
+        self.tx_selected = if forward {
+            (self.tx_selected + 1).min(count.saturating_sub(1))
+        } else {
+            self.tx_selected.saturating_sub(1)
+        };
+    }
+    
+    fn poll_background_refresh(&mut self) {
+        let received = match &self.refresh_rx {
+            Some(rx) => rx.try_recv(),
+            None => return,
+        };
+        match received {
+            Ok(result) => {
+                self.refresh_rx = None;
+                self.apply_refresh(result);
+            },
+            Err(mpsc::TryRecvError::Disconnected) => {
+                // The refresh thread died without reporting; try again next interval
+                self.refresh_rx = None;
+                self.last_refresh = Instant::now();
+            },
+            Err(mpsc::TryRecvError::Empty) => {}
+        }
+    }
+    
+    fn set_status(&mut self, message: String, status_type: StatusType) {
+        if let StatusType::Error = status_type {
+            self.log_error(message.clone());
+        }
+        self.status_message = Some(StatusMessage {
+            message,
+            status_type,
+            timestamp: Instant::now(),
+        });
+    }
+    
+    // Adds an error to the log, dropping the oldest entries beyond the cap
+    fn log_error(&mut self, message: String) {
+        self.error_log.push((Instant::now(), message));
+        if self.error_log.len() > ERROR_LOG_CAPACITY {
+            let excess = self.error_log.len() - ERROR_LOG_CAPACITY;
+            self.error_log.drain(..excess);
+        }
+    }
+    
+    fn clear_status_if_expired(&mut self) {
+        if let Some(status) = &self.status_message {
+            if status_expired(
+                &status.status_type,
+                status.timestamp.elapsed(),
+                self.status_timeout_seconds,
+                self.persist_error_status,
+            ) {
+                self.status_message = None;
+            }
+        }
+    }
+    
+    fn add_wallet(&mut self, file_path: String) {
+        if file_path.is_empty() {
+            self.set_status("Please enter a valid file path".to_string(), StatusType::Warning);
+            return;
+        }
+        
+        // Generate a wallet name from the file path
+        let file_name = key_validator::key_file_stem(&file_path).unwrap_or_else(|| "new_wallet".to_string());
+        
+        match wallet_manager::add_wallet_from_file(&file_name, &file_path) {
+            Ok(wallet_name) => {
+                self.set_status(format!("Wallet '{}' added successfully", wallet_name), StatusType::Success);
+                self.load_wallets(); // Refresh wallet list
+                self.current_view = View::WalletList;
+                self.input_buffer.clear();
+            },
+            Err(WalletManagerError::AlreadyExists(msg)) => {
+                self.set_status(format!("Wallet not added: {}", msg), StatusType::Warning);
+            },
+            Err(WalletManagerError::InvalidKey(_)) if matches!(key_validator::is_solana_wallet_json_file(&file_path), Ok(false)) => {
+                self.set_status(
+                    format!("Failed to add wallet: {}", key_validator::describe_key_file(&file_path)),
+                    StatusType::Error,
+                );
+            },
+            Err(e) => {
+                // Provide more helpful error message with suggestions
+                let error_msg = match &e {
+                    WalletManagerError::NotFound(_) => {
+                        format!("Failed to add wallet: File not found. Please check the file path and try again. Error: {}", e)
+                    },
+                    WalletManagerError::Io(io_error) if io_error.kind() == io::ErrorKind::PermissionDenied => {
+                        format!("Failed to add wallet: Permission denied. Please check file permissions. Error: {}", e)
+                    },
+                    WalletManagerError::InvalidKey(_) => {
+                        format!("Failed to add wallet: Invalid wallet file format. Please ensure it's a valid Solana wallet JSON file. Error: {}", e)
+                    },
+                    WalletManagerError::Storage(_) => {
+                        format!("Failed to add wallet: Could not save it to the encrypted vault. Error: {}", e)
+                    },
+                    _ => format!("Failed to add wallet: {}. Press 'h' for help or try a different file.", e),
+                };
+                self.set_status(error_msg, StatusType::Error);
+            }
+        }
+    }
+    
+    // Writes the public address book to the path typed by the user
+    fn export_address_book(&mut self, dest_path: String) {
+        if dest_path.trim().is_empty() {
+            self.set_status("Please enter a destination file".to_string(), StatusType::Warning);
+            return;
+        }
+        
+        match wallet_manager::export_address_book(Path::new(dest_path.trim())) {
+            Ok(()) => {
+                self.set_status(format!("Address book exported to {}", dest_path.trim()), StatusType::Success);
+                self.current_view = View::WalletList;
+                self.input_buffer.clear();
+            },
+            Err(e) => {
+                self.set_status(format!("Failed to export address book: {}", e), StatusType::Error);
+            }
+        }
+    }
+    
+    fn open_reveal_secret(&mut self) {
+        if self.selected_wallet.is_none_or(|selected| selected >= self.wallets.len()) {
+            return;
+        }
+        self.clear_revealed_secret();
+        self.input_buffer.clear();
+        self.current_view = View::RevealSecret;
+    }
+    
+    // Loads the selected wallet's secret once the confirmation has been typed
+    fn reveal_selected_secret(&mut self) {
+        if self.input_buffer != REVEAL_CONFIRMATION {
+            self.set_status(format!("Type {} to show the secret key", REVEAL_CONFIRMATION), StatusType::Warning);
+            return;
+        }
+        self.input_buffer.clear();
+        
+        let Some(wallet_name) = self.selected_wallet.and_then(|selected| self.wallets.get(selected)).cloned() else {
+            return;
+        };
+        match secure_storage::retrieve_private_key(&wallet_name) {
+            Ok(Some(key_bytes)) => {
+                self.revealed_secret = Some(key_bytes);
+                self.secret_visible = false;
+            },
+            Ok(None) => {
+                self.set_status(format!("Wallet '{}' has no stored key", wallet_name), StatusType::Error);
+            },
+            Err(e) => {
+                self.set_status(format!("Failed to read secret key: {}", e), StatusType::Error);
+            }
+        }
+    }
+    
+    fn copy_revealed_secret(&mut self) {
+        let Some(key_bytes) = &self.revealed_secret else {
+            return;
+        };
+        let text = format_secret_bytes(key_bytes);
+        match execute!(stdout(), crossterm::clipboard::CopyToClipboard::to_clipboard_from(text.as_bytes())) {
+            Ok(()) => self.set_status(
+                "Secret key copied. Anyone who can read your clipboard can take this wallet's funds; clear it once it is backed up".to_string(),
+                StatusType::Warning
+            ),
+            Err(e) => self.set_status(format!("Failed to copy secret key: {}", e), StatusType::Error),
+        }
+    }
+    
+    // Dropping the revealed secret wipes it
+    fn clear_revealed_secret(&mut self) {
+        self.revealed_secret = None;
+        self.secret_visible = false;
+    }
+    
+    // Locks the TUI once no key has been pressed for lock_timeout_minutes.
+    // A running vanity search is left alone, since it is expected to sit idle.
+    fn lock_if_idle(&mut self) {
+        let locked = matches!(self.current_view, View::Locked);
+        // A running search is left to finish and save its wallet; a paused one is not running
+        let searching = self.vanity_thread.as_ref().is_some_and(|thread| !thread.is_finished())
+            && !self.vanity_paused.load(Ordering::SeqCst);
+        if !searching && lock_due(self.last_input.elapsed(), self.lock_timeout_minutes, locked) {
+            self.lock();
+        }
+    }
+    
+    // Forgets everything loaded from the wallet file and shows only the lock screen
+    fn lock(&mut self) {
+        self.clear_revealed_secret();
+        self.wallets.clear();
+        self.wallet_details.clear();
+        self.filtered_wallets.clear();
+        self.marked_wallets.clear();
+        self.selected_wallet = None;
+        self.input_buffer.clear();
+        self.search_query.clear();
+        self.refresh_rx = None;
+        self.history_rx = None;
+        self.balance_rx = None;
+        self.selected_balance_rx = None;
+        // Forms can hold a seed phrase or name wallets and addresses
+        self.seed_form = SeedImportForm::new();
+        self.batch_form = BatchForm::new(0);
+        self.mixing_form = MixingForm::new(HashSet::new(), 0);
+        self.unlock_passphrase = Zeroizing::new(String::new());
+        self.unlock_needs_passphrase = secure_storage::active_master_key_store()
+            .is_ok_and(|source| source.key_file.is_some());
+        self.current_view = View::Locked;
+        self.set_status(
+            format!("Locked after {} minute(s) without input", self.lock_timeout_minutes),
+            StatusType::Info,
+        );
+    }
+    
+    // Unlocks only once access to the master key is proven again, so an idle
+    // terminal cannot simply be reopened with Enter
+    fn unlock(&mut self) {
+        let result = secure_storage::reauthenticate(&self.unlock_passphrase);
+        self.unlock_passphrase = Zeroizing::new(String::new());
+        if let Err(e) = result {
+            self.set_status(format!("Unlock failed: {}", e), StatusType::Error);
+            return;
+        }
+        let searching = self.vanity_thread.as_ref().is_some_and(|thread| !thread.is_finished());
+        self.current_view = if searching { View::VanityProgress } else { View::WalletList };
+        self.status_message = None;
+        self.load_wallets();
+    }
+    
+    fn leave_reveal_secret(&mut self) {
+        self.clear_revealed_secret();
+        self.input_buffer.clear();
+        self.current_view = View::WalletDetail;
+    }
+    
+    fn remove_selected_wallet(&mut self) {
+        if let Some(selected) = self.selected_wallet {
+            if selected < self.wallets.len() {
+                let wallet_name = &self.wallets[selected];
+                match wallet_manager::remove_wallet(wallet_name) {
+                    Ok(_) => {
+                        self.set_status(format!("Wallet '{}' removed successfully", wallet_name), StatusType::Success);
+                        self.load_wallets(); // Refresh wallet list
                         
                         // Adjust selected index if needed
                         if self.wallets.is_empty() {
@@ -263,12 +1394,14 @@ impl App {
                         }
                     },
                     Err(e) => {
-                        let error_msg = if e.to_string().contains("not found") {
-                            format!("Failed to remove wallet '{}': Wallet not found in storage. It may have been already removed.", wallet_name)
-                        } else if e.to_string().contains("permission") {
-                            format!("Failed to remove wallet '{}': Permission denied. Please check system permissions.", wallet_name)
-                        } else {
-                            format!("Failed to remove wallet '{}': {}. Please try again or restart the application.", wallet_name, e)
+                        let error_msg = match &e {
+                            WalletManagerError::NotFound(_) => {
+                                format!("Failed to remove wallet '{}': Wallet not found in storage. It may have been already removed.", wallet_name)
+                            },
+                            _ if e.kind() == io::ErrorKind::PermissionDenied => {
+                                format!("Failed to remove wallet '{}': Permission denied. Please check system permissions.", wallet_name)
+                            },
+                            _ => format!("Failed to remove wallet '{}': {}. Please try again or restart the application.", wallet_name, e),
                         };
                         self.set_status(error_msg, StatusType::Error);
                     }
@@ -279,22 +1412,205 @@ impl App {
         self.confirm_action = false;
     }
     
-    fn update_filtered_wallets(&mut self) {
-        if self.search_query.is_empty() {
-            // If no search query, include all wallets
-            self.filtered_wallets = (0..self.wallets.len()).collect();
+    // Marks or unmarks the selected wallet for batch actions
+    fn toggle_wallet_mark(&mut self) {
+        if let Some(selected) = self.selected_wallet.filter(|&i| i < self.wallets.len()) {
+            if !self.marked_wallets.remove(&selected) {
+                self.marked_wallets.insert(selected);
+            }
+        }
+    }
+    
+    // Removes every marked wallet, keeping the ones that failed marked
+    fn remove_marked_wallets(&mut self) {
+        let mut indices: Vec<usize> = self.marked_wallets.iter().copied().collect();
+        indices.sort_unstable();
+        let names: Vec<String> = indices.iter()
+            .filter_map(|&index| self.wallets.get(index).cloned())
+            .collect();
+        
+        let outcome = remove_wallets(&names, wallet_manager::remove_wallet);
+        self.marked_wallets = indices.into_iter()
+            .filter(|&index| {
+                self.wallets.get(index)
+                    .is_some_and(|wallet| outcome.failed.iter().any(|(name, _)| name == wallet))
+            })
+            .collect();
+        if outcome.removed > 0 {
+            self.load_wallets(); // Refresh wallet list
+        }
+        
+        if outcome.failed.is_empty() {
+            self.set_status(format!("Removed {} wallet(s)", outcome.removed), StatusType::Success);
         } else {
-            // Filter wallets based on search query (case-insensitive)
-            let query = self.search_query.to_lowercase();
-            self.filtered_wallets = self.wallets.iter()
-                .enumerate()
-                .filter(|(_, name)| name.to_lowercase().contains(&query))
-                .map(|(i, _)| i)
+            let failures: Vec<String> = outcome.failed.iter()
+                .map(|(name, error)| format!("{} ({})", name, error))
                 .collect();
+            self.set_status(
+                format!("Removed {} of {} wallet(s); failed: {}", outcome.removed, names.len(), failures.join("; ")),
+                StatusType::Error,
+            );
         }
-        
-        // Reset selection if current selection is not in filtered list
+        self.current_view = View::WalletList;
+        self.confirm_action = false;
+    }
+    
+    // Carries out whichever deletion the confirmation dialog is asking about
+    fn confirm_delete(&mut self) {
+        self.input_buffer.clear();
+        if matches!(self.current_view, View::ConfirmBatchDelete) {
+            self.remove_marked_wallets();
+        } else {
+            self.remove_selected_wallet();
+        }
+    }
+    
+    // Opens the delete dialog with "No" highlighted and nothing typed
+    fn open_delete_confirmation(&mut self, view: View) {
+        self.current_view = view;
+        self.confirm_action = false;
+        self.input_buffer.clear();
+    }
+    
+    // Text that has to be typed in strict delete mode: the wallet's name, or
+    // "delete N" when removing N marked wallets at once
+    fn delete_confirmation_text(&self) -> String {
+        if matches!(self.current_view, View::ConfirmBatchDelete) {
+            format!("delete {}", self.marked_wallets.len())
+        } else {
+            self.selected_wallet
+                .and_then(|selected| self.wallets.get(selected))
+                .cloned()
+                .unwrap_or_default()
+        }
+    }
+    
+    // Whether the typed text allows the deletion to go ahead. Compared
+    // exactly, so a wallet called "Main" is not removed by typing "main".
+    fn delete_confirmation_matches(&self) -> bool {
+        let expected = self.delete_confirmation_text();
+        !expected.is_empty() && self.input_buffer == expected
+    }
+    
+    fn rename_selected_wallet(&mut self, new_name: String) {
+        let new_name = new_name.trim().to_string();
+        if new_name.is_empty() {
+            self.set_status("Please enter a new wallet name".to_string(), StatusType::Warning);
+            return;
+        }
+
         if let Some(selected) = self.selected_wallet {
+            if selected < self.wallets.len() {
+                let old_name = self.wallets[selected].clone();
+                if old_name == new_name {
+                    self.current_view = View::WalletDetail;
+                    self.input_buffer.clear();
+                    return;
+                }
+
+                match wallet_manager::rename_wallet(&old_name, &new_name) {
+                    Ok(_) => {
+                        self.set_status(format!("Wallet '{}' renamed to '{}'", old_name, new_name), StatusType::Success);
+                        self.load_wallets(); // Refresh wallet list
+
+                        // Keep the renamed wallet selected
+                        self.selected_wallet = self.wallets.iter().position(|name| *name == new_name);
+                        self.current_view = View::WalletDetail;
+                        self.input_buffer.clear();
+                    },
+                    Err(e) => {
+                        let error_msg = match e {
+                            WalletManagerError::AlreadyExists(_) => {
+                                format!("Failed to rename wallet: a wallet named '{}' already exists.", new_name)
+                            },
+                            WalletManagerError::NotFound(_) => {
+                                format!("Failed to rename wallet '{}': it is no longer in storage.", old_name)
+                            },
+                            e => format!("Failed to rename wallet '{}': {}", old_name, e),
+                        };
+                        self.set_status(error_msg, StatusType::Error);
+                    }
+                }
+            }
+        }
+    }
+    
+    // Replaces the unreadable vault with the wallets from a vault backup
+    fn restore_vault_backup(&mut self, backup_path: &Path, passphrase: &str) {
+        match secure_storage::import_vault(backup_path, passphrase) {
+            Ok(()) => {
+                self.current_view = View::WalletList;
+                self.recovery_step = RecoveryStep::Choose;
+                self.load_wallets();
+                self.set_status(format!("Restored {} wallets from backup", self.wallets.len()), StatusType::Success);
+            },
+            Err(e) => {
+                self.set_status(format!("Restore failed: {}", e), StatusType::Error);
+            }
+        }
+        self.input_buffer.clear();
+    }
+    
+    // Moves the unreadable vault aside so new wallets go into an empty one
+    fn start_fresh_vault(&mut self) {
+        match secure_storage::set_aside_vault() {
+            Ok(aside_path) => {
+                self.current_view = View::WalletList;
+                self.recovery_step = RecoveryStep::Choose;
+                self.load_wallets();
+                self.set_status(
+                    format!("Started an empty vault; the old file was kept at {}", aside_path.display()),
+                    StatusType::Success,
+                );
+            },
+            Err(e) => {
+                self.recovery_step = RecoveryStep::Choose;
+                self.set_status(format!("Could not set the wallet file aside: {}", e), StatusType::Error);
+            }
+        }
+    }
+    
+    fn update_filtered_wallets(&mut self) {
+        // Fuzzy score per wallet index; every wallet scores 0 for an empty query
+        let scores: Vec<Option<i64>> = if self.search_query.is_empty() {
+            vec![Some(0); self.wallets.len()]
+        } else {
+            let matcher = SkimMatcherV2::default().ignore_case();
+            // Addresses are indexed alongside names so a pasted partial
+            // address finds its wallet
+            let pubkeys: Vec<Option<String>> = (0..self.wallets.len())
+                .map(|i| self.wallet_details.get(i).and_then(|d| d.pubkey).map(|p| p.to_string()))
+                .collect();
+            self.wallets.iter()
+                .zip(&pubkeys)
+                .map(|(name, pubkey)| {
+                    let name_score = matcher.fuzzy_match(name, &self.search_query);
+                    let pubkey_score = pubkey.as_deref()
+                        .filter(|pubkey| pubkey_matches_query(pubkey, &self.search_query))
+                        .map(|_| 0);
+                    name_score.max(pubkey_score)
+                })
+                .collect()
+        };
+        self.filtered_wallets = (0..self.wallets.len())
+            .filter(|&i| scores[i].is_some())
+            .collect();
+        
+        // Best matches first, then the chosen sort mode among equal scores
+        let (wallets, details, mode) = (&self.wallets, &self.wallet_details, self.sort_mode);
+        self.filtered_wallets.sort_by(|&a, &b| {
+            scores[b].cmp(&scores[a]).then_with(|| match (details.get(a), details.get(b)) {
+                (Some(a), Some(b)) => compare_wallets(mode, a, b),
+                // Details not loaded yet: order by name alone
+                _ => wallets[a].cmp(&wallets[b]),
+            })
+        });
+        
+        if !self.search_query.is_empty() {
+            // Keep the highest-scoring wallet selected while searching
+            self.selected_wallet = self.filtered_wallets.first().copied();
+        } else if let Some(selected) = self.selected_wallet {
+            // Reset selection if current selection is not in filtered list
             if !self.filtered_wallets.contains(&selected) {
                 self.selected_wallet = self.filtered_wallets.first().copied();
             }
@@ -341,22 +1657,439 @@ impl App {
         }
     }
     
-    fn start_vanity_wallet_creation(&mut self) {
-        // Reset status
-        self.vanity_status = Some(VanityStatus {
-            attempts: 0,
-            completed: false,
-            success: false,
-            pubkey: None,
-            elapsed_seconds: 0.0,
-            attempts_per_second: 0.0,
-        });
+    fn cycle_sort_mode(&mut self) {
+        self.sort_mode = self.sort_mode.next();
+        self.update_filtered_wallets();
+        self.set_status(format!("Sorted by {}", self.sort_mode.label()), StatusType::Info);
+    }
+    
+    // Moves the selection a full page through the filtered list
+    fn select_wallet_page(&mut self, forward: bool) {
+        if self.filtered_wallets.is_empty() {
+            return;
+        }
         
-        // Reset cancellation flag
-        self.vanity_cancelled.store(false, Ordering::SeqCst);
+        let page = self.list_page_size.max(1);
+        let last = self.filtered_wallets.len() - 1;
+        let pos = self.selected_wallet
+            .and_then(|selected| self.filtered_wallets.iter().position(|&i| i == selected))
+            .unwrap_or(0);
+        let new_pos = if forward {
+            (pos + page).min(last)
+        } else {
+            pos.saturating_sub(page)
+        };
+        self.selected_wallet = Some(self.filtered_wallets[new_pos]);
+    }
+    
+    // Opens the batch transfer form paying from the selected wallet
+    fn open_batch_form(&mut self) {
+        self.batch_form = BatchForm::new(self.selected_wallet.unwrap_or(0));
+        self.current_view = View::BatchOperations;
+    }
+    
+    // Cycles the batch form's paying wallet through the wallet list
+    fn cycle_batch_source(&mut self, forward: bool) {
+        let count = self.wallets.len();
+        if count == 0 {
+            return;
+        }
+        let source = self.batch_form.source.min(count - 1);
+        self.batch_form.allow_unsafe_recipients = false;
+        self.batch_form.source = if forward {
+            (source + 1) % count
+        } else {
+            (source + count - 1) % count
+        };
+    }
+    
+    // Starts the batch transfer simulation in the background; it queries the
+    // RPC node for fees and balances, which must not stall the UI
+    fn simulate_batch_transfer(&mut self) {
+        if self.batch_rx.is_some() {
+            self.set_status("A batch simulation is already running".to_string(), StatusType::Warning);
+            return;
+        }
+        let Some(source_wallet) = self.wallets.get(self.batch_form.source).cloned() else {
+            self.set_status("Add a wallet to send from first".to_string(), StatusType::Warning);
+            return;
+        };
+        let batch = match self.batch_form.to_transaction(&source_wallet) {
+            Ok(batch) => batch,
+            Err(e) => {
+                self.set_status(e, StatusType::Warning);
+                return;
+            }
+        };
         
-        // Reset result
-        let mut result = self.vanity_result.lock().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let rpc_url = self.rpc_url.clone();
+        self.set_status(format!("Simulating transfer to {} recipient(s)…", batch.recipients.len()), StatusType::Info);
+        thread::spawn(move || {
+            let result = transaction_handler::execute_batch_transaction(&batch, &HttpRpc::new(rpc_url));
+            let _ = tx.send((batch.recipients.len(), result));
+        });
+        self.batch_rx = Some(rx);
+    }
+    
+    // Keeps the log of a finished batch simulation for display
+    fn poll_batch_simulation(&mut self) {
+        let received = match &self.batch_rx {
+            Some(rx) => rx.try_recv(),
+            None => return,
+        };
+        match received {
+            Ok((recipient_count, result)) => {
+                self.batch_rx = None;
+                match result {
+                    Ok(log) => {
+                        self.batch_form.log = Some(log);
+                        self.batch_form.log_scroll = 0;
+                        self.set_status(
+                            format!("Simulated transfer to {} recipient(s)", recipient_count),
+                            StatusType::Success,
+                        );
+                    },
+                    Err(e @ transaction_handler::TransactionError::UnsafeRecipient(_)) => {
+                        self.batch_form.allow_unsafe_recipients = true;
+                        self.set_status(format!("{}. Press Enter again to simulate anyway", e), StatusType::Warning);
+                    },
+                    Err(e) => {
+                        self.set_status(format!("Batch transfer failed: {}", e), StatusType::Error);
+                    }
+                }
+            },
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.batch_rx = None;
+            },
+            Err(mpsc::TryRecvError::Empty) => {}
+        }
+    }
+    
+    // Derives the address of the chosen seed account so it can be checked
+    // before a second Enter imports it
+    fn preview_seed_account(&mut self) {
+        let account = match self.seed_form.account_index() {
+            Ok(account) => account,
+            Err(e) => {
+                self.set_status(e, StatusType::Warning);
+                return;
+            }
+        };
+        if self.seed_form.name_prefix.is_empty() {
+            self.set_status("Enter a name prefix for the wallet".to_string(), StatusType::Warning);
+            return;
+        }
+        match wallet_manager::seed_account_pubkey(&self.seed_form.phrase, account) {
+            Ok(pubkey) => self.seed_form.preview = Some(pubkey),
+            Err(e) => self.set_status(e.to_string(), StatusType::Warning),
+        }
+    }
+    
+    // Stores the previewed seed account and returns to the wallet list
+    fn import_seed_account(&mut self) {
+        let account = match self.seed_form.account_index() {
+            Ok(account) => account,
+            Err(e) => {
+                self.set_status(e, StatusType::Warning);
+                return;
+            }
+        };
+        let path = wallet_manager::seed_account_derivation_path(account);
+        match wallet_manager::add_wallets_from_seed(&self.seed_form.name_prefix, &self.seed_form.phrase, 1, Some(&path)) {
+            Ok(created) => {
+                match created.first() {
+                    Some(name) => self.set_status(format!("Wallet '{}' imported from the seed phrase", name), StatusType::Success),
+                    None => self.set_status(format!("Account {} is already stored", account), StatusType::Warning),
+                }
+                self.seed_form = SeedImportForm::new();
+                self.load_wallets();
+                self.current_view = View::WalletList;
+            },
+            Err(WalletManagerError::AlreadyExists(msg)) => {
+                self.seed_form.preview = None;
+                self.set_status(format!("Wallet not added: {}", msg), StatusType::Warning);
+            },
+            Err(e) => {
+                self.seed_form.preview = None;
+                self.set_status(format!("Failed to import from seed phrase: {}", e), StatusType::Error);
+            }
+        }
+    }
+    
+    // Opens the token mixing form, preselecting the marked wallets or else
+    // the highlighted one
+    fn open_mixing_form(&mut self) {
+        let sources = if self.marked_wallets.is_empty() {
+            self.selected_wallet.into_iter().collect()
+        } else {
+            self.marked_wallets.clone()
+        };
+        self.mixing_form = MixingForm::new(sources, self.selected_wallet.unwrap_or(0));
+        self.current_view = View::TokenMixing;
+    }
+    
+    fn move_mixing_cursor(&mut self, forward: bool) {
+        let last = self.wallets.len().saturating_sub(1);
+        let cursor = &mut self.mixing_form.source_cursor;
+        *cursor = if forward { (*cursor + 1).min(last) } else { cursor.saturating_sub(1) };
+    }
+    
+    fn toggle_mixing_source(&mut self) {
+        let form = &mut self.mixing_form;
+        if form.source_cursor < self.wallets.len() && !form.sources.remove(&form.source_cursor) {
+            form.sources.insert(form.source_cursor);
+        }
+    }
+    
+    // Saves the batch or mixing log on display to a timestamped file
+    fn save_simulation_log(&mut self) {
+        let (kind, log) = match self.current_view {
+            View::BatchOperations => ("batch", self.batch_form.log.as_deref()),
+            View::TokenMixing => ("mixing", self.mixing_form.plan.as_deref()),
+            _ => return,
+        };
+        let Some(log) = log else {
+            self.set_status("Nothing to save yet; press Enter to run the simulation first".to_string(), StatusType::Warning);
+            return;
+        };
+        let path = transaction_handler::simulation_log_path(&self.simulation_log_dir, kind, chrono::Local::now());
+        match transaction_handler::write_simulation_log(&path, log) {
+            Ok(()) => self.set_status(format!("Log saved to {}", path.display()), StatusType::Success),
+            Err(e) => self.set_status(format!("Failed to save log to {}: {}", path.display(), e), StatusType::Error),
+        }
+    }
+    
+    // Generates the mixing plan and keeps it for display
+    fn generate_mixing_plan(&mut self) {
+        let plan = match self.mixing_form.to_plan(&self.wallets) {
+            Ok(plan) => plan,
+            Err(e) => {
+                self.set_status(e, StatusType::Warning);
+                return;
+            }
+        };
+        
+        match transaction_handler::generate_token_mixing_plan(&plan) {
+            Ok(output) => {
+                self.mixing_form.plan = Some(output);
+                self.mixing_form.plan_scroll = 0;
+                self.set_status(format!("Generated a {}-step mixing plan", plan.steps), StatusType::Success);
+            },
+            Err(e) => {
+                self.set_status(format!("Mixing plan failed: {}", e), StatusType::Error);
+            }
+        }
+    }
+    
+    fn session_settings(&self) -> SessionSettings {
+        SessionSettings {
+            vanity_prefix: self.vanity_config.prefix.clone(),
+            vanity_timeout_seconds: self.vanity_config.timeout_seconds,
+            vanity_threads: self.vanity_config.thread_count,
+            rpc_url: self.rpc_url.clone(),
+        }
+    }
+    
+    // Saves changed settings, reporting a failure in the status bar. Called on
+    // leaving forms rather than on every keystroke.
+    fn persist_settings(&mut self) {
+        if let Err(e) = self.save_settings() {
+            self.set_status(format!("Could not save settings: {:#}", e), StatusType::Warning);
+        }
+    }
+    
+    // Writes settings changed since the last save to config.toml. Only the
+    // changed fields are written, so values that came from environment
+    // overrides stay out of the file.
+    fn save_settings(&mut self) -> anyhow::Result<()> {
+        let settings = self.session_settings();
+        if settings == self.saved_settings {
+            return Ok(());
+        }
+        let Some(path) = self.settings_path.clone() else {
+            return Ok(());
+        };
+        
+        let saved = &self.saved_settings;
+        let result = config::update_config_file(&path, |config| {
+            if settings.vanity_prefix != saved.vanity_prefix {
+                config.vanity.default_prefix = settings.vanity_prefix.clone();
+            }
+            if settings.vanity_timeout_seconds != saved.vanity_timeout_seconds {
+                config.vanity.timeout_seconds = settings.vanity_timeout_seconds;
+            }
+            if settings.vanity_threads != saved.vanity_threads {
+                config.vanity.max_threads = settings.vanity_threads;
+            }
+            if settings.rpc_url != saved.rpc_url {
+                config.general.rpc_url = settings.rpc_url.clone();
+            }
+        });
+        result?;
+        self.saved_settings = settings;
+        Ok(())
+    }
+    
+    fn open_vanity_form(&mut self) {
+        self.current_view = View::CreateVanityWallet;
+        self.vanity_confirming = false;
+        self.vanity_focus = VanityField::WalletName;
+        self.vanity_timeout_input = self.vanity_config.timeout_seconds.to_string();
+        self.vanity_threads_input = self.vanity_config.thread_count.to_string();
+    }
+    
+    // Applies the focused numeric field to vanity_config. Returns false and
+    // warns when the typed value is out of range, keeping the focus in place.
+    fn commit_vanity_field(&mut self) -> bool {
+        match self.vanity_focus {
+            VanityField::WalletName => true,
+            VanityField::Prefix => match vanity_wallet::validate_prefix(&self.vanity_config.prefix) {
+                Ok(()) => true,
+                Err(e) => {
+                    self.set_status(e.to_string(), StatusType::Warning);
+                    false
+                }
+            },
+            // The suffix is optional, but must be base58 when given and cannot
+            // be added to a search that already matches the end
+            VanityField::Suffix => match self.vanity_config.validate_suffix() {
+                Ok(()) => true,
+                Err(e) => {
+                    self.set_status(format!("Invalid suffix: {}", e), StatusType::Warning);
+                    false
+                }
+            },
+            VanityField::Timeout => match self.vanity_timeout_input.parse::<u64>() {
+                Ok(timeout) if (1..=MAX_VANITY_TIMEOUT_SECONDS).contains(&timeout) => {
+                    self.vanity_config.timeout_seconds = timeout;
+                    true
+                },
+                _ => {
+                    self.set_status(format!("Timeout must be between 1 and {} seconds", MAX_VANITY_TIMEOUT_SECONDS), StatusType::Warning);
+                    false
+                }
+            },
+            VanityField::ThreadCount => {
+                let max_threads = num_cpus::get();
+                match self.vanity_threads_input.parse::<usize>() {
+                    Ok(threads) if (1..=max_threads).contains(&threads) => {
+                        self.vanity_config.thread_count = threads;
+                        true
+                    },
+                    _ => {
+                        self.set_status(format!("Thread count must be between 1 and {}", max_threads), StatusType::Warning);
+                        false
+                    }
+                }
+            }
+        }
+    }
+    
+    fn focus_vanity_field(&mut self, forward: bool) {
+        self.vanity_confirming = false;
+        if self.commit_vanity_field() {
+            self.vanity_focus = if forward { self.vanity_focus.next() } else { self.vanity_focus.prev() };
+        }
+    }
+    
+    fn edit_vanity_field(&mut self, key_code: KeyCode) {
+        self.vanity_confirming = false;
+        let numeric = matches!(self.vanity_focus, VanityField::Timeout | VanityField::ThreadCount);
+        let field = match self.vanity_focus {
+            VanityField::WalletName => &mut self.vanity_wallet_name,
+            VanityField::Prefix => &mut self.vanity_config.prefix,
+            VanityField::Suffix => &mut self.vanity_config.suffix,
+            VanityField::Timeout => &mut self.vanity_timeout_input,
+            VanityField::ThreadCount => &mut self.vanity_threads_input,
+        };
+        
+        match key_code {
+            KeyCode::Backspace => {
+                field.pop();
+            },
+            KeyCode::Char(c) if numeric && !c.is_ascii_digit() => {
+                self.set_status("Only digits are allowed in this field".to_string(), StatusType::Warning);
+            },
+            KeyCode::Char(c) => {
+                field.push(c);
+            },
+            _ => {}
+        }
+        
+        // Clamp the timeout as it is typed rather than failing on commit
+        if matches!(self.vanity_focus, VanityField::Timeout)
+            && !self.vanity_timeout_input.is_empty()
+            && self.vanity_timeout_input.parse::<u64>().map_or(true, |t| t > MAX_VANITY_TIMEOUT_SECONDS)
+        {
+            self.vanity_timeout_input = MAX_VANITY_TIMEOUT_SECONDS.to_string();
+            self.set_status(format!("Timeout is limited to {} seconds", MAX_VANITY_TIMEOUT_SECONDS), StatusType::Warning);
+        }
+    }
+    
+    // Starts the search, first asking for confirmation when it is expected
+    // to run long. A second call while confirming starts it regardless.
+    fn confirm_or_start_vanity_wallet_creation(&mut self) {
+        if let Err(e) = vanity_wallet::validate_prefix(&self.vanity_config.prefix) {
+            self.set_status(e.to_string(), StatusType::Warning);
+            return;
+        }
+        
+        let expected_seconds = self.vanity_config.expected_attempts()
+            / (vanity_wallet::ESTIMATED_ATTEMPTS_PER_THREAD * self.vanity_config.thread_count.max(1) as f64);
+        if self.vanity_confirming || expected_seconds <= VANITY_CONFIRM_SECONDS {
+            self.vanity_confirming = false;
+            self.start_vanity_wallet_creation();
+        } else {
+            self.vanity_confirming = true;
+        }
+    }
+    
+    // Lifetime statistics are kept per target, so a suffix search is counted
+    // apart from a plain search for the same prefix
+    fn vanity_stats_key(&self) -> String {
+        if self.vanity_config.suffix.is_empty() {
+            self.vanity_config.prefix.clone()
+        } else {
+            format!("{}...{}", self.vanity_config.prefix, self.vanity_config.suffix)
+        }
+    }
+    
+    fn start_vanity_wallet_creation(&mut self) {
+        if let Err(e) = vanity_wallet::validate_prefix(&self.vanity_config.prefix)
+            .and_then(|()| self.vanity_config.validate_suffix())
+        {
+            self.set_status(e.to_string(), StatusType::Warning);
+            return;
+        }
+        
+        // Reset status
+        self.vanity_status = Some(VanityStatus {
+            attempts: 0,
+            completed: false,
+            success: false,
+            pubkey: None,
+            matched_prefix: None,
+            elapsed_seconds: 0.0,
+            attempts_per_second: 0.0,
+        });
+        
+        // Reset cancellation and pause flags
+        self.vanity_cancelled.store(false, Ordering::SeqCst);
+        self.vanity_paused.store(false, Ordering::SeqCst);
+        
+        // Lifetime totals shown alongside this run's progress
+        self.vanity_lifetime_attempts = match vanity_wallet::load_vanity_stats(&self.vanity_stats_path) {
+            Ok(stats) => stats.get(&self.vanity_stats_key().to_lowercase()).map_or(0, |s| s.attempts),
+            Err(e) => {
+                self.log_error(format!("Failed to read vanity statistics: {}", e));
+                0
+            }
+        };
+        self.vanity_run_recorded = false;
+        
+        // Reset result
+        let mut result = self.vanity_result.lock().unwrap();
         *result = None;
         drop(result);
         
@@ -367,12 +2100,16 @@ impl App {
         // Start vanity wallet generation in a separate thread
         let vanity_config = self.vanity_config.clone();
         let result = Arc::clone(&self.vanity_result);
+        let cancelled = Arc::clone(&self.vanity_cancelled);
+        let paused = Arc::clone(&self.vanity_paused);
         
         let handle = thread::spawn(move || {
             // Create a local callback that updates the shared status
             let status_clone = Arc::clone(&status);
-            let keypair_result = vanity_wallet::generate_vanity_keypair_with_progress(
+            let keypair_result = vanity_wallet::generate_vanity_keypair_pausable(
                 &vanity_config,
+                &cancelled,
+                &paused,
                 move |new_status| {
                     let mut status_guard = status_clone.lock().unwrap();
                     *status_guard = new_status.clone();
@@ -389,19 +2126,25 @@ impl App {
         // Store thread handle and status for UI updates
         self.vanity_thread = Some(handle);
         self.vanity_status = Some(status_for_ui.lock().unwrap().clone());
+        self.vanity_shared_status = Some(status_for_ui);
         
         // Switch to progress view
         self.current_view = View::VanityProgress;
+        self.persist_settings();
         
         // Set initial status message
         self.set_status(
-            format!("Generating vanity wallet with prefix '{}'...", self.vanity_config.prefix),
+            format!("Generating vanity wallet with {}...", self.vanity_config.target_description()),
             StatusType::Info
         );
     }
     
     fn update_vanity_status(&mut self) {
-        // Get the latest status from the shared status
+        // Copy the latest progress reported by the generation thread
+        if let Some(shared) = &self.vanity_shared_status {
+            self.vanity_status = Some(shared.lock().unwrap().clone());
+        }
+        
         let status_updated = {
             // Check if vanity generation is complete by checking the result
             let result_guard = self.vanity_result.lock().unwrap();
@@ -417,6 +2160,7 @@ impl App {
                     let mut secret_key = [0u8; SECRET_KEY_BYTES];
                     secret_key.copy_from_slice(&keypair_bytes[0..SECRET_KEY_BYTES]);
                     let keypair_copy = solana_sdk::signer::keypair::Keypair::new_from_array(secret_key);
+                    self.record_vanity_run();
                     self.save_vanity_wallet(&keypair_copy);
                 }
                 true
@@ -431,6 +2175,7 @@ impl App {
             if let Some(thread) = &self.vanity_thread {
                 if thread.is_finished() {
                     // Thread is done but no result, must have timed out or been cancelled
+                    self.record_vanity_run();
                     self.current_view = View::WalletList;
                     self.set_status(
                         "Vanity wallet generation completed without finding a match".to_string(),
@@ -441,51 +2186,172 @@ impl App {
         }
     }
     
+    // Adds the current run to the lifetime statistics, once per run
+    fn record_vanity_run(&mut self) {
+        if self.vanity_run_recorded {
+            return;
+        }
+        self.vanity_run_recorded = true;
+        
+        let (attempts, elapsed) = self.vanity_status.as_ref()
+            .map_or((0, 0.0), |status| (status.attempts, status.elapsed_seconds));
+        if let Err(e) = vanity_wallet::record_vanity_run(&self.vanity_stats_path, &self.vanity_stats_key(), attempts, elapsed) {
+            self.log_error(format!("Failed to save vanity statistics: {}", e));
+        }
+    }
+    
     fn save_vanity_wallet(&mut self, keypair: &solana_sdk::signer::keypair::Keypair) {
-        // Save the keypair to a temporary file
-        let temp_dir = std::env::temp_dir();
-        let file_path = temp_dir.join(format!("{}.json", self.vanity_wallet_name));
-        
-        // Convert keypair to bytes and save as JSON array
-        let key_bytes = keypair.to_bytes();
-        let json_array = serde_json::to_string(&key_bytes.to_vec()).unwrap_or_default();
-        
-        match std::fs::write(&file_path, json_array) {
-            Ok(_) => {
-                // Add wallet from the temporary file
-                match wallet_manager::add_wallet_from_file(&self.vanity_wallet_name, file_path.to_str().unwrap()) {
-                    Ok(_) => {
-                        self.set_status(
-                            format!("Vanity wallet '{}' created successfully with address {}", 
-                                   self.vanity_wallet_name, keypair.pubkey()),
-                            StatusType::Success
-                        );
-                        self.load_wallets(); // Refresh wallet list
-                        self.current_view = View::WalletList;
-                    },
-                    Err(e) => {
-                        self.set_status(
-                            format!("Failed to add vanity wallet: {}", e),
-                            StatusType::Error
-                        );
-                    }
-                }
-                
-                // Clean up temporary file
-                let _ = std::fs::remove_file(file_path);
+        // Store the keypair straight into the vault; it never touches disk unencrypted.
+        // A taken name gets a suffix rather than replacing the wallet already under it
+        match wallet_manager::add_wallet_under_unique_name(&self.vanity_wallet_name, &keypair.to_bytes()) {
+            Ok(wallet_name) => {
+                let matched = self.vanity_status.as_ref()
+                    .and_then(|status| status.matched_prefix.as_ref())
+                    .map(|prefix| format!(" (found address matching '{}')", prefix))
+                    .unwrap_or_default();
+                self.set_status(
+                    format!("Vanity wallet '{}' created successfully with address {}{}", 
+                           wallet_name, keypair.pubkey(), matched),
+                    StatusType::Success
+                );
+                self.load_wallets(); // Refresh wallet list
+                self.current_view = View::WalletList;
             },
             Err(e) => {
                 self.set_status(
-                    format!("Failed to save vanity wallet: {}", e),
+                    format!("Failed to add vanity wallet: {}", e),
                     StatusType::Error
                 );
             }
         }
     }
     
+    fn start_wallet_scan(&mut self, dir_path: String) {
+        let dir_path = dir_path.trim().to_string();
+        if dir_path.is_empty() || !std::path::Path::new(&dir_path).is_dir() {
+            self.set_status("Please enter an existing directory to scan".to_string(), StatusType::Warning);
+            return;
+        }
+        
+        // Fresh shared state per scan, so a cancelled scan still running in the
+        // background can never overwrite the results of a newer one
+        let scanned = Arc::new(AtomicUsize::new(0));
+        let outcome = Arc::new(Mutex::new(None));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.scan_scanned = Arc::clone(&scanned);
+        self.scan_outcome = Arc::clone(&outcome);
+        self.scan_cancelled = Arc::clone(&cancelled);
+        self.scan_results.clear();
+        self.scan_selected = 0;
+        
+        let config = SearchConfig {
+            ignore_patterns: SCAN_IGNORE_PATTERNS.iter().map(|p| p.to_string()).collect(),
+            ..SearchConfig::default()
+        };
+        
+        let handle = thread::spawn(move || {
+            let result = file_searcher::search_solana_wallet_files_with_progress(
+                &dir_path,
+                &config,
+                &cancelled,
+                |count| scanned.store(count, Ordering::SeqCst),
+            );
+            *outcome.lock().unwrap() = Some(result);
+        });
+        
+        self.scan_thread = Some(handle);
+        self.set_status("Scanning for wallet files...".to_string(), StatusType::Info);
+    }
+    
+    fn update_scan_status(&mut self) {
+        let finished = match &self.scan_thread {
+            Some(thread) => thread.is_finished(),
+            None => return,
+        };
+        if !finished {
+            return;
+        }
+        
+        self.scan_thread = None;
+        let outcome = self.scan_outcome.lock().unwrap().take();
+        match outcome {
+            Some(Ok(paths)) => {
+                let message = format!(
+                    "Scan complete: {} wallet file(s) found in {} files",
+                    paths.len(),
+                    self.scan_scanned.load(Ordering::SeqCst)
+                );
+                self.scan_results = paths;
+                self.scan_results.sort();
+                self.set_status(message, StatusType::Success);
+            },
+            Some(Err(e)) => {
+                self.set_status(format!("Scan failed: {}", e), StatusType::Error);
+            },
+            None => {
+                self.set_status("Scan stopped without a result".to_string(), StatusType::Warning);
+            }
+        }
+    }
+    
+    fn cancel_wallet_scan(&mut self) {
+        // Stop the search thread and drop whatever it found so far
+        self.scan_cancelled.store(true, Ordering::SeqCst);
+        self.scan_thread = None;
+        self.scan_outcome = Arc::new(Mutex::new(None));
+        self.set_status("Wallet scan cancelled".to_string(), StatusType::Warning);
+    }
+    
+    fn import_selected_scan_result(&mut self) {
+        if self.scan_selected >= self.scan_results.len() {
+            return;
+        }
+        
+        let file_path = self.scan_results[self.scan_selected].clone();
+        let wallet_name = key_validator::key_file_stem(&file_path).unwrap_or_else(|| "new_wallet".to_string());
+        
+        match wallet_manager::add_wallet_from_file(&wallet_name, &file_path) {
+            Ok(wallet_name) => {
+                self.set_status(format!("Wallet '{}' imported from {}", wallet_name, file_path), StatusType::Success);
+                self.load_wallets(); // Refresh wallet list
+                self.scan_results.remove(self.scan_selected);
+                if self.scan_selected > 0 && self.scan_selected >= self.scan_results.len() {
+                    self.scan_selected -= 1;
+                }
+            },
+            Err(WalletManagerError::AlreadyExists(msg)) => {
+                self.set_status(format!("Wallet not added: {}", msg), StatusType::Warning);
+            },
+            Err(WalletManagerError::InvalidKey(_)) if matches!(key_validator::is_solana_wallet_json_file(&file_path), Ok(false)) => {
+                self.set_status(
+                    format!("Failed to import {}: {}", file_path, key_validator::describe_key_file(&file_path)),
+                    StatusType::Error,
+                );
+            },
+            Err(e) => {
+                self.set_status(format!("Failed to import {}: {}", file_path, e), StatusType::Error);
+            }
+        }
+    }
+    
+    fn toggle_vanity_pause(&mut self) {
+        let paused = !self.vanity_paused.load(Ordering::SeqCst);
+        self.vanity_paused.store(paused, Ordering::SeqCst);
+        if paused {
+            self.set_status("Vanity search paused. Press Space to resume".to_string(), StatusType::Info);
+        } else {
+            self.set_status(
+                format!("Generating vanity wallet with {}...", self.vanity_config.target_description()),
+                StatusType::Info
+            );
+        }
+    }
+    
     fn cancel_vanity_generation(&mut self) {
         // Use the vanity_cancelled Arc to signal cancellation
         vanity_wallet::cancel_vanity_generation(&self.vanity_cancelled);
+        self.vanity_paused.store(false, Ordering::SeqCst);
+        self.record_vanity_run();
         
         self.set_status("Vanity wallet generation cancelled".to_string(), StatusType::Warning);
         self.current_view = View::WalletList;
@@ -494,8 +2360,23 @@ impl App {
 
 // Main TUI run function
 pub fn run_tui() -> io::Result<()> {
+    let (config, config_error) = match config::load_config() {
+        Ok(config) => (config, None),
+        Err(e) => (Config::default(), Some(e)),
+    };
+    
+    // Resolve the vault location before switching screens so a fallback warning stays visible
+    let _ = secure_storage::get_config_path();
+    
+    let watch_wallet_file = config.general.watch_wallet_file;
+    
+    install_terminal_guards();
     let mut terminal = init_terminal()?;
-    let mut app = App::new();
+    let mut app = App::with_config(config);
+    // Without a readable config, keep the defaults in memory only rather than overwriting the file
+    if config_error.is_none() {
+        app.settings_path = Some(config::get_config_path());
+    }
     app.load_wallets(); // Load initial wallet list
     
     // Enhanced welcome message based on wallet count
@@ -510,7 +2391,32 @@ pub fn run_tui() -> io::Result<()> {
             StatusType::Info
         );
     }
+    
+    if watch_wallet_file {
+        let watcher = secure_storage::get_config_path()
+            .map_err(|e| e.to_string())
+            .and_then(|path| VaultWatcher::new(&path).map_err(|e| e.to_string()));
+        match watcher {
+            Ok(watcher) => app.vault_watcher = Some(watcher),
+            Err(e) => app.set_status(format!("Not watching the wallet file for changes: {}", e), StatusType::Warning),
+        }
+    }
+    
+    if let Some(e) = config_error {
+        app.set_status(format!("Using default settings, failed to load config: {:#}", e), StatusType::Warning);
+    }
+
+    // Restore the terminal even when the loop fails, then report the error
+    let result = run_event_loop(&mut terminal, &mut app);
+    restore_terminal()?;
+    if let Err(e) = app.save_settings() {
+        eprintln!("Could not save settings: {:#}", e);
+    }
+    result
+}
 
+// Runs the UI until the user quits
+fn run_event_loop(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) -> io::Result<()> {
     loop {
         app.clear_status_if_expired();
         
@@ -519,39 +2425,99 @@ pub fn run_tui() -> io::Result<()> {
             app.update_vanity_status();
         }
         
-        terminal.draw(|frame| ui(frame, &mut app))?;
+        // Pick up scan results once the background search finishes
+        if let View::ScanWallets = app.current_view {
+            app.update_scan_status();
+        }
+        
+        app.lock_if_idle();
+        // Nothing is loaded from the wallet file while locked
+        let locked = matches!(app.current_view, View::Locked);
+        
+        if !locked && refresh_due(app.last_refresh.elapsed(), app.auto_refresh_seconds, app.refresh_rx.is_some()) {
+            app.start_background_refresh();
+        }
+        app.poll_background_refresh();
+        app.poll_balance_updates();
+        
+        if let View::WalletDetail = app.current_view {
+            app.ensure_transaction_history();
+        }
+        app.poll_transaction_history();
+        app.poll_airdrop();
+        app.poll_batch_simulation();
+        app.poll_cluster_health();
+        if !locked {
+            app.poll_vault_watcher();
+        }
+        
+        terminal.draw(|frame| ui(frame, app))?;
 
         if event::poll(Duration::from_millis(100))? {
             if let CrosstermEvent::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    handle_key_event(&mut app, key.code);
+                // Raw mode delivers Ctrl-C as a key press rather than a signal
+                if key.kind == KeyEventKind::Press
+                    && key.modifiers.contains(KeyModifiers::CONTROL)
+                    && key.code == KeyCode::Char('c')
+                {
+                    app.should_quit = true;
+                } else if key.kind == KeyEventKind::Press {
+                    handle_key_event(app, key.code);
                 }
             }
         }
 
         if app.should_quit {
-            break;
+            return Ok(());
         }
     }
+}
 
-    restore_terminal()?;
-    Ok(())
+// Whether the terminal is in raw mode on the alternate screen
+static TERMINAL_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+// Makes sure a panic or an interrupt signal leaves the terminal usable
+fn install_terminal_guards() {
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal();
+        previous_hook(info);
+    }));
+    
+    // Only reached when SIGINT comes from outside, e.g. `kill -INT`
+    let _ = ctrlc::set_handler(|| {
+        let _ = restore_terminal();
+        std::process::exit(130);
+    });
+}
+
+// Runs `restore` only if `active` is still set, so repeated calls are no-ops
+fn restore_once(active: &AtomicBool, restore: impl FnOnce() -> io::Result<()>) -> io::Result<()> {
+    if active.swap(false, Ordering::SeqCst) {
+        restore()
+    } else {
+        Ok(())
+    }
 }
 
 // Initialize the terminal
 fn init_terminal() -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
     enable_raw_mode()?;
+    TERMINAL_ACTIVE.store(true, Ordering::SeqCst);
     let mut stdout = stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     Terminal::new(backend)
 }
 
-// Restore the terminal to its original state
+// Restore the terminal to its original state. Safe to call more than once,
+// e.g. from both the panic hook and the normal exit path.
 fn restore_terminal() -> io::Result<()> {
-    disable_raw_mode()?;
-    execute!(stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
-    Ok(())
+    restore_once(&TERMINAL_ACTIVE, || {
+        disable_raw_mode()?;
+        execute!(stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+        Ok(())
+    })
 }
 
 // Define the UI layout and widgets
@@ -572,23 +2538,39 @@ fn ui(frame: &mut Frame, app: &mut App) {
         View::WalletDetail => "Wallet Details",
         View::Help => "Help",
         View::AddWallet => "Add New Wallet",
-        View::ConfirmDelete => "Confirm Delete",
+        View::ConfirmDelete | View::ConfirmBatchDelete => "Confirm Delete",
         View::SearchWallets => "Search Wallets",
         View::BatchOperations => "Batch Operations",
         View::CreateVanityWallet => "Create Vanity Wallet",
         View::VanityProgress => "Generating Vanity Wallet",
+        View::RenameWallet => "Rename Wallet",
+        View::ScanWallets => "Scan for Wallets",
+        View::TokenMixing => "Token Mixing",
+        View::ImportSeed => "Import from Seed Phrase",
+        View::VaultRecovery => "Recover Wallet File",
+        View::ExportAddressBook => "Export Address Book",
+        View::RevealSecret => "Reveal Secret Key",
+        View::Errors => "Error Log",
+        View::Locked => "Locked",
     };
     
     // Format last refresh time
-    let refresh_time = format!("Last refresh: {} seconds ago", 
-                              app.last_refresh.elapsed().as_secs());
+    let refresh_time = if app.refresh_rx.is_some() {
+        "refreshing…".to_string()
+    } else {
+        format!("Last refresh: {} seconds ago", app.last_refresh.elapsed().as_secs())
+    };
+    
+    let (cluster_text, cluster_color) = cluster_indicator(&rpc::cluster_name(&app.rpc_url), app.cluster_health.as_ref());
     
     let title_text = Line::from(vec![
-        Span::styled("svmai ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+        Span::styled("svmai ", Style::default().fg(app.theme.title).add_modifier(Modifier::BOLD)),
         Span::styled("v0.1.0 ", Style::default().fg(Color::Gray)),
         Span::styled("| ", Style::default().fg(Color::DarkGray)),
         Span::styled(title, Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
         Span::styled(" | ", Style::default().fg(Color::DarkGray)),
+        Span::styled(cluster_text, Style::default().fg(cluster_color)),
+        Span::styled(" | ", Style::default().fg(Color::DarkGray)),
         Span::styled(refresh_time, Style::default().fg(Color::Gray)),
     ]);
     
@@ -603,38 +2585,242 @@ fn ui(frame: &mut Frame, app: &mut App) {
     match app.current_view {
         View::WalletList => render_wallet_list(frame, app, main_layout[1]),
         View::WalletDetail => render_wallet_detail(frame, app, main_layout[1]),
-        View::Help => render_help(frame, main_layout[1]),
+        View::Help => render_help(frame, app, main_layout[1]),
         View::AddWallet => render_add_wallet(frame, app, main_layout[1]),
-        View::ConfirmDelete => render_confirm_delete(frame, app, main_layout[1]),
+        View::ConfirmDelete | View::ConfirmBatchDelete => render_confirm_delete(frame, app, main_layout[1]),
         View::SearchWallets => render_search_wallets(frame, app, main_layout[1]),
         View::BatchOperations => render_batch_operations(frame, app, main_layout[1]),
         View::CreateVanityWallet => render_create_vanity_wallet(frame, app, main_layout[1]),
         View::VanityProgress => render_vanity_progress(frame, app, main_layout[1]),
+        View::RenameWallet => render_rename_wallet(frame, app, main_layout[1]),
+        View::ScanWallets => render_scan_wallets(frame, app, main_layout[1]),
+        View::TokenMixing => render_token_mixing(frame, app, main_layout[1]),
+        View::ImportSeed => render_import_seed(frame, app, main_layout[1]),
+        View::VaultRecovery => render_vault_recovery(frame, app, main_layout[1]),
+        View::ExportAddressBook => render_export_address_book(frame, app, main_layout[1]),
+        View::RevealSecret => render_reveal_secret(frame, app, main_layout[1]),
+        View::Errors => render_error_log(frame, app, main_layout[1]),
+        View::Locked => render_locked(frame, app, main_layout[1]),
     }
 
     // Render status bar
     render_status_bar(frame, app, main_layout[2]);
 }
 
-fn render_wallet_list(frame: &mut Frame, app: &App, area: Rect) {
-    // Create a layout with search bar at top if needed
-    let list_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3), // Search bar or stats
-            Constraint::Min(0),    // Wallet list
-        ])
-        .split(area);
+// Loads the details shown for each named wallet, collecting per-wallet errors
+fn fetch_wallet_details(names: &[String]) -> (Vec<WalletDetail>, Vec<String>) {
+    let mut details = Vec::with_capacity(names.len());
+    let mut error_messages = Vec::new();
     
-    // Render wallet stats or search bar
-    let wallet_count = app.wallets.len();
-    let filtered_count = app.filtered_wallets.len();
-    let stats_text = if app.search_query.is_empty() {
-        format!("Total wallets: {}", wallet_count)
+    for wallet_name in names {
+        let mut detail = WalletDetail {
+            name: wallet_name.clone(),
+            pubkey: None,
+            balance: None,
+            balance_error: None,
+            transactions: None,
+            history_error: None,
+            token_balances: Vec::new(), // Initialize empty token balances
+            label: None,
+            created_at: None,
+        };
+        
+        // The record caches the public key, so no keypair is rebuilt here
+        let pubkey = match secure_storage::retrieve_wallet_record(wallet_name) {
+            Ok(Some(record)) => {
+                detail.label = record.label.clone();
+                detail.created_at = Some(record.created_at).filter(|&ts| ts > 0);
+                wallet_manager::record_pubkey(&record).map(Some)
+            },
+            Ok(None) => Ok(None), // Removed since the names were listed
+            Err(e) => Err(e.into()),
+        };
+        
+        match pubkey {
+            Ok(Some(owner)) => {
+                // The balance and token accounts are fetched afterwards on the balance thread
+                detail.pubkey = Some(owner);
+            },
+            Ok(None) => {},
+            Err(e) => {
+                error_messages.push(format!("Error loading wallet details for {}: {}", wallet_name, e));
+            }
+        }
+        
+        details.push(detail);
+    }
+    
+    (details, error_messages)
+}
+
+// Fetches the SOL balance of each wallet on a background thread, sending each
+// one as it arrives
+fn spawn_balance_fetch(rpc_url: String, wallets: Vec<(String, Pubkey)>) -> mpsc::Receiver<BalanceUpdate> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let rpc = HttpRpc::new(rpc_url);
+        for (name, pubkey) in wallets {
+            let balance = rpc.get_balance(&pubkey).map_err(|e| e.to_string());
+            let tokens = rpc.get_owned_token_accounts(&pubkey).map_err(|e| e.to_string());
+            // The receiver is gone once a newer refresh replaced this one
+            if tx.send(BalanceUpdate { name, balance, tokens }).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+// Applies the balances queued on `rx`, dropping it once its thread is done
+fn drain_balance_updates(rx: &mut Option<mpsc::Receiver<BalanceUpdate>>, details: &mut [WalletDetail]) {
+    let Some(receiver) = rx else {
+        return;
+    };
+    loop {
+        match receiver.try_recv() {
+            Ok(update) => apply_balance_update(details, update),
+            Err(mpsc::TryRecvError::Empty) => break,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                *rx = None;
+                break;
+            },
+        }
+    }
+}
+
+// Stores a fetched balance on the wallet it belongs to. The update is matched
+// by name, since the wallet may have moved in the list or been removed.
+fn apply_balance_update(details: &mut [WalletDetail], update: BalanceUpdate) {
+    if let Some(detail) = details.iter_mut().find(|detail| detail.name == update.name) {
+        match update.balance {
+            Ok(balance) => {
+                detail.balance = Some(balance);
+                detail.balance_error = None;
+            },
+            Err(e) => detail.balance_error = Some(e),
+        }
+        // Only what the node reported is shown; empty accounts are left out
+        detail.token_balances = update.tokens
+            .unwrap_or_default()
+            .iter()
+            .filter(|account| account.amount > 0)
+            .map(TokenBalance::from_account)
+            .collect();
+    }
+}
+
+// Whether an automatic refresh should start: enabled, none already running,
+// and the interval has passed since the last refresh
+fn refresh_due(since_last_refresh: Duration, interval_seconds: u64, in_flight: bool) -> bool {
+    interval_seconds > 0 && !in_flight && since_last_refresh >= Duration::from_secs(interval_seconds)
+}
+
+// Title bar text and color for the cluster and the node's last health check
+fn cluster_indicator(cluster: &str, health: Option<&rpc::Result<()>>) -> (String, Color) {
+    match health {
+        None => (format!("○ {} (checking…)", cluster), Color::Gray),
+        Some(Ok(())) => (format!("● {}", cluster), Color::Green),
+        Some(Err(rpc::RpcError::Transport(_))) => (format!("● {} (unreachable)", cluster), Color::Red),
+        Some(Err(_)) => (format!("● {} (unhealthy)", cluster), Color::Red),
+    }
+}
+
+// Whether the TUI should lock: enabled, not already locked, and no key has
+// been pressed for the timeout
+fn lock_due(idle: Duration, timeout_minutes: u64, locked: bool) -> bool {
+    timeout_minutes > 0 && !locked && idle >= Duration::from_secs(timeout_minutes.saturating_mul(60))
+}
+
+// Whether a status message shown for `elapsed` should be cleared. A timeout of
+// zero keeps every message, and errors can be configured to stay until replaced.
+fn status_expired(status_type: &StatusType, elapsed: Duration, timeout_seconds: u64, persist_errors: bool) -> bool {
+    if timeout_seconds == 0 || (persist_errors && matches!(status_type, StatusType::Error)) {
+        return false;
+    }
+    elapsed > Duration::from_secs(timeout_seconds)
+}
+
+// Orders two wallets for the given sort mode. Wallets without a balance or
+// creation date sort after those with one, and ties fall back to name order.
+fn compare_wallets(mode: SortMode, a: &WalletDetail, b: &WalletDetail) -> cmp::Ordering {
+    let by_name = a.name.to_lowercase().cmp(&b.name.to_lowercase())
+        .then_with(|| a.name.cmp(&b.name));
+    match mode {
+        SortMode::NameAsc => by_name,
+        SortMode::NameDesc => by_name.reverse(),
+        SortMode::BalanceDesc => b.balance.cmp(&a.balance).then(by_name),
+        SortMode::CreatedDesc => b.created_at.cmp(&a.created_at).then(by_name),
+    }
+}
+
+// Returns the first visible row so that `selected` stays within a viewport of
+// `height` rows, moving as little as possible from the current `offset`
+fn adjust_scroll_offset(offset: usize, selected: usize, height: usize, len: usize) -> usize {
+    if height == 0 {
+        return offset;
+    }
+    let offset = if selected < offset {
+        selected
+    } else if selected >= offset + height {
+        selected + 1 - height
+    } else {
+        offset
+    };
+    // Never leave blank rows at the bottom when the list could fill them
+    offset.min(len.saturating_sub(height))
+}
+
+// Range of list positions to draw: starts at the adjusted scroll offset and
+// holds at most `height` rows
+fn visible_window(offset: usize, selected: usize, height: usize, len: usize) -> std::ops::Range<usize> {
+    let start = adjust_scroll_offset(offset, selected, height, len).min(len);
+    start..(start + height).min(len)
+}
+
+// Result of removing several wallets in one go
+#[derive(Debug, Default, PartialEq)]
+struct BatchRemoval {
+    removed: usize,
+    failed: Vec<(String, String)>, // Wallet name and why it could not be removed
+}
+
+// Removes each named wallet, carrying on past failures
+fn remove_wallets(
+    names: &[String],
+    mut remove: impl FnMut(&str) -> Result<(), WalletManagerError>,
+) -> BatchRemoval {
+    let mut outcome = BatchRemoval::default();
+    for name in names {
+        match remove(name) {
+            Ok(()) => outcome.removed += 1,
+            Err(e) => outcome.failed.push((name.clone(), e.to_string())),
+        }
+    }
+    outcome
+}
+
+fn render_wallet_list(frame: &mut Frame, app: &mut App, area: Rect) {
+    // Create a layout with search bar at top if needed
+    let list_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Search bar or stats
+            Constraint::Min(0),    // Wallet list
+        ])
+        .split(area);
+    
+    // Render wallet stats or search bar
+    let wallet_count = app.wallets.len();
+    let filtered_count = app.filtered_wallets.len();
+    let mut stats_text = if app.search_query.is_empty() {
+        format!("Total wallets: {} | Sort: {}", wallet_count, app.sort_mode.label())
     } else {
-        format!("Showing {} of {} wallets matching: \"{}\"", 
-                filtered_count, wallet_count, app.search_query)
+        format!("Showing {} of {} wallets matching: \"{}\" | Sort: {}", 
+                filtered_count, wallet_count, app.search_query, app.sort_mode.label())
     };
+    if !app.marked_wallets.is_empty() {
+        stats_text.push_str(&format!(" | Marked: {}", app.marked_wallets.len()));
+    }
     
     frame.render_widget(
         Paragraph::new(stats_text)
@@ -662,13 +2848,28 @@ fn render_wallet_list(frame: &mut Frame, app: &App, area: Rect) {
         return;
     }
 
-    // Create list items from filtered wallets
-    let items: Vec<ListItem> = app.filtered_wallets.iter()
+    // Keep the selection inside the visible rows (the block borders take two)
+    let visible_rows = list_layout[1].height.saturating_sub(2) as usize;
+    app.list_page_size = visible_rows.max(1);
+    
+    // Find the position of the selected wallet in the filtered list
+    let selected_pos = app.selected_wallet
+        .and_then(|selected| app.filtered_wallets.iter().position(|&i| i == selected));
+    let window = visible_window(
+        app.scroll_offset,
+        selected_pos.unwrap_or(0),
+        visible_rows,
+        app.filtered_wallets.len(),
+    );
+    app.scroll_offset = window.start;
+    
+    // Only the rows on screen become list items, however large the vault
+    let items: Vec<ListItem> = app.filtered_wallets[window.clone()].iter()
         .map(|&index| {
             let wallet_name = &app.wallets[index];
             let mut style = Style::default();
             if app.selected_wallet == Some(index) {
-                style = style.fg(Color::Yellow).add_modifier(Modifier::BOLD);
+                style = style.fg(app.theme.selected).add_modifier(Modifier::BOLD);
             }
             
             // Get public key if available
@@ -686,18 +2887,24 @@ fn render_wallet_list(frame: &mut Frame, app: &App, area: Rect) {
             };
             
             // Get balance if available
-            let balance_display = if index < app.wallet_details.len() {
-                if let Some(balance) = app.wallet_details[index].balance {
-                    format!(" | {:.4} SOL", balance)
-                } else {
-                    "".to_string()
-                }
+            let balance_display = match app.wallet_details.get(index) {
+                Some(WalletDetail { balance: Some(balance), .. }) => format!(" | {}", app.format_balance(*balance, 4)),
+                Some(detail) if detail.balance_pending() => " | …".to_string(),
+                _ => "".to_string(),
+            };
+            
+            // Checkmark column, only shown while something is marked
+            let mark = if app.marked_wallets.contains(&index) {
+                "✓ "
+            } else if app.marked_wallets.is_empty() {
+                ""
             } else {
-                "".to_string()
+                "  "
             };
             
             ListItem::new(Line::from(vec![
-                Span::styled(format!("{}", wallet_name), style),
+                Span::styled(mark, Style::default().fg(app.theme.success)),
+                Span::styled(wallet_name.clone(), style),
                 Span::styled(pubkey_display, Style::default().fg(Color::DarkGray)),
                 Span::styled(balance_display, Style::default().fg(Color::Green)),
             ]))
@@ -719,15 +2926,9 @@ fn render_wallet_list(frame: &mut Frame, app: &App, area: Rect) {
         )
         .highlight_symbol(">> ");
 
-    // Create a stateful list
-    let mut state = ListState::default();
-    
-    // Find the position of the selected wallet in the filtered list
-    if let Some(selected) = app.selected_wallet {
-        if let Some(pos) = app.filtered_wallets.iter().position(|&i| i == selected) {
-            state.select(Some(pos));
-        }
-    }
+    // The list holds just the visible window, so positions are relative to it
+    let mut state = ListState::default()
+        .with_selected(selected_pos.and_then(|pos| pos.checked_sub(window.start)));
 
     frame.render_stateful_widget(list, list_layout[1], &mut state);
 }
@@ -742,8 +2943,9 @@ fn render_wallet_detail(frame: &mut Frame, app: &App, area: Rect) {
                 .constraints([
                     Constraint::Length(3), // Name
                     Constraint::Length(3), // Public Key
+                    Constraint::Length(3), // Label and creation date
                     Constraint::Length(3), // Balance
-                    Constraint::Length(3), // Last Transaction
+                    Constraint::Percentage(50), // Recent Transactions
                     Constraint::Min(0),    // Token Balances
                 ])
                 .split(area);
@@ -768,49 +2970,122 @@ fn render_wallet_detail(frame: &mut Frame, app: &App, area: Rect) {
                 detail_layout[1],
             );
             
-            // Balance
-            let balance_text = match detail.balance {
-                Some(balance) => format!("{:.9} SOL", balance),
-                None => "Not available".to_string(),
+            // Label and creation date
+            let label_text = detail.label.clone().unwrap_or_else(|| "None".to_string());
+            let created_text = match detail.created_at.and_then(|ts| chrono::DateTime::from_timestamp(ts, 0)) {
+                Some(created) => created.format("%Y-%m-%d %H:%M UTC").to_string(),
+                None => "Unknown".to_string(),
             };
             frame.render_widget(
-                Paragraph::new(balance_text)
-                    .style(Style::default().fg(Color::Green))
-                    .block(Block::default().borders(Borders::ALL).title("SOL Balance")),
+                Paragraph::new(Line::from(vec![
+                    Span::styled("Label: ", Style::default().fg(Color::DarkGray)),
+                    Span::raw(label_text),
+                    Span::styled("  Created: ", Style::default().fg(Color::DarkGray)),
+                    Span::raw(created_text),
+                ]))
+                .block(Block::default().borders(Borders::ALL).title("Details")),
                 detail_layout[2],
             );
             
-            // Last Transaction
-            let tx_text = match &detail.last_transaction {
-                Some(tx) => tx.clone(),
-                None => "No transaction history available".to_string(),
+            // Balance
+            let balance_text = match (detail.balance, &detail.balance_error) {
+                (Some(balance), _) => app.format_balance(balance, app.display.sol_decimals),
+                (None, Some(e)) => format!("Not available: {}", e),
+                (None, None) if detail.balance_pending() => "Loading…".to_string(),
+                (None, None) => "Not available".to_string(),
             };
             frame.render_widget(
-                Paragraph::new(tx_text)
-                    .block(Block::default().borders(Borders::ALL).title("Last Transaction")),
+                Paragraph::new(balance_text)
+                    .style(Style::default().fg(Color::Green))
+                    .block(Block::default().borders(Borders::ALL).title("SOL Balance")),
                 detail_layout[3],
             );
             
+            // Recent Transactions
+            let tx_block = Block::default().borders(Borders::ALL).title("Recent Transactions");
+            match (&detail.transactions, &detail.history_error) {
+                (Some(transactions), _) if !transactions.is_empty() => {
+                    let tx_items: Vec<ListItem> = transactions.iter()
+                        .map(|tx| {
+                            let (marker, marker_color) = if tx.success {
+                                ("✓ ", app.theme.success)
+                            } else {
+                                ("✗ ", app.theme.error)
+                            };
+                            let time = tx.block_time
+                                .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+                                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                                .unwrap_or_else(|| "unknown time".to_string());
+                            ListItem::new(Line::from(vec![
+                                Span::styled(marker, Style::default().fg(marker_color)),
+                                Span::raw(match (tx.signature.get(..8), tx.signature.len().checked_sub(8)) {
+                                    (Some(head), Some(tail)) if tail >= 8 => {
+                                        format!("{}...{} ", head, &tx.signature[tail..])
+                                    },
+                                    _ => format!("{} ", tx.signature),
+                                }),
+                                Span::styled(format!("slot {} | {}", tx.slot, time),
+                                            Style::default().fg(Color::DarkGray)),
+                            ]))
+                        })
+                        .collect();
+                    
+                    let mut tx_state = ListState::default()
+                        .with_selected(Some(app.tx_selected.min(transactions.len() - 1)));
+                    frame.render_stateful_widget(
+                        List::new(tx_items)
+                            .block(tx_block)
+                            .highlight_style(Style::default().bg(Color::DarkGray)),
+                        detail_layout[4],
+                        &mut tx_state,
+                    );
+                },
+                (Some(_), _) => {
+                    frame.render_widget(
+                        Paragraph::new("No transactions found").block(tx_block),
+                        detail_layout[4],
+                    );
+                },
+                (None, Some(error)) => {
+                    frame.render_widget(
+                        Paragraph::new(format!("Transaction history unavailable: {}", error))
+                            .style(Style::default().fg(app.theme.warning))
+                            .wrap(Wrap { trim: true })
+                            .block(tx_block),
+                        detail_layout[4],
+                    );
+                },
+                (None, None) => {
+                    let text = if detail.pubkey.is_some() {
+                        "Loading recent transactions..."
+                    } else {
+                        "No transaction history available"
+                    };
+                    frame.render_widget(Paragraph::new(text).block(tx_block), detail_layout[4]);
+                },
+            }
+            
             // Token Balances
             if detail.token_balances.is_empty() {
                 frame.render_widget(
                     Paragraph::new("No token balances available")
                         .alignment(Alignment::Center)
                         .block(Block::default().borders(Borders::ALL).title("Token Balances")),
-                    detail_layout[4],
+                    detail_layout[5],
                 );
             } else {
                 let token_items: Vec<ListItem> = detail.token_balances.iter()
                     .map(|token| {
+                        // No token registry is consulted, so the mint names the token
                         let line = Line::from(vec![
-                            Span::styled(format!("{}: ", token.token_name), 
-                                        Style::default().fg(Color::Yellow)),
-                            Span::styled(format!("{:.6} ", token.amount),
-                                        Style::default().fg(Color::Green)),
-                            Span::styled(format!("({}...{})", 
+                            Span::styled(format!("{}...{}: ", 
                                                &token.mint_address[..4], 
                                                &token.mint_address[token.mint_address.len()-4..]),
-                                        Style::default().fg(Color::DarkGray)),
+                                        Style::default().fg(Color::Yellow)),
+                            Span::styled(app.display.format_token(token.amount),
+                                        Style::default().fg(Color::Green)),
+                            Span::styled(format!("  Account: {}", token.token_account),
+                                        Style::default().fg(Color::Gray)),
                         ]);
                         ListItem::new(line)
                     })
@@ -819,7 +3094,7 @@ fn render_wallet_detail(frame: &mut Frame, app: &App, area: Rect) {
                 let tokens_list = List::new(token_items)
                     .block(Block::default().borders(Borders::ALL).title("Token Balances"));
                 
-                frame.render_widget(tokens_list, detail_layout[4]);
+                frame.render_widget(tokens_list, detail_layout[5]);
             }
         } else {
             frame.render_widget(
@@ -839,7 +3114,7 @@ fn render_wallet_detail(frame: &mut Frame, app: &App, area: Rect) {
     }
 }
 
-fn render_help(frame: &mut Frame, area: Rect) {
+fn render_help(frame: &mut Frame, app: &App, area: Rect) {
     let help_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -850,7 +3125,7 @@ fn render_help(frame: &mut Frame, area: Rect) {
     
     frame.render_widget(
         Paragraph::new("svmai Wallet Manager Help")
-            .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+            .style(Style::default().fg(app.theme.title).add_modifier(Modifier::BOLD))
             .alignment(Alignment::Center)
             .block(Block::default().borders(Borders::ALL)),
         help_layout[0],
@@ -861,6 +3136,7 @@ fn render_help(frame: &mut Frame, area: Rect) {
             Span::styled("Navigation:", Style::default().add_modifier(Modifier::BOLD).fg(Color::Yellow)),
         ]),
         Line::from("  ↑/↓: Navigate wallet list"),
+        Line::from("  PgUp/PgDn: Jump a page through the wallet list"),
         Line::from("  Enter: View wallet details"),
         Line::from("  Esc/Backspace: Return to previous view"),
         Line::from(""),
@@ -868,27 +3144,42 @@ fn render_help(frame: &mut Frame, area: Rect) {
             Span::styled("Wallet Management:", Style::default().add_modifier(Modifier::BOLD).fg(Color::Yellow)),
         ]),
         Line::from("  a: Add existing wallet"),
-        Line::from("  v: Create new vanity wallet with 'ai' prefix"),
+        Line::from("  m: Import one account from a seed phrase, previewing its address first"),
+        Line::from("  v: Create new vanity wallet with a custom prefix (Space pauses the search)"),
         Line::from("  d: Delete selected wallet"),
+        Line::from("  Space: Mark/unmark wallet for batch actions (Esc clears marks)"),
+        Line::from("  D: Delete all marked wallets"),
+        Line::from("  With wallet.strict_delete set, deleting needs the wallet name typed instead of y"),
+        Line::from("  s: Cycle the wallet list sort order"),
+        Line::from("  f: Scan a directory for wallet files to import"),
+        Line::from("  e: Export names and public addresses to share (no keys)"),
+        Line::from("  E: Show recent errors"),
+        Line::from("  n: Rename wallet (from details view)"),
+        Line::from("  a: Airdrop 1 SOL on devnet/testnet (from details view)"),
+        Line::from("  k: Reveal the secret key for backup (from details view)"),
         Line::from("  r: Refresh wallet list and balances"),
+        Line::from("  R: Refresh the balance of the open wallet (detail view)"),
+        Line::from("  u: Show balances in SOL or lamports"),
         Line::from(""),
         Line::from(vec![
             Span::styled("Search and Filter:", Style::default().add_modifier(Modifier::BOLD).fg(Color::Yellow)),
         ]),
-        Line::from("  /: Search wallets by name"),
+        Line::from("  /: Search wallets by name or address"),
         Line::from("  Esc: Clear search"),
         Line::from(""),
         Line::from(vec![
             Span::styled("Operations:", Style::default().add_modifier(Modifier::BOLD).fg(Color::Yellow)),
         ]),
-        Line::from("  b: Batch operations menu"),
+        Line::from("  b: Batch transfer to several recipients (simulation)"),
         Line::from("  t: Token mixing simulation"),
+        Line::from("  F2: Save the simulation log to the data directory (in either form)"),
         Line::from(""),
         Line::from(vec![
             Span::styled("General:", Style::default().add_modifier(Modifier::BOLD).fg(Color::Yellow)),
         ]),
         Line::from("  h: Show this help"),
         Line::from("  q: Quit application"),
+        Line::from("  The screen locks after general.lock_timeout_minutes without input; Enter unlocks"),
     ];
     
     frame.render_widget(
@@ -918,7 +3209,7 @@ fn render_add_wallet(frame: &mut Frame, app: &App, area: Rect) {
     
     frame.render_widget(
         Paragraph::new(app.input_buffer.clone())
-            .style(Style::default().fg(Color::Yellow))
+            .style(Style::default().fg(app.theme.selected))
             .block(Block::default().borders(Borders::ALL).title("File Path")),
         input_layout[1],
     );
@@ -930,142 +3221,720 @@ fn render_add_wallet(frame: &mut Frame, app: &App, area: Rect) {
     );
 }
 
-fn render_confirm_delete(frame: &mut Frame, app: &App, area: Rect) {
-    let wallet_name = if let Some(selected) = app.selected_wallet {
-        if selected < app.wallets.len() {
-            &app.wallets[selected]
-        } else {
-            "Unknown wallet"
-        }
+fn render_locked(frame: &mut Frame, app: &App, area: Rect) {
+    let mut text = vec![
+        Line::from(""),
+        Line::from(Span::styled("svmai is locked", Style::default().fg(app.theme.title).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from("Wallet details were cleared after a period without input."),
+    ];
+    if app.unlock_needs_passphrase {
+        text.push(Line::from("Type SVMAI_PASSPHRASE and press Enter to unlock, or Esc to quit."));
+        text.push(Line::from(""));
+        text.push(Line::from(format!("Passphrase: {}", "*".repeat(app.unlock_passphrase.chars().count()))));
     } else {
-        "Unknown wallet"
-    };
+        text.push(Line::from("Press Enter to unlock with the keychain and reload them, or q to quit."));
+    }
     
-    let confirm_layout = Layout::default()
+    frame.render_widget(
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL)),
+        area,
+    );
+}
+
+fn render_error_log(frame: &mut Frame, app: &App, area: Rect) {
+    if app.error_log.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No errors so far.")
+                .alignment(Alignment::Center)
+                .block(Block::default().borders(Borders::ALL)),
+            area,
+        );
+        return;
+    }
+    
+    // Newest first
+    let lines: Vec<Line> = app.error_log.iter().rev()
+        .map(|(at, message)| Line::from(vec![
+            Span::styled(format!("{:>5}s ago  ", at.elapsed().as_secs()), Style::default().fg(Color::DarkGray)),
+            Span::styled(message.clone(), Style::default().fg(app.theme.error)),
+        ]))
+        .collect();
+    
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((app.error_log_scroll.min(u16::MAX as usize) as u16, 0))
+            .block(Block::default().borders(Borders::ALL)
+                .title(format!("{} Recent Error(s)", app.error_log.len()))),
+        area,
+    );
+}
+
+fn render_export_address_book(frame: &mut Frame, app: &App, area: Rect) {
+    let input_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // Warning
-            Constraint::Length(3), // Wallet name
-            Constraint::Length(3), // Confirmation options
+            Constraint::Length(3), // Instructions
+            Constraint::Length(3), // Input field
+            Constraint::Min(0),    // Extra space
         ])
         .split(area);
     
     frame.render_widget(
-        Paragraph::new("Are you sure you want to delete this wallet?")
-            .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
-            .alignment(Alignment::Center)
-            .block(Block::default().borders(Borders::ALL).title("Warning")),
-        confirm_layout[0],
+        Paragraph::new("Names and public addresses of all wallets are written as JSON. No keys are included.")
+            .alignment(Alignment::Left)
+            .block(Block::default().borders(Borders::ALL)),
+        input_layout[0],
     );
     
     frame.render_widget(
-        Paragraph::new(wallet_name)
-            .style(Style::default().fg(Color::Yellow))
-            .alignment(Alignment::Center)
-            .block(Block::default().borders(Borders::ALL).title("Wallet to Delete")),
-        confirm_layout[1],
+        Paragraph::new(app.input_buffer.clone())
+            .style(Style::default().fg(app.theme.selected))
+            .block(Block::default().borders(Borders::ALL).title("Destination File")),
+        input_layout[1],
     );
     
-    let options = if app.confirm_action {
-        "▶ Yes   No"
-    } else {
-        "Yes   ▶ No"
-    };
-    
     frame.render_widget(
-        Paragraph::new(options)
-            .alignment(Alignment::Center)
-            .block(Block::default().borders(Borders::ALL)),
-        confirm_layout[2],
+        Paragraph::new("Press Enter to export or Esc to cancel")
+            .alignment(Alignment::Center),
+        input_layout[2],
     );
 }
 
-fn render_search_wallets(frame: &mut Frame, app: &mut App, area: Rect) {
-    let search_layout = Layout::default()
+fn render_scan_wallets(frame: &mut Frame, app: &App, area: Rect) {
+    let scan_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // Search input
-            Constraint::Min(0),    // Instructions
+            Constraint::Length(3), // Directory input
+            Constraint::Length(3), // Scan progress
+            Constraint::Min(0),    // Results
         ])
         .split(area);
     
     frame.render_widget(
-        Paragraph::new(app.search_query.clone())
-            .style(Style::default().fg(Color::Yellow))
-            .block(Block::default().borders(Borders::ALL).title("Search Query")),
-        search_layout[0],
+        Paragraph::new(app.input_buffer.clone())
+            .style(Style::default().fg(app.theme.selected))
+            .block(Block::default().borders(Borders::ALL).title("Directory")),
+        scan_layout[0],
     );
     
+    let scanned = app.scan_scanned.load(Ordering::SeqCst);
+    let progress_text = if app.scan_thread.is_some() {
+        format!("Scanning... {} files checked (Press Esc to cancel)", scanned)
+    } else if !app.scan_results.is_empty() {
+        format!("{} wallet file(s) found", app.scan_results.len())
+    } else {
+        "Press Enter to scan this directory for Solana wallet files".to_string()
+    };
     frame.render_widget(
-        Paragraph::new("Type to search wallets by name\nPress Enter to apply search or Esc to cancel")
-            .alignment(Alignment::Center),
-        search_layout[1],
+        Paragraph::new(progress_text)
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title("Progress")),
+        scan_layout[1],
     );
+    
+    let items: Vec<ListItem> = app.scan_results.iter()
+        .map(|path| ListItem::new(path.clone()))
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("Wallet Files"))
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(">> ");
+    
+    let mut state = ListState::default();
+    if !app.scan_results.is_empty() {
+        state.select(Some(app.scan_selected));
+    }
+    frame.render_stateful_widget(list, scan_layout[2], &mut state);
 }
 
-fn render_batch_operations(frame: &mut Frame, _app: &App, area: Rect) {
-    // This is a placeholder for batch operations UI
-    // In a real implementation, this would include options for batch sending tokens
-    frame.render_widget(
-        Paragraph::new("Batch Operations (Coming Soon)\n\nThis feature will allow you to send tokens to multiple recipients in a single operation.")
-            .alignment(Alignment::Center)
-            .block(Block::default().borders(Borders::ALL).title("Batch Operations")),
-        area,
-    );
+// Formats key bytes as the JSON array Solana keypair files use. The text is
+// written into one buffer sized up front, so no copy of it is left behind.
+fn format_secret_bytes(key_bytes: &[u8]) -> Zeroizing<String> {
+    use std::fmt::Write;
+    
+    let mut text = Zeroizing::new(String::with_capacity(2 + key_bytes.len() * 4));
+    text.push('[');
+    for (index, byte) in key_bytes.iter().enumerate() {
+        if index > 0 {
+            text.push(',');
+        }
+        // Writing into a String cannot fail
+        let _ = write!(text, "{}", byte);
+    }
+    text.push(']');
+    text
 }
 
-fn render_create_vanity_wallet(frame: &mut Frame, app: &App, area: Rect) {
-    let input_layout = Layout::default()
+fn render_reveal_secret(frame: &mut Frame, app: &App, area: Rect) {
+    let layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // Title
-            Constraint::Length(3), // Wallet name input
-            Constraint::Length(3), // Prefix input (disabled, fixed to "ai")
-            Constraint::Length(3), // Timeout input
-            Constraint::Min(0),    // Instructions
+            Constraint::Length(4), // Warning
+            Constraint::Min(3),    // Confirmation input or secret
         ])
         .split(area);
     
+    let wallet_name = app.selected_wallet
+        .and_then(|selected| app.wallets.get(selected))
+        .map(String::as_str)
+        .unwrap_or("");
     frame.render_widget(
-        Paragraph::new("Create a new wallet with a vanity address")
-            .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
-            .alignment(Alignment::Center)
+        Paragraph::new(format!(
+            "Anyone who sees the secret key of '{}' can take its funds. Only reveal it somewhere private.",
+            wallet_name
+        ))
+            .style(Style::default().fg(Color::Yellow))
+            .wrap(Wrap { trim: true })
             .block(Block::default().borders(Borders::ALL)),
-        input_layout[0],
+        layout[0],
     );
     
-    frame.render_widget(
-        Paragraph::new(app.vanity_wallet_name.clone())
-            .style(Style::default().fg(Color::Yellow))
-            .block(Block::default().borders(Borders::ALL).title("Wallet Name")),
-        input_layout[1],
-    );
+    match &app.revealed_secret {
+        None => {
+            frame.render_widget(
+                Paragraph::new(app.input_buffer.clone())
+                    .style(Style::default().fg(app.theme.selected))
+                    .block(Block::default().borders(Borders::ALL)
+                        .title(format!("Type {} and press Enter", REVEAL_CONFIRMATION))),
+                layout[1],
+            );
+        },
+        Some(key_bytes) => {
+            let text = if app.secret_visible {
+                format_secret_bytes(key_bytes)
+            } else {
+                Zeroizing::new(format!("[{}]", vec!["***"; key_bytes.len()].join(",")))
+            };
+            frame.render_widget(
+                Paragraph::new(text.as_str())
+                    .wrap(Wrap { trim: true })
+                    .block(Block::default().borders(Borders::ALL)
+                        .title(format!("Secret Key ({} bytes)", key_bytes.len()))),
+                layout[1],
+            );
+        },
+    }
+}
+
+fn render_rename_wallet(frame: &mut Frame, app: &App, area: Rect) {
+    let current_name = match app.selected_wallet {
+        Some(selected) if selected < app.wallets.len() => app.wallets[selected].as_str(),
+        _ => "Unknown wallet",
+    };
+
+    let input_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Current name
+            Constraint::Length(3), // Input field
+            Constraint::Min(0),    // Extra space
+        ])
+        .split(area);
     
     frame.render_widget(
-        Paragraph::new(app.vanity_config.prefix.clone())
+        Paragraph::new(current_name)
             .style(Style::default().fg(Color::DarkGray))
-            .block(Block::default().borders(Borders::ALL).title("Address Prefix (Fixed)")),
-        input_layout[2],
+            .block(Block::default().borders(Borders::ALL).title("Current Name")),
+        input_layout[0],
     );
     
     frame.render_widget(
-        Paragraph::new(app.vanity_config.timeout_seconds.to_string())
-            .style(Style::default().fg(Color::Yellow))
-            .block(Block::default().borders(Borders::ALL).title("Timeout (seconds)")),
-        input_layout[3],
+        Paragraph::new(app.input_buffer.clone())
+            .style(Style::default().fg(app.theme.selected))
+            .block(Block::default().borders(Borders::ALL).title("New Name")),
+        input_layout[1],
     );
     
     frame.render_widget(
-        Paragraph::new("Press Enter to start generating or Esc to cancel\n\nNote: Finding a vanity address may take some time depending on the prefix.")
+        Paragraph::new("Press Enter to rename or Esc to cancel")
             .alignment(Alignment::Center),
-        input_layout[4],
+        input_layout[2],
     );
 }
 
-fn render_vanity_progress(frame: &mut Frame, app: &App, area: Rect) {
-    let progress_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
+// Shortest query matched against addresses; shorter ones would hit almost
+// every address and drown out the name matches
+const PUBKEY_SEARCH_MIN_CHARS: usize = 4;
+
+// Whether `query` is part of the address. Base58 is case-sensitive, so unlike
+// names the address has to match exactly rather than fuzzily.
+fn pubkey_matches_query(pubkey: &str, query: &str) -> bool {
+    let query = query.trim();
+    query.chars().count() >= PUBKEY_SEARCH_MIN_CHARS && pubkey.contains(query)
+}
+
+fn render_confirm_delete(frame: &mut Frame, app: &App, area: Rect) {
+    let (question, wallet_name) = if matches!(app.current_view, View::ConfirmBatchDelete) {
+        let mut names: Vec<&str> = app.marked_wallets.iter()
+            .filter_map(|&index| app.wallets.get(index).map(String::as_str))
+            .collect();
+        names.sort_unstable();
+        (format!("Are you sure you want to delete these {} wallets?", names.len()), names.join(", "))
+    } else {
+        let name = app.selected_wallet
+            .and_then(|selected| app.wallets.get(selected))
+            .map_or("Unknown wallet", String::as_str);
+        ("Are you sure you want to delete this wallet?".to_string(), name.to_string())
+    };
+    
+    let confirm_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Warning
+            Constraint::Length(3), // Wallet name
+            Constraint::Length(3), // Confirmation options
+        ])
+        .split(area);
+    
+    frame.render_widget(
+        Paragraph::new(question)
+            .style(Style::default().fg(app.theme.error).add_modifier(Modifier::BOLD))
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title("Warning")),
+        confirm_layout[0],
+    );
+    
+    frame.render_widget(
+        Paragraph::new(wallet_name)
+            .style(Style::default().fg(Color::Yellow))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL).title("Wallet to Delete")),
+        confirm_layout[1],
+    );
+    
+    if app.strict_delete {
+        // The typed name, with its border showing whether Enter will delete
+        let (title, color) = if app.delete_confirmation_matches() {
+            ("Matches - press Enter to delete".to_string(), app.theme.error)
+        } else {
+            (format!("Type '{}' to delete - does not match", app.delete_confirmation_text()), Color::Gray)
+        };
+        frame.render_widget(
+            Paragraph::new(app.input_buffer.as_str())
+                .style(Style::default().fg(color))
+                .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(color)).title(title)),
+            confirm_layout[2],
+        );
+        return;
+    }
+    
+    let options = if app.confirm_action {
+        "▶ Yes   No"
+    } else {
+        "Yes   ▶ No"
+    };
+    
+    frame.render_widget(
+        Paragraph::new(options)
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL)),
+        confirm_layout[2],
+    );
+}
+
+fn render_search_wallets(frame: &mut Frame, app: &mut App, area: Rect) {
+    let search_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Search input
+            Constraint::Min(0),    // Instructions
+        ])
+        .split(area);
+    
+    frame.render_widget(
+        Paragraph::new(app.search_query.clone())
+            .style(Style::default().fg(app.theme.selected))
+            .block(Block::default().borders(Borders::ALL).title("Search Query")),
+        search_layout[0],
+    );
+    
+    frame.render_widget(
+        Paragraph::new("Type to search wallets by name or paste part of an address\nPress Enter to apply search or Esc to cancel")
+            .alignment(Alignment::Center),
+        search_layout[1],
+    );
+}
+
+fn render_batch_operations(frame: &mut Frame, app: &App, area: Rect) {
+    let form = &app.batch_form;
+    let batch_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Source wallet
+            Constraint::Length(3), // Token mint
+            Constraint::Min(4),    // Recipients
+            Constraint::Length(10), // Simulation log
+        ])
+        .split(area);
+    
+    // Highlight the field that receives typed input
+    let field_styles = |field: BatchField| {
+        if form.focus == field {
+            (
+                Style::default().fg(app.theme.selected).add_modifier(Modifier::BOLD),
+                Style::default().fg(app.theme.selected),
+            )
+        } else {
+            (Style::default().fg(Color::Gray), Style::default())
+        }
+    };
+    
+    let source = app.wallets.get(form.source)
+        .map_or("No wallets available".to_string(), |name| format!("◀ {} ▶", name));
+    let (text_style, border_style) = field_styles(BatchField::Source);
+    frame.render_widget(
+        Paragraph::new(source)
+            .style(text_style)
+            .block(Block::default().borders(Borders::ALL).border_style(border_style).title("Send From (←/→)")),
+        batch_layout[0],
+    );
+    
+    let (text_style, border_style) = field_styles(BatchField::TokenMint);
+    let mint = if form.token_mint.is_empty() && form.focus != BatchField::TokenMint {
+        "SOL".to_string()
+    } else {
+        form.token_mint.clone()
+    };
+    frame.render_widget(
+        Paragraph::new(mint)
+            .style(text_style)
+            .block(Block::default().borders(Borders::ALL).border_style(border_style).title("Token Mint (empty for SOL)")),
+        batch_layout[1],
+    );
+    
+    let recipient_lines: Vec<Line> = form.recipients.iter()
+        .enumerate()
+        .map(|(row, recipient)| {
+            let (address_style, _) = field_styles(BatchField::Address(row));
+            let (amount_style, _) = field_styles(BatchField::Amount(row));
+            let address = if recipient.address.is_empty() { "<address>" } else { &recipient.address };
+            let amount = if recipient.amount.is_empty() { "<amount>" } else { &recipient.amount };
+            let validity = match (&recipient.address_error, recipient.address.is_empty()) {
+                (Some(error), _) => Span::styled(format!("  ✗ {}", error), Style::default().fg(app.theme.error)),
+                (None, false) => Span::styled("  ✓", Style::default().fg(app.theme.success)),
+                (None, true) => Span::raw(""),
+            };
+            Line::from(vec![
+                Span::raw(format!("{:>3}. ", row + 1)),
+                Span::styled(address.to_string(), address_style),
+                Span::raw("  "),
+                Span::styled(amount.to_string(), amount_style),
+                validity,
+            ])
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(recipient_lines)
+            .block(Block::default().borders(Borders::ALL).title("Recipients (Ins: add, Del: remove)")),
+        batch_layout[2],
+    );
+    
+    let log = form.log.as_deref().unwrap_or("Press Enter to simulate the transfer.");
+    frame.render_widget(
+        Paragraph::new(log)
+            .wrap(Wrap { trim: false })
+            .scroll((form.log_scroll, 0))
+            .block(Block::default().borders(Borders::ALL).title("Simulation Log")),
+        batch_layout[3],
+    );
+}
+
+fn render_token_mixing(frame: &mut Frame, app: &App, area: Rect) {
+    let form = &app.mixing_form;
+    let mixing_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(8), // Source wallets
+            Constraint::Length(3), // Destination addresses
+            Constraint::Length(3), // Amount and steps
+            Constraint::Min(0),    // Generated plan
+        ])
+        .split(area);
+    
+    // Highlight the field that receives input
+    let field_styles = |field: MixingField| {
+        if form.focus == field {
+            (
+                Style::default().fg(app.theme.selected).add_modifier(Modifier::BOLD),
+                Style::default().fg(app.theme.selected),
+            )
+        } else {
+            (Style::default().fg(Color::Gray), Style::default())
+        }
+    };
+    
+    let (_, border_style) = field_styles(MixingField::Sources);
+    let source_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(border_style)
+        .title(format!("Source Wallets ({} selected, Space to toggle)", form.sources.len()));
+    if app.wallets.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No wallets available to mix from.").block(source_block),
+            mixing_layout[0],
+        );
+    } else {
+        let items: Vec<ListItem> = app.wallets.iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let mark = if form.sources.contains(&index) { "[✓] " } else { "[ ] " };
+                ListItem::new(format!("{}{}", mark, name))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(source_block)
+            .highlight_style(Style::default().fg(app.theme.selected).add_modifier(Modifier::BOLD));
+        let mut state = ListState::default().with_selected(Some(form.source_cursor));
+        frame.render_stateful_widget(list, mixing_layout[0], &mut state);
+    }
+    
+    let (text_style, border_style) = field_styles(MixingField::Destinations);
+    frame.render_widget(
+        Paragraph::new(form.destinations.clone())
+            .style(text_style)
+            .block(Block::default().borders(Borders::ALL).border_style(border_style).title("Destination Addresses (comma separated)")),
+        mixing_layout[1],
+    );
+    
+    let number_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(1, 3), Constraint::Ratio(1, 3)])
+        .split(mixing_layout[2]);
+    let numbers = [
+        (MixingField::TotalAmount, "Total Amount (SOL)", &form.total_amount),
+        (MixingField::Steps, "Steps", &form.steps),
+        (MixingField::Seed, "Seed (optional, reproducible plan)", &form.seed),
+    ];
+    for (index, (field, title, value)) in numbers.into_iter().enumerate() {
+        let (text_style, border_style) = field_styles(field);
+        frame.render_widget(
+            Paragraph::new(value.clone())
+                .style(text_style)
+                .block(Block::default().borders(Borders::ALL).border_style(border_style).title(title)),
+            number_layout[index],
+        );
+    }
+    
+    let plan = form.plan.as_deref().unwrap_or("Press Enter to generate a simulated mixing plan.");
+    frame.render_widget(
+        Paragraph::new(plan)
+            .wrap(Wrap { trim: false })
+            .scroll((form.plan_scroll, 0))
+            .block(Block::default().borders(Borders::ALL).title("Mixing Plan")),
+        mixing_layout[3],
+    );
+}
+
+fn render_import_seed(frame: &mut Frame, app: &App, area: Rect) {
+    let input_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Seed phrase input
+            Constraint::Length(3), // Account index input
+            Constraint::Length(3), // Name prefix input
+            Constraint::Length(4), // Address preview
+            Constraint::Min(0),    // Instructions
+        ])
+        .split(area);
+    
+    let form = &app.seed_form;
+    // The phrase is masked letter by letter, keeping the gaps so the word count shows
+    let masked_phrase: String = form.phrase.chars().map(|c| if c == ' ' { ' ' } else { '*' }).collect();
+    let fields = [
+        (SeedField::Phrase, "Seed Phrase", masked_phrase),
+        (SeedField::Account, "Account Index", form.account.clone()),
+        (SeedField::NamePrefix, "Name Prefix", form.name_prefix.clone()),
+    ];
+    for (index, (field, title, value)) in fields.into_iter().enumerate() {
+        let (text_style, border_style) = if form.focus == field {
+            (
+                Style::default().fg(app.theme.selected).add_modifier(Modifier::BOLD),
+                Style::default().fg(app.theme.selected),
+            )
+        } else {
+            (Style::default().fg(Color::Gray), Style::default())
+        };
+        frame.render_widget(
+            Paragraph::new(value)
+                .style(text_style)
+                .block(Block::default().borders(Borders::ALL).border_style(border_style).title(title)),
+            input_layout[index],
+        );
+    }
+    
+    let preview = match (form.preview, form.account_index()) {
+        (Some(pubkey), Ok(account)) => Paragraph::new(format!(
+            "{}\nPath {} - stored as '{}_{}'",
+            pubkey,
+            wallet_manager::seed_account_derivation_path(account),
+            form.name_prefix,
+            account
+        ))
+            .style(Style::default().fg(app.theme.success)),
+        _ => Paragraph::new("Press Enter to derive the address of this account")
+            .style(Style::default().fg(Color::Gray)),
+    };
+    frame.render_widget(
+        preview.block(Block::default().borders(Borders::ALL).title("Address Preview")),
+        input_layout[3],
+    );
+    
+    let instructions = if form.preview.is_some() {
+        "Check the address above, then press Enter to import it or Esc to change the form"
+    } else {
+        "Enter the 12 or 24 word phrase and the account to import (0 is the first)\nTab/Shift+Tab to switch fields, Esc to cancel"
+    };
+    frame.render_widget(
+        Paragraph::new(instructions)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        input_layout[4],
+    );
+}
+
+fn render_create_vanity_wallet(frame: &mut Frame, app: &App, area: Rect) {
+    let input_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Title
+            Constraint::Length(3), // Wallet name input
+            Constraint::Length(3), // Prefix input
+            Constraint::Length(3), // Suffix input
+            Constraint::Length(3), // Timeout input
+            Constraint::Length(3), // Thread count input
+            Constraint::Min(0),    // Instructions
+        ])
+        .split(area);
+    
+    frame.render_widget(
+        Paragraph::new("Create a new wallet with a vanity address")
+            .style(Style::default().fg(app.theme.title).add_modifier(Modifier::BOLD))
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL)),
+        input_layout[0],
+    );
+    
+    let fields = [
+        (VanityField::WalletName, "Wallet Name", app.vanity_wallet_name.clone()),
+        (VanityField::Prefix, "Address Prefix", app.vanity_config.prefix.clone()),
+        (VanityField::Suffix, "Address Suffix (optional)", app.vanity_config.suffix.clone()),
+        (VanityField::Timeout, "Timeout (seconds)", app.vanity_timeout_input.clone()),
+        (VanityField::ThreadCount, "Threads", app.vanity_threads_input.clone()),
+    ];
+    for (index, (field, title, value)) in fields.into_iter().enumerate() {
+        // Highlight the field that receives typed input
+        let (text_style, border_style) = if app.vanity_focus == field {
+            (
+                Style::default().fg(app.theme.selected).add_modifier(Modifier::BOLD),
+                Style::default().fg(app.theme.selected),
+            )
+        } else {
+            (Style::default().fg(Color::Gray), Style::default())
+        };
+        frame.render_widget(
+            Paragraph::new(value)
+                .style(text_style)
+                .block(Block::default().borders(Borders::ALL).border_style(border_style).title(title)),
+            input_layout[index + 1],
+        );
+    }
+    
+    let instructions = if app.vanity_confirming {
+        Paragraph::new(vanity_confirmation(&app.vanity_config))
+            .style(Style::default().fg(app.theme.warning))
+    } else {
+        Paragraph::new(format!(
+            "Tab/Shift+Tab to switch fields, Enter to start generating or Esc to cancel\n\n{}",
+            vanity_time_warning(&app.vanity_config)
+        ))
+    };
+    frame.render_widget(
+        instructions
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        input_layout[6],
+    );
+}
+
+// Summary shown before a long search: the estimated run time, and a warning
+// when the search is expected to outlast its timeout
+fn vanity_confirmation(config: &VanityConfig) -> String {
+    let attempts = config.expected_attempts();
+    let rate = vanity_wallet::ESTIMATED_ATTEMPTS_PER_THREAD * config.thread_count.max(1) as f64;
+    let mut text = format!(
+        "The {} needs about {:.0} attempts, which may take approximately {} on {} thread(s).",
+        config.target_description(),
+        attempts,
+        vanity_wallet::format_estimated_duration(attempts / rate),
+        config.thread_count
+    );
+    if !config.suffix.is_empty() {
+        // The two ends multiply, which is easy to underestimate
+        text.push_str(&format!(
+            "\n\nThat is ~{:.0} for the prefix times ~{} for the suffix.",
+            config.prefix_attempts(),
+            vanity_wallet::estimate_difficulty_combined("", &config.suffix, false)
+        ));
+    }
+    if vanity_wallet::likely_to_time_out(attempts, rate, config.timeout_seconds) {
+        text.push_str(&format!(
+            "\n\nThis is longer than the {}-second timeout, so the search will almost certainly time out.",
+            config.timeout_seconds
+        ));
+    }
+    text.push_str("\n\nPress Enter again to start or Esc to go back.");
+    text
+}
+
+// Describes how long a search is likely to take, based on the expected attempts
+fn vanity_time_warning(config: &VanityConfig) -> String {
+    let attempts = config.expected_attempts();
+    let duration = if attempts < 1e5 {
+        "should only take a few seconds"
+    } else if attempts < 1e7 {
+        "may take several minutes"
+    } else if attempts < 1e9 {
+        "may take hours"
+    } else {
+        "may take days or longer"
+    };
+    let length = config.prefix.chars().count() + config.suffix.chars().count();
+    format!(
+        "Note: {} target character(s) need about {:.0} attempts on average and {}.",
+        length,
+        attempts,
+        duration
+    )
+}
+
+// Fraction of the expected attempts made so far, clamped to 1.0, or None once
+// the estimate has been exceeded and progress can no longer be predicted
+fn vanity_progress_fraction(attempts: u64, expected: f64) -> Option<f64> {
+    if !expected.is_finite() || expected <= 0.0 {
+        return None;
+    }
+    let attempts = attempts as f64;
+    if attempts > expected {
+        None
+    } else {
+        Some((attempts / expected).clamp(0.0, 1.0))
+    }
+}
+
+fn render_vanity_progress(frame: &mut Frame, app: &App, area: Rect) {
+    let progress_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
             Constraint::Length(3), // Title
             Constraint::Length(3), // Attempts
             Constraint::Length(3), // Speed
@@ -1075,9 +3944,14 @@ fn render_vanity_progress(frame: &mut Frame, app: &App, area: Rect) {
         ])
         .split(area);
     
+    let paused = app.vanity_paused.load(Ordering::SeqCst);
     frame.render_widget(
-        Paragraph::new(format!("Generating wallet with prefix '{}'", app.vanity_config.prefix))
-            .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+        Paragraph::new(format!(
+            "Generating wallet with {}{}",
+            app.vanity_config.target_description(),
+            if paused { " (paused)" } else { "" }
+        ))
+            .style(Style::default().fg(app.theme.title).add_modifier(Modifier::BOLD))
             .alignment(Alignment::Center)
             .block(Block::default().borders(Borders::ALL)),
         progress_layout[0],
@@ -1102,8 +3976,13 @@ fn render_vanity_progress(frame: &mut Frame, app: &App, area: Rect) {
         progress_layout[1],
     );
     
+    let speed_text = if paused {
+        "paused".to_string()
+    } else {
+        format!("{:.2} attempts/sec", speed)
+    };
     frame.render_widget(
-        Paragraph::new(format!("{:.2} attempts/sec", speed))
+        Paragraph::new(speed_text)
             .style(Style::default().fg(Color::Green))
             .alignment(Alignment::Center)
             .block(Block::default().borders(Borders::ALL).title("Speed")),
@@ -1118,28 +3997,77 @@ fn render_vanity_progress(frame: &mut Frame, app: &App, area: Rect) {
         progress_layout[3],
     );
     
-    // Add a visual progress indicator (spinner)
-    let spinner_chars = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
-    let spinner_idx = (elapsed as usize / 1) % spinner_chars.len();
-    let spinner = spinner_chars[spinner_idx];
-    
-    let progress_text = format!("{} Searching... (Press Esc to cancel)", spinner);
+    // Progress against the expected number of attempts for this target
+    let expected = app.vanity_config.expected_attempts();
+    let gauge = match vanity_progress_fraction(attempts, expected) {
+        Some(fraction) => Gauge::default()
+            .gauge_style(Style::default().fg(Color::Yellow))
+            .ratio(fraction)
+            .label(format!("{:.0}% of ~{:.0} expected attempts (Press Esc to cancel)", fraction * 100.0, expected)),
+        None => {
+            // Past the estimate: sweep the bar so it reads as indeterminate
+            let pulse = (elapsed * 0.5).fract();
+            Gauge::default()
+                .gauge_style(Style::default().fg(Color::Magenta))
+                .ratio(pulse)
+                .label("Past the estimate, still searching... (Press Esc to cancel)")
+        },
+    };
     
     frame.render_widget(
-        Paragraph::new(progress_text)
-            .style(Style::default().fg(Color::Yellow))
-            .alignment(Alignment::Center)
-            .block(Block::default().borders(Borders::ALL).title("Progress")),
+        gauge.block(Block::default().borders(Borders::ALL).title("Progress")),
         progress_layout[4],
     );
     
     frame.render_widget(
-        Paragraph::new("The wallet will be saved automatically when found.\nThis may take some time depending on luck.")
+        Paragraph::new(format!(
+            "Lifetime attempts for '{}': {}\n\nThe wallet will be saved automatically when found.\nThis may take some time depending on luck.",
+            app.vanity_stats_key(),
+            app.vanity_lifetime_attempts + attempts
+        ))
             .alignment(Alignment::Center),
         progress_layout[5],
     );
 }
 
+fn render_vault_recovery(frame: &mut Frame, app: &App, area: Rect) {
+    let recovery_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(5), // Explanation
+            Constraint::Length(3), // Current step
+            Constraint::Min(0),    // Extra space
+        ])
+        .split(area);
+    
+    frame.render_widget(
+        Paragraph::new("The wallet file was encrypted with a different key than the one in this keychain, \
+                        so its wallets cannot be opened. Restore a vault backup made with the old key, \
+                        or set the file aside and start with an empty vault.")
+            .style(Style::default().fg(app.theme.error))
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL).title("Wallet File Unreadable")),
+        recovery_layout[0],
+    );
+    
+    let (title, text) = match &app.recovery_step {
+        RecoveryStep::Choose => ("Options", "r: Restore from a backup file    f: Start fresh    q: Quit".to_string()),
+        RecoveryStep::BackupPath => ("Backup File", app.input_buffer.clone()),
+        RecoveryStep::Passphrase(_) => ("Backup Passphrase", "*".repeat(app.input_buffer.chars().count())),
+        RecoveryStep::ConfirmFresh => (
+            "Start Fresh?",
+            "The old file is kept next to the new vault. Press y to continue or n to go back.".to_string(),
+        ),
+    };
+    
+    frame.render_widget(
+        Paragraph::new(text)
+            .style(Style::default().fg(app.theme.selected))
+            .block(Block::default().borders(Borders::ALL).title(title)),
+        recovery_layout[1],
+    );
+}
+
 fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     let status_layout = Layout::default()
         .direction(Direction::Horizontal)
@@ -1152,10 +4080,10 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     // Status message
     let (status_text, status_style) = if let Some(status) = &app.status_message {
         let style = match status.status_type {
-            StatusType::Info => Style::default().fg(Color::Blue),
-            StatusType::Success => Style::default().fg(Color::Green),
-            StatusType::Error => Style::default().fg(Color::Red),
-            StatusType::Warning => Style::default().fg(Color::Yellow),
+            StatusType::Info => Style::default().fg(app.theme.info),
+            StatusType::Success => Style::default().fg(app.theme.success),
+            StatusType::Error => Style::default().fg(app.theme.error),
+            StatusType::Warning => Style::default().fg(app.theme.warning),
         };
         (status.message.clone(), style)
     } else {
@@ -1171,15 +4099,34 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     
     // Help hint based on current view
     let help_hint = match app.current_view {
-        View::WalletList => "h: Help | a: Add | v: Vanity | d: Delete | /: Search | Enter: Details | q: Quit",
-        View::WalletDetail => "Esc: Back | r: Refresh | b: Batch Operations",
+        View::WalletList => "h: Help | a: Add | m: Seed Phrase | f: Scan | e: Export Addresses | E: Errors | s: Sort | u: SOL/Lamports | v: Vanity | d: Delete | Space: Mark | D: Delete Marked | /: Search | Enter: Details | q: Quit",
+        View::WalletDetail if !rpc::is_mainnet_url(&app.rpc_url) => "Esc: Back | ↑/↓: Transactions | r: Refresh | R: Refresh Balance | u: SOL/Lamports | n: Rename | a: Airdrop | b: Batch Operations | k: Reveal Key",
+        View::WalletDetail => "Esc: Back | ↑/↓: Transactions | r: Refresh | R: Refresh Balance | u: SOL/Lamports | n: Rename | b: Batch Operations | k: Reveal Key",
         View::Help => "Esc: Back",
-        View::AddWallet => "Enter: Confirm | Esc: Cancel",
-        View::ConfirmDelete => "←/→: Select | Enter: Confirm",
+        View::AddWallet | View::ExportAddressBook => "Enter: Confirm | Esc: Cancel",
+        View::RevealSecret if app.revealed_secret.is_some() => "s: Show/Hide | c: Copy | Esc: Back",
+        View::RevealSecret => "Enter: Confirm | Esc: Back",
+        View::Errors => "↑/↓: Scroll | c: Clear | Esc: Back",
+        View::Locked => "Enter: Unlock | Esc: Quit",
+        View::ConfirmDelete | View::ConfirmBatchDelete if app.strict_delete => "Type the name shown | Enter: Delete | Esc: Cancel",
+        View::ConfirmDelete | View::ConfirmBatchDelete => "y: Delete | n/Esc: Cancel | ←/→: Select | Enter: Confirm",
         View::SearchWallets => "Enter: Apply | Esc: Cancel",
-        View::BatchOperations => "Esc: Back",
-        View::CreateVanityWallet => "Enter: Start | Esc: Cancel",
-        View::VanityProgress => "Esc: Cancel",
+        View::BatchOperations => "Tab: Next | Ins/Del: Add/Remove Row | Enter: Simulate | PgUp/PgDn: Scroll Log | F2: Save Log | Esc: Back",
+        View::CreateVanityWallet if app.vanity_confirming => "Enter: Start Anyway | Esc: Back",
+        View::CreateVanityWallet => "Tab: Next Field | Enter: Start | Esc: Cancel",
+        View::VanityProgress => "Space: Pause/Resume | Esc: Cancel",
+        View::RenameWallet => "Enter: Rename | Esc: Cancel",
+        View::ScanWallets if app.scan_thread.is_some() => "Esc: Cancel scan",
+        View::ScanWallets if !app.scan_results.is_empty() => "↑/↓: Select | Enter: Import | Esc: Back",
+        View::ScanWallets => "Enter: Scan | Esc: Back",
+        View::TokenMixing => "Tab: Next | Space: Toggle Source | Enter: Generate | PgUp/PgDn: Scroll Plan | F2: Save Plan | Esc: Back",
+        View::ImportSeed if app.seed_form.preview.is_some() => "Enter: Import | Esc: Back to Form",
+        View::ImportSeed => "Tab: Next Field | Enter: Preview Address | Esc: Cancel",
+        View::VaultRecovery => match app.recovery_step {
+            RecoveryStep::Choose => "r: Restore Backup | f: Start Fresh | q: Quit",
+            RecoveryStep::BackupPath | RecoveryStep::Passphrase(_) => "Enter: Continue | Esc: Back",
+            RecoveryStep::ConfirmFresh => "y: Start Fresh | n/Esc: Back",
+        },
     };
     
     frame.render_widget(
@@ -1192,16 +4139,26 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
 
 // Handle key events
 fn handle_key_event(app: &mut App, key_code: KeyCode) {
+    app.last_input = Instant::now();
     match app.current_view {
         View::WalletList => handle_wallet_list_keys(app, key_code),
         View::WalletDetail => handle_wallet_detail_keys(app, key_code),
         View::Help => handle_help_keys(app, key_code),
         View::AddWallet => handle_add_wallet_keys(app, key_code),
-        View::ConfirmDelete => handle_confirm_delete_keys(app, key_code),
+        View::ConfirmDelete | View::ConfirmBatchDelete => handle_confirm_delete_keys(app, key_code),
         View::SearchWallets => handle_search_wallets_keys(app, key_code),
         View::BatchOperations => handle_batch_operations_keys(app, key_code),
         View::CreateVanityWallet => handle_create_vanity_wallet_keys(app, key_code),
         View::VanityProgress => handle_vanity_progress_keys(app, key_code),
+        View::RenameWallet => handle_rename_wallet_keys(app, key_code),
+        View::ScanWallets => handle_scan_wallets_keys(app, key_code),
+        View::TokenMixing => handle_token_mixing_keys(app, key_code),
+        View::ImportSeed => handle_import_seed_keys(app, key_code),
+        View::VaultRecovery => handle_vault_recovery_keys(app, key_code),
+        View::ExportAddressBook => handle_export_address_book_keys(app, key_code),
+        View::RevealSecret => handle_reveal_secret_keys(app, key_code),
+        View::Errors => handle_error_log_keys(app, key_code),
+        View::Locked => handle_locked_keys(app, key_code),
     }
 }
 
@@ -1216,30 +4173,47 @@ fn handle_wallet_list_keys(app: &mut App, key_code: KeyCode) {
         KeyCode::Down => {
             app.select_next_wallet();
         },
-        KeyCode::Enter => {
-            if app.selected_wallet.is_some() {
-                app.current_view = View::WalletDetail;
-            }
+        KeyCode::PageUp => {
+            app.select_wallet_page(false);
+        },
+        KeyCode::PageDown => {
+            app.select_wallet_page(true);
+        },
+        KeyCode::Enter if app.selected_wallet.is_some() => {
+            app.current_view = View::WalletDetail;
+            app.tx_selected = 0;
         },
         KeyCode::Char('h') | KeyCode::Char('H') => {
             app.current_view = View::Help;
         },
+        KeyCode::Char('u') | KeyCode::Char('U') => {
+            app.show_lamports = !app.show_lamports;
+        },
         KeyCode::Char('a') | KeyCode::Char('A') => {
             app.current_view = View::AddWallet;
             app.input_buffer.clear();
         },
         KeyCode::Char('v') | KeyCode::Char('V') => {
-            app.current_view = View::CreateVanityWallet;
-            app.vanity_wallet_name = "ai_wallet".to_string();
-            app.vanity_config.prefix = "ai".to_string();
-            app.vanity_config.timeout_seconds = 120;
-        },
-        KeyCode::Char('d') | KeyCode::Char('D') => {
-            if app.selected_wallet.is_some() && !app.wallets.is_empty() {
-                app.current_view = View::ConfirmDelete;
-                app.confirm_action = false; // Default to "No"
+            app.open_vanity_form();
+        },
+        KeyCode::Char('d')
+            if app.selected_wallet.is_some() && !app.wallets.is_empty() =>
+        {
+            app.open_delete_confirmation(View::ConfirmDelete);
+        },
+        KeyCode::Char(' ') => {
+            app.toggle_wallet_mark();
+        },
+        KeyCode::Char('D') => {
+            if app.marked_wallets.is_empty() {
+                app.set_status("Mark wallets with Space before deleting them in bulk".to_string(), StatusType::Warning);
+            } else {
+                app.open_delete_confirmation(View::ConfirmBatchDelete);
             }
         },
+        KeyCode::Esc if !app.marked_wallets.is_empty() => {
+            app.marked_wallets.clear();
+        },
         KeyCode::Char('r') | KeyCode::Char('R') => {
             app.load_wallets();
             app.set_status("Wallet list refreshed".to_string(), StatusType::Info);
@@ -1249,7 +4223,33 @@ fn handle_wallet_list_keys(app: &mut App, key_code: KeyCode) {
             app.search_query.clear();
         },
         KeyCode::Char('b') | KeyCode::Char('B') => {
-            app.current_view = View::BatchOperations;
+            app.open_batch_form();
+        },
+        KeyCode::Char('t') | KeyCode::Char('T') => {
+            app.open_mixing_form();
+        },
+        KeyCode::Char('m') | KeyCode::Char('M') => {
+            app.seed_form = SeedImportForm::new();
+            app.current_view = View::ImportSeed;
+        },
+        KeyCode::Char('s') | KeyCode::Char('S') => {
+            app.cycle_sort_mode();
+        },
+        KeyCode::Char('E') => {
+            app.error_log_scroll = 0;
+            app.current_view = View::Errors;
+        },
+        KeyCode::Char('e') => {
+            app.current_view = View::ExportAddressBook;
+            app.input_buffer = dirs::home_dir()
+                .map(|home| home.join("svmai_addresses.json").to_string_lossy().into_owned())
+                .unwrap_or_else(|| "svmai_addresses.json".to_string());
+        },
+        KeyCode::Char('f') | KeyCode::Char('F') => {
+            app.current_view = View::ScanWallets;
+            app.input_buffer = dirs::home_dir()
+                .map(|home| home.to_string_lossy().into_owned())
+                .unwrap_or_default();
         },
         _ => {}
     }
@@ -1260,15 +4260,41 @@ fn handle_wallet_detail_keys(app: &mut App, key_code: KeyCode) {
         KeyCode::Esc | KeyCode::Backspace => {
             app.current_view = View::WalletList;
         },
-        KeyCode::Char('r') | KeyCode::Char('R') => {
+        KeyCode::Up => {
+            app.select_transaction(false);
+        },
+        KeyCode::Down => {
+            app.select_transaction(true);
+        },
+        KeyCode::Char('r') => {
             app.load_wallets();
             app.set_status("Wallet details refreshed".to_string(), StatusType::Info);
         },
+        KeyCode::Char('R') => {
+            app.refresh_selected_balance();
+        },
+        KeyCode::Char('u') | KeyCode::Char('U') => {
+            app.show_lamports = !app.show_lamports;
+        },
+        KeyCode::Char('a') | KeyCode::Char('A') => {
+            app.request_airdrop();
+        },
         KeyCode::Char('b') | KeyCode::Char('B') => {
-            app.current_view = View::BatchOperations;
+            app.open_batch_form();
         },
-        _ => {}
-    }
+        KeyCode::Char('n') | KeyCode::Char('N') => {
+            if let Some(selected) = app.selected_wallet {
+                if selected < app.wallets.len() {
+                    app.input_buffer = app.wallets[selected].clone();
+                    app.current_view = View::RenameWallet;
+                }
+            }
+        },
+        KeyCode::Char('k') | KeyCode::Char('K') => {
+            app.open_reveal_secret();
+        },
+        _ => {}
+    }
 }
 
 fn handle_help_keys(app: &mut App, key_code: KeyCode) {
@@ -1300,10 +4326,213 @@ fn handle_add_wallet_keys(app: &mut App, key_code: KeyCode) {
     }
 }
 
-fn handle_confirm_delete_keys(app: &mut App, key_code: KeyCode) {
+fn handle_scan_wallets_keys(app: &mut App, key_code: KeyCode) {
+    // While a scan is running only cancelling is possible
+    if app.scan_thread.is_some() {
+        if key_code == KeyCode::Esc {
+            app.cancel_wallet_scan();
+        }
+        return;
+    }
+    
+    match key_code {
+        KeyCode::Esc => {
+            app.current_view = View::WalletList;
+            app.input_buffer.clear();
+            app.scan_results.clear();
+        },
+        KeyCode::Up if !app.scan_results.is_empty() => {
+            app.scan_selected = app.scan_selected.saturating_sub(1);
+        },
+        KeyCode::Down if app.scan_selected + 1 < app.scan_results.len() => {
+            app.scan_selected += 1;
+        },
+        KeyCode::Enter if !app.scan_results.is_empty() => {
+            app.import_selected_scan_result();
+        },
+        KeyCode::Enter => {
+            let input_buffer_clone = app.input_buffer.clone();
+            app.start_wallet_scan(input_buffer_clone);
+        },
+        KeyCode::Backspace => {
+            app.input_buffer.pop();
+            app.scan_results.clear();
+        },
+        KeyCode::Char(c) => {
+            app.input_buffer.push(c);
+            app.scan_results.clear();
+        },
+        _ => {}
+    }
+}
+
+fn handle_export_address_book_keys(app: &mut App, key_code: KeyCode) {
+    match key_code {
+        KeyCode::Esc => {
+            app.current_view = View::WalletList;
+            app.input_buffer.clear();
+        },
+        KeyCode::Enter => {
+            let input_buffer_clone = app.input_buffer.clone();
+            app.export_address_book(input_buffer_clone);
+        },
+        KeyCode::Backspace => {
+            app.input_buffer.pop();
+        },
+        KeyCode::Char(c) => {
+            app.input_buffer.push(c);
+        },
+        _ => {}
+    }
+}
+
+fn handle_locked_keys(app: &mut App, key_code: KeyCode) {
+    match key_code {
+        KeyCode::Enter => app.unlock(),
+        KeyCode::Esc => {
+            app.should_quit = true;
+        },
+        KeyCode::Backspace if app.unlock_needs_passphrase => {
+            app.unlock_passphrase.pop();
+        },
+        KeyCode::Char(c) if app.unlock_needs_passphrase => {
+            app.unlock_passphrase.push(c);
+        },
+        KeyCode::Char('q') | KeyCode::Char('Q') => {
+            app.should_quit = true;
+        },
+        _ => {}
+    }
+}
+
+fn handle_error_log_keys(app: &mut App, key_code: KeyCode) {
+    match key_code {
+        KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('q') => {
+            app.current_view = View::WalletList;
+        },
+        KeyCode::Up => {
+            app.error_log_scroll = app.error_log_scroll.saturating_sub(1);
+        },
+        KeyCode::Down => {
+            app.error_log_scroll = (app.error_log_scroll + 1).min(app.error_log.len().saturating_sub(1));
+        },
+        KeyCode::Char('c') | KeyCode::Char('C') => {
+            app.error_log.clear();
+            app.error_log_scroll = 0;
+        },
+        _ => {}
+    }
+}
+
+fn handle_reveal_secret_keys(app: &mut App, key_code: KeyCode) {
+    if app.revealed_secret.is_some() {
+        match key_code {
+            KeyCode::Esc | KeyCode::Backspace => app.leave_reveal_secret(),
+            KeyCode::Char('s') | KeyCode::Char('S') => {
+                app.secret_visible = !app.secret_visible;
+            },
+            KeyCode::Char('c') | KeyCode::Char('C') => app.copy_revealed_secret(),
+            _ => {}
+        }
+        return;
+    }
+    
+    match key_code {
+        KeyCode::Esc => app.leave_reveal_secret(),
+        KeyCode::Enter => app.reveal_selected_secret(),
+        KeyCode::Backspace => {
+            app.input_buffer.pop();
+        },
+        KeyCode::Char(c) => {
+            app.input_buffer.push(c);
+        },
+        _ => {}
+    }
+}
+
+fn handle_rename_wallet_keys(app: &mut App, key_code: KeyCode) {
     match key_code {
         KeyCode::Esc => {
+            app.current_view = View::WalletDetail;
+            app.input_buffer.clear();
+        },
+        KeyCode::Enter => {
+            let input_buffer_clone = app.input_buffer.clone();
+            app.rename_selected_wallet(input_buffer_clone);
+        },
+        KeyCode::Backspace => {
+            app.input_buffer.pop();
+        },
+        KeyCode::Char(c) => {
+            app.input_buffer.push(c);
+        },
+        _ => {}
+    }
+}
+
+fn handle_vault_recovery_keys(app: &mut App, key_code: KeyCode) {
+    match (app.recovery_step.clone(), key_code) {
+        (RecoveryStep::Choose, KeyCode::Char('r')) => {
+            app.input_buffer.clear();
+            app.recovery_step = RecoveryStep::BackupPath;
+        },
+        (RecoveryStep::Choose, KeyCode::Char('f')) => {
+            app.recovery_step = RecoveryStep::ConfirmFresh;
+        },
+        (RecoveryStep::Choose, KeyCode::Char('q')) | (RecoveryStep::Choose, KeyCode::Esc) => {
+            app.should_quit = true;
+        },
+        (RecoveryStep::BackupPath, KeyCode::Enter) => {
+            let path = app.input_buffer.trim();
+            if !path.is_empty() {
+                app.recovery_step = RecoveryStep::Passphrase(PathBuf::from(path));
+                app.input_buffer.clear();
+            }
+        },
+        (RecoveryStep::BackupPath, KeyCode::Esc) => {
+            app.input_buffer.clear();
+            app.recovery_step = RecoveryStep::Choose;
+        },
+        (RecoveryStep::Passphrase(path), KeyCode::Enter) => {
+            let passphrase = std::mem::take(&mut app.input_buffer);
+            app.restore_vault_backup(&path, &passphrase);
+        },
+        // Going back keeps the chosen path so it can be corrected
+        (RecoveryStep::Passphrase(path), KeyCode::Esc) => {
+            app.input_buffer = path.to_string_lossy().into_owned();
+            app.recovery_step = RecoveryStep::BackupPath;
+        },
+        (RecoveryStep::BackupPath, KeyCode::Backspace) | (RecoveryStep::Passphrase(_), KeyCode::Backspace) => {
+            app.input_buffer.pop();
+        },
+        (RecoveryStep::BackupPath, KeyCode::Char(c)) | (RecoveryStep::Passphrase(_), KeyCode::Char(c)) => {
+            app.input_buffer.push(c);
+        },
+        (RecoveryStep::ConfirmFresh, KeyCode::Char('y')) | (RecoveryStep::ConfirmFresh, KeyCode::Char('Y')) => {
+            app.start_fresh_vault();
+        },
+        (RecoveryStep::ConfirmFresh, KeyCode::Char('n'))
+        | (RecoveryStep::ConfirmFresh, KeyCode::Char('N'))
+        | (RecoveryStep::ConfirmFresh, KeyCode::Esc) => {
+            app.recovery_step = RecoveryStep::Choose;
+        },
+        _ => {}
+    }
+}
+
+fn handle_confirm_delete_keys(app: &mut App, key_code: KeyCode) {
+    if app.strict_delete {
+        handle_strict_confirm_delete_keys(app, key_code);
+        return;
+    }
+    match key_code {
+        KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('N') => {
             app.current_view = View::WalletList;
+            app.confirm_action = false;
+        },
+        // Deletes regardless of which option is highlighted
+        KeyCode::Char('y') | KeyCode::Char('Y') => {
+            app.confirm_delete();
         },
         KeyCode::Left => {
             app.confirm_action = true; // Yes
@@ -1313,7 +4542,7 @@ fn handle_confirm_delete_keys(app: &mut App, key_code: KeyCode) {
         },
         KeyCode::Enter => {
             if app.confirm_action {
-                app.remove_selected_wallet();
+                app.confirm_delete();
             } else {
                 app.current_view = View::WalletList;
             }
@@ -1322,6 +4551,34 @@ fn handle_confirm_delete_keys(app: &mut App, key_code: KeyCode) {
     }
 }
 
+// Strict mode: letters go into the name field rather than answering the
+// prompt, and Enter only deletes once the typed name matches
+fn handle_strict_confirm_delete_keys(app: &mut App, key_code: KeyCode) {
+    match key_code {
+        KeyCode::Esc => {
+            app.current_view = View::WalletList;
+            app.input_buffer.clear();
+        },
+        KeyCode::Char(c) => {
+            app.input_buffer.push(c);
+        },
+        KeyCode::Backspace => {
+            app.input_buffer.pop();
+        },
+        KeyCode::Enter => {
+            if app.delete_confirmation_matches() {
+                app.confirm_delete();
+            } else {
+                app.set_status(
+                    format!("Type '{}' exactly to delete", app.delete_confirmation_text()),
+                    StatusType::Warning,
+                );
+            }
+        },
+        _ => {}
+    }
+}
+
 fn handle_search_wallets_keys(app: &mut App, key_code: KeyCode) {
     match key_code {
         KeyCode::Esc => {
@@ -1344,38 +4601,149 @@ fn handle_search_wallets_keys(app: &mut App, key_code: KeyCode) {
 }
 
 fn handle_batch_operations_keys(app: &mut App, key_code: KeyCode) {
+    // The form stays as it was simulated until the result is in, so a reported
+    // unsafe recipient can only be confirmed for the recipients it was found in
+    if app.batch_rx.is_some() && key_code != KeyCode::Esc {
+        return;
+    }
     match key_code {
-        KeyCode::Esc | KeyCode::Backspace => {
+        KeyCode::Esc => {
             app.current_view = View::WalletList;
         },
+        KeyCode::Enter => {
+            app.simulate_batch_transfer();
+        },
+        KeyCode::Tab | KeyCode::Down => {
+            app.batch_form.focus_next();
+        },
+        KeyCode::BackTab | KeyCode::Up => {
+            app.batch_form.focus_prev();
+        },
+        KeyCode::Left | KeyCode::Right if app.batch_form.focus == BatchField::Source => {
+            app.cycle_batch_source(key_code == KeyCode::Right);
+        },
+        KeyCode::Insert => {
+            app.batch_form.add_recipient();
+        },
+        KeyCode::Delete => {
+            app.batch_form.remove_recipient();
+        },
+        KeyCode::PageUp => {
+            app.batch_form.log_scroll = app.batch_form.log_scroll.saturating_sub(5);
+        },
+        KeyCode::PageDown => {
+            app.batch_form.log_scroll = app.batch_form.log_scroll.saturating_add(5);
+        },
+        KeyCode::F(2) => {
+            app.save_simulation_log();
+        },
+        KeyCode::Backspace | KeyCode::Char(_) => {
+            app.batch_form.edit(key_code);
+        },
         _ => {}
     }
 }
 
-fn handle_create_vanity_wallet_keys(app: &mut App, key_code: KeyCode) {
+fn handle_token_mixing_keys(app: &mut App, key_code: KeyCode) {
+    let on_sources = app.mixing_form.focus == MixingField::Sources;
     match key_code {
         KeyCode::Esc => {
             app.current_view = View::WalletList;
         },
         KeyCode::Enter => {
-            app.start_vanity_wallet_creation();
+            app.generate_mixing_plan();
         },
-        KeyCode::Backspace => {
-            app.vanity_wallet_name.pop();
+        KeyCode::Tab => {
+            app.mixing_form.focus = app.mixing_form.focus.next();
         },
-        KeyCode::Char(c) => {
-            // Only allow editing the wallet name, prefix is fixed to "ai"
-            app.vanity_wallet_name.push(c);
+        KeyCode::BackTab => {
+            app.mixing_form.focus = app.mixing_form.focus.prev();
+        },
+        KeyCode::Up if on_sources => {
+            app.move_mixing_cursor(false);
+        },
+        KeyCode::Down if on_sources => {
+            app.move_mixing_cursor(true);
+        },
+        KeyCode::Char(' ') if on_sources => {
+            app.toggle_mixing_source();
+        },
+        KeyCode::PageUp => {
+            app.mixing_form.plan_scroll = app.mixing_form.plan_scroll.saturating_sub(5);
+        },
+        KeyCode::PageDown => {
+            app.mixing_form.plan_scroll = app.mixing_form.plan_scroll.saturating_add(5);
+        },
+        KeyCode::F(2) => {
+            app.save_simulation_log();
+        },
+        KeyCode::Backspace | KeyCode::Char(_) => {
+            app.mixing_form.edit(key_code);
         },
         _ => {}
     }
 }
 
-fn handle_vanity_progress_keys(app: &mut App, key_code: KeyCode) {
+fn handle_import_seed_keys(app: &mut App, key_code: KeyCode) {
+    match key_code {
+        // Esc backs out of the preview before leaving the form
+        KeyCode::Esc if app.seed_form.preview.is_some() => {
+            app.seed_form.preview = None;
+        },
+        KeyCode::Esc => {
+            // Drop the phrase rather than keeping it around for next time
+            app.seed_form = SeedImportForm::new();
+            app.current_view = View::WalletList;
+        },
+        KeyCode::Enter if app.seed_form.preview.is_some() => {
+            app.import_seed_account();
+        },
+        KeyCode::Enter => {
+            app.preview_seed_account();
+        },
+        KeyCode::Tab => {
+            app.seed_form.focus = app.seed_form.focus.next();
+        },
+        KeyCode::BackTab => {
+            app.seed_form.focus = app.seed_form.focus.prev();
+        },
+        KeyCode::Backspace | KeyCode::Char(_) => {
+            app.seed_form.edit(key_code);
+        },
+        _ => {}
+    }
+}
+
+fn handle_create_vanity_wallet_keys(app: &mut App, key_code: KeyCode) {
     match key_code {
+        // Esc backs out of a pending confirmation before leaving the form
+        KeyCode::Esc if app.vanity_confirming => {
+            app.vanity_confirming = false;
+        },
         KeyCode::Esc => {
-            app.cancel_vanity_generation();
+            app.persist_settings();
+            app.current_view = View::WalletList;
+        },
+        KeyCode::Enter if app.vanity_confirming || app.commit_vanity_field() => {
+            app.confirm_or_start_vanity_wallet_creation();
+        },
+        KeyCode::Tab => {
+            app.focus_vanity_field(true);
+        },
+        KeyCode::BackTab => {
+            app.focus_vanity_field(false);
         },
+        KeyCode::Backspace | KeyCode::Char(_) => {
+            app.edit_vanity_field(key_code);
+        },
+        _ => {}
+    }
+}
+
+fn handle_vanity_progress_keys(app: &mut App, key_code: KeyCode) {
+    match key_code {
+        KeyCode::Char(' ') => app.toggle_vanity_pause(),
+        KeyCode::Esc => app.cancel_vanity_generation(),
         _ => {}
     }
 }
@@ -1405,10 +4773,10 @@ mod tests {
             "test_wallet".to_string(),
         ];
         
-        // Test with empty search query (should include all wallets)
+        // Test with empty search query (should include all wallets, by name)
         app.search_query = "".to_string();
         app.update_filtered_wallets();
-        assert_eq!(app.filtered_wallets, vec![0, 1, 2]);
+        assert_eq!(app.filtered_wallets, vec![2, 0, 1]);
         
         // Test with matching search query
         app.search_query = "test".to_string();
@@ -1421,6 +4789,81 @@ mod tests {
         assert!(app.filtered_wallets.is_empty());
     }
     
+    #[test]
+    fn test_fuzzy_search_matches_non_contiguous_queries() {
+        let mut app = App::new();
+        app.wallets = vec![
+            "wallet1".to_string(),
+            "wallet2".to_string(),
+            "trading".to_string(),
+        ];
+        
+        // A plain substring search misses abbreviated queries...
+        assert!(!"wallet1".contains("wlt1"));
+        
+        // ...which fuzzy matching finds, selecting the best match
+        app.search_query = "wlt1".to_string();
+        app.update_filtered_wallets();
+        assert_eq!(app.filtered_wallets, vec![0]);
+        assert_eq!(app.selected_wallet, Some(0));
+        
+        // Matching ignores case, like the substring search did
+        app.search_query = "WLT".to_string();
+        app.update_filtered_wallets();
+        assert_eq!(app.filtered_wallets, vec![0, 1]);
+    }
+    
+    #[test]
+    fn test_search_matches_pubkeys() {
+        let mut app = App::new();
+        app.wallets = vec!["savings".to_string(), "trading".to_string()];
+        let pubkey = Pubkey::new_unique();
+        app.wallet_details = vec![
+            WalletDetail { pubkey: Some(pubkey), ..sample_detail("savings", None, None) },
+            WalletDetail { pubkey: Some(Pubkey::new_unique()), ..sample_detail("trading", None, None) },
+        ];
+        
+        // A pasted slice of the address finds the wallet though the name does not match
+        let address = pubkey.to_string();
+        app.search_query = address[10..22].to_string();
+        app.update_filtered_wallets();
+        assert_eq!(app.filtered_wallets, vec![0]);
+        assert_eq!(app.selected_wallet, Some(0));
+        
+        // The full address works too
+        app.search_query = address;
+        app.update_filtered_wallets();
+        assert_eq!(app.filtered_wallets, vec![0]);
+        
+        // A query matching neither a name nor an address finds nothing
+        app.search_query = "zzzzzzzzzzzz".to_string();
+        app.update_filtered_wallets();
+        assert!(app.filtered_wallets.is_empty());
+        
+        // Short queries only match names
+        assert!(!pubkey_matches_query(&pubkey.to_string(), &pubkey.to_string()[..3]));
+    }
+    
+    #[test]
+    fn test_fuzzy_search_ranks_by_score() {
+        let mut app = App::new();
+        app.wallets = vec![
+            "my_old_savings".to_string(),
+            "savings".to_string(),
+        ];
+        
+        // The contiguous match outranks the scattered one despite sorting later by name
+        app.search_query = "savings".to_string();
+        app.update_filtered_wallets();
+        assert_eq!(app.filtered_wallets, vec![1, 0]);
+        assert_eq!(app.selected_wallet, Some(1));
+        
+        // An empty query shows everything in the sort mode's order
+        app.search_query.clear();
+        app.update_filtered_wallets();
+        assert_eq!(app.filtered_wallets, vec![0, 1]);
+    }
+    
     #[test]
     fn test_wallet_navigation() {
         let mut app = App::new();
@@ -1460,5 +4903,1554 @@ mod tests {
         assert_eq!(app.selected_wallet, Some(0));
     }
 
+    #[test]
+    fn test_transaction_history_results_reach_the_wallet() {
+        let mut app = App::new();
+        app.wallets = vec!["wallet1".to_string(), "wallet2".to_string()];
+        app.wallet_details = vec![
+            sample_detail("wallet1", None, None),
+            sample_detail("wallet2", None, None),
+        ];
+        app.selected_wallet = Some(1);
+        app.current_view = View::WalletDetail;
+        
+        let summary = |slot| TxSummary {
+            signature: format!("sig{}", slot),
+            slot,
+            block_time: None,
+            success: true,
+        };
+        let (tx, rx) = mpsc::channel();
+        app.history_rx = Some(rx);
+        tx.send(("wallet2".to_string(), Ok(vec![summary(3), summary(2), summary(1)]))).unwrap();
+        app.poll_transaction_history();
+        
+        assert!(app.history_rx.is_none());
+        assert!(app.wallet_details[0].transactions.is_none());
+        assert_eq!(app.wallet_details[1].transactions.as_ref().unwrap().len(), 3);
+        
+        // Scrolling stays within the list
+        handle_key_event(&mut app, KeyCode::Down);
+        handle_key_event(&mut app, KeyCode::Down);
+        handle_key_event(&mut app, KeyCode::Down);
+        assert_eq!(app.tx_selected, 2);
+        handle_key_event(&mut app, KeyCode::Up);
+        assert_eq!(app.tx_selected, 1);
+        
+        // RPC failures are kept for display rather than retried in a loop
+        let (tx, rx) = mpsc::channel();
+        app.history_rx = Some(rx);
+        tx.send(("wallet1".to_string(), Err(rpc::RpcError::Transport("offline".to_string())))).unwrap();
+        app.poll_transaction_history();
+        assert!(app.wallet_details[0].history_error.as_ref().unwrap().contains("offline"));
+    }
+    
+    #[test]
+    fn test_batch_simulation_result_arrives_in_the_background() {
+        let mut app = App::new();
+        app.current_view = View::BatchOperations;
+        let (tx, rx) = mpsc::channel();
+        app.batch_rx = Some(rx);
+        
+        // The form cannot change under a running simulation
+        let focus = app.batch_form.focus;
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.batch_form.focus, focus);
+        app.poll_batch_simulation();
+        assert!(app.batch_rx.is_some());
+        
+        tx.send((1, Err(transaction_handler::TransactionError::UnsafeRecipient("own address".to_string())))).unwrap();
+        app.poll_batch_simulation();
+        assert!(app.batch_rx.is_none());
+        assert!(app.batch_form.allow_unsafe_recipients);
+        
+        let (tx, rx) = mpsc::channel();
+        app.batch_rx = Some(rx);
+        tx.send((2, Ok("simulated".to_string()))).unwrap();
+        app.poll_batch_simulation();
+        assert_eq!(app.batch_form.log.as_deref(), Some("simulated"));
+        assert!(matches!(app.status_message.as_ref().map(|m| &m.status_type), Some(StatusType::Success)));
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_ne!(app.batch_form.focus, focus);
+    }
+    
+    #[test]
+    fn test_restore_is_idempotent() {
+        let active = AtomicBool::new(true);
+        let restores = AtomicUsize::new(0);
+        let restore = || {
+            restores.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        };
+        
+        restore_once(&active, restore).unwrap();
+        restore_once(&active, restore).unwrap();
+        assert_eq!(restores.load(Ordering::SeqCst), 1);
+        
+        // Never restores a terminal that was not set up
+        restore_terminal().unwrap();
+        restore_terminal().unwrap();
+    }
+    
+    #[test]
+    fn test_error_log_keeps_errors_and_trims_oldest() {
+        let mut app = App::new();
+        app.set_status("just info".to_string(), StatusType::Info);
+        assert!(app.error_log.is_empty());
+
+        app.set_status("first failure".to_string(), StatusType::Error);
+        assert_eq!(app.error_log.len(), 1);
+        assert_eq!(app.error_log[0].1, "first failure");
+
+        for i in 0..ERROR_LOG_CAPACITY + 5 {
+            app.log_error(format!("error {}", i));
+        }
+        assert_eq!(app.error_log.len(), ERROR_LOG_CAPACITY);
+        assert_eq!(app.error_log[0].1, "error 5");
+        assert_eq!(app.error_log.last().unwrap().1, format!("error {}", ERROR_LOG_CAPACITY + 4));
+    }
+
+    #[test]
+    fn test_error_log_view() {
+        let mut app = App::new();
+        app.log_error("one".to_string());
+        app.log_error("two".to_string());
+
+        handle_key_event(&mut app, KeyCode::Char('E'));
+        assert!(matches!(app.current_view, View::Errors));
+
+        // Scrolling stops at the last entry
+        for _ in 0..5 {
+            handle_key_event(&mut app, KeyCode::Down);
+        }
+        assert_eq!(app.error_log_scroll, 1);
+        handle_key_event(&mut app, KeyCode::Up);
+        assert_eq!(app.error_log_scroll, 0);
+
+        handle_key_event(&mut app, KeyCode::Char('c'));
+        assert!(app.error_log.is_empty());
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert!(matches!(app.current_view, View::WalletList));
+
+        // Lowercase 'e' still exports the address book
+        handle_key_event(&mut app, KeyCode::Char('e'));
+        assert!(matches!(app.current_view, View::ExportAddressBook));
+    }
+
+    #[test]
+    fn test_is_external_change() {
+        // No write of ours since the last check: someone else changed the file
+        assert!(is_external_change(3, 3));
+        // Our own save caused the event
+        assert!(!is_external_change(4, 3));
+    }
+
+    #[test]
+    fn test_vault_watcher_reports_external_write_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("wallets.json");
+        // Other tests write vaults concurrently, so count no writes of our own
+        let mut watcher = VaultWatcher::with_write_counter(&vault_path, || 0).unwrap();
+        assert!(!watcher.external_change_settled());
+
+        // Other files in the directory are ignored
+        std::fs::write(dir.path().join("unrelated.txt"), "x").unwrap();
+        thread::sleep(VAULT_WATCH_DEBOUNCE * 2);
+        assert!(!watcher.external_change_settled());
+
+        // Several quick writes settle into a single change
+        for content in ["{}", "{ }", "{  }"] {
+            std::fs::write(&vault_path, content).unwrap();
+        }
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut changes = 0;
+        while Instant::now() < deadline && changes == 0 {
+            thread::sleep(Duration::from_millis(50));
+            if watcher.external_change_settled() {
+                changes += 1;
+            }
+        }
+        assert_eq!(changes, 1);
+        thread::sleep(VAULT_WATCH_DEBOUNCE * 2);
+        assert!(!watcher.external_change_settled());
+        
+        // The quiet period restarts with every event, not just the burst's first
+        let (tx, events) = mpsc::channel();
+        watcher.events = events;
+        watcher.last_event = Some(Instant::now() - VAULT_WATCH_DEBOUNCE * 2);
+        tx.send(()).unwrap();
+        assert!(!watcher.external_change_settled());
+        assert!(watcher.last_event.is_some());
+    }
+
+    #[test]
+    fn test_refresh_due() {
+        let interval = 30;
+        assert!(!refresh_due(Duration::from_secs(29), interval, false));
+        assert!(refresh_due(Duration::from_secs(30), interval, false));
+        
+        // Never while a refresh is already running, or when disabled
+        assert!(!refresh_due(Duration::from_secs(60), interval, true));
+        assert!(!refresh_due(Duration::from_secs(3600), 0, false));
+    }
+    
+    #[test]
+    fn test_cluster_indicator() {
+        assert_eq!(cluster_indicator("devnet", None), ("○ devnet (checking…)".to_string(), Color::Gray));
+        assert_eq!(cluster_indicator("devnet", Some(&Ok(()))), ("● devnet".to_string(), Color::Green));
+        let down: rpc::Result<()> = Err(rpc::RpcError::Rpc { code: -32005, message: "Node is behind".to_string() });
+        assert_eq!(
+            cluster_indicator("mainnet-beta", Some(&down)),
+            ("● mainnet-beta (unhealthy)".to_string(), Color::Red)
+        );
+        let offline: rpc::Result<()> = Err(rpc::RpcError::Transport("connection refused".to_string()));
+        assert_eq!(
+            cluster_indicator("localnet", Some(&offline)),
+            ("● localnet (unreachable)".to_string(), Color::Red)
+        );
+    }
+    
+    #[test]
+    fn test_lock_due() {
+        let timeout = 5;
+        assert!(!lock_due(Duration::from_secs(299), timeout, false));
+        assert!(lock_due(Duration::from_secs(300), timeout, false));
+        
+        // Never once locked, or when disabled
+        assert!(!lock_due(Duration::from_secs(600), timeout, true));
+        assert!(!lock_due(Duration::from_secs(86_400), 0, false));
+    }
+    
+    #[test]
+    fn test_idle_lock_clears_wallet_details() {
+        let mut app = App::new();
+        app.lock_timeout_minutes = 1;
+        app.wallets = vec!["alpha".to_string()];
+        app.wallet_details = vec![sample_detail("alpha", Some(1_000_000_000), None)];
+        app.update_filtered_wallets();
+        app.selected_wallet = Some(0);
+        app.current_view = View::RevealSecret;
+        app.revealed_secret = Some(Zeroizing::new(vec![7u8; 64]));
+        app.seed_form.phrase.push_str("neither lonely flavor");
+        app.batch_form.token_mint.push_str("mint");
+        app.mixing_form.destinations.push_str("destination");
+        
+        // Still within the timeout
+        app.last_input = Instant::now() - Duration::from_secs(59);
+        app.lock_if_idle();
+        assert!(matches!(app.current_view, View::RevealSecret));
+        
+        app.last_input = Instant::now() - Duration::from_secs(61);
+        app.lock_if_idle();
+        assert!(matches!(app.current_view, View::Locked));
+        assert!(app.wallets.is_empty());
+        assert!(app.wallet_details.is_empty());
+        assert!(app.filtered_wallets.is_empty());
+        assert!(app.selected_wallet.is_none());
+        assert!(app.revealed_secret.is_none());
+        assert!(app.seed_form.phrase.is_empty());
+        assert!(app.batch_form.token_mint.is_empty());
+        assert!(app.mixing_form.destinations.is_empty());
+        
+        // Only Enter unlocks; a key press still counts as input
+        handle_key_event(&mut app, KeyCode::Char('d'));
+        assert!(matches!(app.current_view, View::Locked));
+        assert!(app.last_input.elapsed() < Duration::from_secs(1));
+        handle_key_event(&mut app, KeyCode::Char('q'));
+        assert!(app.should_quit);
+    }
+    
+    #[test]
+    fn test_unlock_needs_the_master_key_again() {
+        let (temp_dir, test_service_name, _env_guard) = secure_storage::test_env::setup_test_env();
+        wallet_manager::add_wallet_from_keypair("locked", &solana_sdk::signer::keypair::Keypair::new()).unwrap();
+        let mut app = App::new();
+        app.lock();
+        assert!(!app.unlock_needs_passphrase);
+        
+        // The test keychain keeps no key, so it cannot vouch for the vault
+        handle_key_event(&mut app, KeyCode::Enter);
+        assert!(matches!(app.current_view, View::Locked));
+        assert!(matches!(app.status_message.as_ref().map(|m| &m.status_type), Some(StatusType::Error)));
+        assert!(app.wallets.is_empty());
+        
+        // Typed characters are not collected when no passphrase is asked for
+        handle_key_event(&mut app, KeyCode::Char('x'));
+        assert!(app.unlock_passphrase.is_empty());
+        
+        // Without a vault there is nothing to protect
+        std::fs::remove_file(secure_storage::get_config_path().unwrap()).unwrap();
+        handle_key_event(&mut app, KeyCode::Enter);
+        assert!(matches!(app.current_view, View::WalletList));
+        
+        secure_storage::test_env::teardown_test_env(temp_dir, &test_service_name);
+    }
+    
+    #[test]
+    fn test_only_a_running_search_holds_off_the_idle_lock() {
+        let mut app = App::new();
+        app.lock_timeout_minutes = 1;
+        app.last_input = Instant::now() - Duration::from_secs(61);
+        let (stop, stopped) = mpsc::channel::<()>();
+        app.vanity_thread = Some(thread::spawn(move || {
+            let _ = stopped.recv();
+        }));
+        
+        app.lock_if_idle();
+        assert!(matches!(app.current_view, View::WalletList));
+        
+        app.vanity_paused.store(true, Ordering::SeqCst);
+        app.lock_if_idle();
+        assert!(matches!(app.current_view, View::Locked));
+        
+        // A finished search does not hold it off either
+        app.current_view = View::WalletList;
+        app.vanity_paused.store(false, Ordering::SeqCst);
+        drop(stop);
+        app.vanity_thread.take().unwrap().join().unwrap();
+        app.vanity_thread = Some(thread::spawn(|| {}));
+        while !app.vanity_thread.as_ref().unwrap().is_finished() {
+            thread::yield_now();
+        }
+        app.lock_if_idle();
+        assert!(matches!(app.current_view, View::Locked));
+    }
+    
+    #[test]
+    fn test_status_expiry_follows_configured_timeout() {
+        let elapsed = Duration::from_secs(10);
+        assert!(status_expired(&StatusType::Info, elapsed, 5, false));
+        assert!(!status_expired(&StatusType::Info, elapsed, 30, false));
+        
+        // Zero disables auto-clearing entirely
+        assert!(!status_expired(&StatusType::Warning, Duration::from_secs(3600), 0, false));
+        
+        // Persistent errors outlive the timeout, other messages do not
+        assert!(!status_expired(&StatusType::Error, elapsed, 5, true));
+        assert!(status_expired(&StatusType::Error, elapsed, 5, false));
+        assert!(status_expired(&StatusType::Success, elapsed, 5, true));
+    }
+
+    fn sample_detail(name: &str, balance: Option<u64>, created_at: Option<i64>) -> WalletDetail {
+        WalletDetail {
+            name: name.to_string(),
+            pubkey: None,
+            balance,
+            balance_error: None,
+            transactions: None,
+            history_error: None,
+            token_balances: Vec::new(),
+            label: None,
+            created_at,
+        }
+    }
+
+    #[test]
+    fn test_balance_updates_are_merged_by_name() {
+        let mut app = App::new();
+        app.wallets = vec!["alice".to_string(), "bob".to_string()];
+        app.wallet_details = app.wallets.iter().map(|name| sample_detail(name, None, None)).collect();
+        for detail in &mut app.wallet_details {
+            detail.pubkey = Some(Pubkey::new_unique());
+        }
+        assert!(app.wallet_details.iter().all(WalletDetail::balance_pending));
+        
+        // Updates arrive in any order; one for a removed wallet is dropped
+        let (tx, rx) = mpsc::channel();
+        app.balance_rx = Some(rx);
+        let token_account = |amount| rpc::TokenAccount {
+            address: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            amount,
+            decimals: 6,
+        };
+        let held = token_account(1_500_000);
+        tx.send(BalanceUpdate {
+            name: "bob".to_string(),
+            balance: Ok(2_500_000_000),
+            tokens: Ok(vec![held.clone(), token_account(0)]),
+        }).unwrap();
+        tx.send(BalanceUpdate { name: "carol".to_string(), balance: Ok(9_000_000_000), tokens: Ok(Vec::new()) }).unwrap();
+        app.poll_balance_updates();
+        assert_eq!(app.wallet_details[0].balance, None);
+        assert!(app.wallet_details[0].balance_pending());
+        assert_eq!(app.wallet_details[1].balance, Some(2_500_000_000));
+        assert!(app.balance_rx.is_some());
+        
+        // Only the tokens the node reported are listed, without empty accounts
+        let tokens = &app.wallet_details[1].token_balances;
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].mint_address, held.mint.to_string());
+        assert_eq!(tokens[0].amount, 1.5);
+        assert_eq!(tokens[0].token_account, held.address);
+        
+        // Everything queued is drained at once, and the channel is dropped when done
+        tx.send(BalanceUpdate {
+            name: "alice".to_string(),
+            balance: Err("timed out".to_string()),
+            tokens: Err("timed out".to_string()),
+        }).unwrap();
+        drop(tx);
+        app.poll_balance_updates();
+        assert_eq!(app.wallet_details[0].balance_error.as_deref(), Some("timed out"));
+        assert!(!app.wallet_details[0].balance_pending());
+        assert!(app.wallet_details[0].token_balances.is_empty());
+        assert!(app.balance_rx.is_none());
+    }
+
+    #[test]
+    fn test_u_toggles_balances_between_sol_and_lamports() {
+        let mut app = App::new();
+        assert_eq!(app.format_balance(1_000_000_000, 9), "1.000000000 SOL");
+        assert_eq!(app.format_balance(999_999_999, 9), "0.999999999 SOL");
+        // The list's four places round on the lamport amount
+        assert_eq!(app.format_balance(999_999_999, 4), "1.0000 SOL");
+        assert_eq!(app.format_balance(1, 4), "0.0000 SOL");
+        
+        handle_key_event(&mut app, KeyCode::Char('u'));
+        assert!(app.show_lamports);
+        assert_eq!(app.format_balance(1_000_000_000, 9), "1000000000 lamports");
+        assert_eq!(app.format_balance(999_999_999, 4), "999999999 lamports");
+        assert_eq!(app.format_balance(1, 4), "1 lamports");
+        
+        // The toggle works from the detail view too
+        app.current_view = View::WalletDetail;
+        handle_key_event(&mut app, KeyCode::Char('u'));
+        assert!(!app.show_lamports);
+        assert_eq!(app.format_balance(1, 9), "0.000000001 SOL");
+    }
+    
+    #[test]
+    fn test_refresh_selected_balance_only_touches_that_wallet() {
+        let mut app = App::new();
+        // Nothing listens here, so the fetch fails quickly without the network
+        app.rpc_url = "http://127.0.0.1:9".to_string();
+        app.wallets = vec!["alice".to_string(), "bob".to_string(), "carol".to_string()];
+        app.wallet_details = app.wallets.iter().map(|name| sample_detail(name, Some(1_000_000_000), None)).collect();
+        for detail in &mut app.wallet_details {
+            detail.pubkey = Some(Pubkey::new_unique());
+        }
+        app.selected_wallet = Some(1);
+        app.current_view = View::WalletDetail;
+        
+        handle_key_event(&mut app, KeyCode::Char('R'));
+        assert!(app.wallet_details[1].balance_pending());
+        assert!(app.balance_rx.is_none(), "A single refresh must not start a full one");
+        
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while app.selected_balance_rx.is_some() && Instant::now() < deadline {
+            app.poll_balance_updates();
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(app.wallet_details[1].balance_error.is_some());
+        for index in [0, 2] {
+            assert_eq!(app.wallet_details[index].balance, Some(1_000_000_000));
+            assert!(app.wallet_details[index].balance_error.is_none());
+        }
+    }
+
+    #[test]
+    fn test_compare_wallets_for_each_sort_mode() {
+        let wallets = [
+            sample_detail("bravo", Some(1_500_000_000), Some(200)),
+            sample_detail("Alpha", None, Some(300)),
+            sample_detail("charlie", Some(9_000_000_000), None),
+            sample_detail("delta", Some(1_500_000_000), Some(100)),
+        ];
+        let sorted = |mode| {
+            let mut refs: Vec<&WalletDetail> = wallets.iter().collect();
+            refs.sort_by(|a, b| compare_wallets(mode, a, b));
+            refs.iter().map(|w| w.name.as_str()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(sorted(SortMode::NameAsc), ["Alpha", "bravo", "charlie", "delta"]);
+        assert_eq!(sorted(SortMode::NameDesc), ["delta", "charlie", "bravo", "Alpha"]);
+        // Equal balances fall back to name order; a missing balance sorts last
+        assert_eq!(sorted(SortMode::BalanceDesc), ["charlie", "bravo", "delta", "Alpha"]);
+        assert_eq!(sorted(SortMode::CreatedDesc), ["Alpha", "bravo", "delta", "charlie"]);
+    }
+
+    #[test]
+    fn test_sort_key_cycles_modes() {
+        let mut app = App::new();
+        assert_eq!(app.sort_mode, SortMode::NameAsc);
+        handle_key_event(&mut app, KeyCode::Char('s'));
+        assert_eq!(app.sort_mode, SortMode::NameDesc);
+        assert!(matches!(app.current_view, View::WalletList));
+        handle_key_event(&mut app, KeyCode::Char('s'));
+        handle_key_event(&mut app, KeyCode::Char('s'));
+        handle_key_event(&mut app, KeyCode::Char('s'));
+        assert_eq!(app.sort_mode, SortMode::NameAsc);
+    }
+
+    #[test]
+    fn test_adjust_scroll_offset() {
+        // 20 wallets in a 5-row viewport
+        assert_eq!(adjust_scroll_offset(0, 3, 5, 20), 0);
+        assert_eq!(adjust_scroll_offset(0, 5, 5, 20), 1);
+        assert_eq!(adjust_scroll_offset(0, 19, 5, 20), 15);
+        assert_eq!(adjust_scroll_offset(10, 12, 5, 20), 10);
+        assert_eq!(adjust_scroll_offset(10, 4, 5, 20), 4);
+
+        // A stale offset past the end is pulled back to fill the viewport
+        assert_eq!(adjust_scroll_offset(18, 19, 5, 20), 15);
+
+        // Lists shorter than the viewport never scroll
+        assert_eq!(adjust_scroll_offset(2, 2, 5, 3), 0);
+    }
+
+    #[test]
+    fn test_visible_window() {
+        // 5000 wallets in a 10-row viewport: only ten rows are built
+        assert_eq!(visible_window(0, 0, 10, 5000), 0..10);
+        assert_eq!(visible_window(0, 12, 10, 5000), 3..13);
+        assert_eq!(visible_window(100, 105, 10, 5000), 100..110);
+        assert_eq!(visible_window(100, 4999, 10, 5000), 4990..5000);
+
+        // Short lists are shown whole
+        assert_eq!(visible_window(0, 1, 10, 3), 0..3);
+
+        // A zero-height area draws nothing, even with a stale offset
+        assert!(visible_window(7, 0, 0, 3).is_empty());
+        assert!(visible_window(0, 0, 10, 0).is_empty());
+    }
+
+    #[test]
+    fn test_wallet_page_navigation() {
+        let mut app = App::new();
+        app.wallets = (0..20).map(|i| format!("wallet{}", i)).collect();
+        app.filtered_wallets = (0..20).collect();
+        app.selected_wallet = Some(0);
+        app.list_page_size = 5;
+
+        handle_key_event(&mut app, KeyCode::PageDown);
+        assert_eq!(app.selected_wallet, Some(5));
+        handle_key_event(&mut app, KeyCode::PageDown);
+        handle_key_event(&mut app, KeyCode::PageDown);
+        handle_key_event(&mut app, KeyCode::PageDown);
+        assert_eq!(app.selected_wallet, Some(19));
+
+        handle_key_event(&mut app, KeyCode::PageUp);
+        assert_eq!(app.selected_wallet, Some(14));
+        app.selected_wallet = Some(2);
+        handle_key_event(&mut app, KeyCode::PageUp);
+        assert_eq!(app.selected_wallet, Some(0));
+    }
+
+    #[test]
+    fn test_confirm_delete_keys() {
+        let (temp_dir, test_service_name, _env_guard) = secure_storage::test_env::setup_test_env();
+        let doomed = solana_sdk::signer::keypair::Keypair::new();
+        wallet_manager::add_wallet_from_keypair("wallet1", &doomed).unwrap();
+        wallet_manager::add_wallet_from_keypair("keep", &solana_sdk::signer::keypair::Keypair::new()).unwrap();
+        let mut app = App::new();
+        app.load_wallets();
+        let select_doomed = |app: &mut App| {
+            app.selected_wallet = app.wallets.iter().position(|name| name == "wallet1");
+        };
+        select_doomed(&mut app);
+        
+        // 'd' opens the dialog with "No" highlighted
+        handle_key_event(&mut app, KeyCode::Char('d'));
+        assert!(matches!(app.current_view, View::ConfirmDelete));
+        assert!(!app.confirm_action);
+        
+        // 'n' cancels even with "Yes" highlighted
+        handle_key_event(&mut app, KeyCode::Left);
+        assert!(app.confirm_action);
+        handle_key_event(&mut app, KeyCode::Char('n'));
+        assert!(matches!(app.current_view, View::WalletList));
+        assert!(!app.confirm_action);
+        
+        // Enter follows the highlighted option
+        select_doomed(&mut app);
+        handle_key_event(&mut app, KeyCode::Char('d'));
+        handle_key_event(&mut app, KeyCode::Enter);
+        assert!(matches!(app.current_view, View::WalletList));
+        assert_eq!(app.wallets.len(), 2);
+        
+        // Esc cancels too
+        select_doomed(&mut app);
+        handle_key_event(&mut app, KeyCode::Char('d'));
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert!(matches!(app.current_view, View::WalletList));
+        assert_eq!(wallet_manager::get_wallet_pubkey("wallet1").unwrap(), Some(doomed.pubkey()));
+        
+        // 'y' confirms regardless of the highlight
+        select_doomed(&mut app);
+        handle_key_event(&mut app, KeyCode::Char('d'));
+        assert!(!app.confirm_action);
+        handle_key_event(&mut app, KeyCode::Char('y'));
+        assert!(matches!(app.current_view, View::WalletList));
+        assert!(!app.confirm_action);
+        assert_eq!(wallet_manager::get_wallet_pubkey("wallet1").unwrap(), None);
+        assert_eq!(app.wallets, vec!["keep".to_string()]);
+        
+        secure_storage::test_env::teardown_test_env(temp_dir, &test_service_name);
+    }
+    
+    #[test]
+    fn test_strict_delete_needs_the_typed_name() {
+        let mut app = App::new();
+        app.strict_delete = true;
+        app.wallets = vec!["savings".to_string()];
+        app.filtered_wallets = vec![0];
+        app.selected_wallet = Some(0);
+        
+        handle_key_event(&mut app, KeyCode::Char('d'));
+        assert!(matches!(app.current_view, View::ConfirmDelete));
+        assert!(app.input_buffer.is_empty());
+        
+        // 'y' is just a letter of the name, and Enter is refused until it matches
+        handle_key_event(&mut app, KeyCode::Char('y'));
+        assert!(matches!(app.current_view, View::ConfirmDelete));
+        handle_key_event(&mut app, KeyCode::Backspace);
+        for c in "Saving".chars() {
+            handle_key_event(&mut app, KeyCode::Char(c));
+        }
+        handle_key_event(&mut app, KeyCode::Enter);
+        assert!(matches!(app.current_view, View::ConfirmDelete));
+        assert!(matches!(app.status_message.as_ref().map(|m| &m.status_type), Some(StatusType::Warning)));
+        
+        // The comparison is exact, case included
+        app.input_buffer = "saving".to_string();
+        assert!(!app.delete_confirmation_matches());
+        handle_key_event(&mut app, KeyCode::Char('s'));
+        assert!(app.delete_confirmation_matches());
+        
+        // Esc backs out and forgets what was typed
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert!(matches!(app.current_view, View::WalletList));
+        assert!(app.input_buffer.is_empty());
+        
+        // Batch deletion asks for the number of wallets instead
+        app.marked_wallets.insert(0);
+        handle_key_event(&mut app, KeyCode::Char('D'));
+        assert!(matches!(app.current_view, View::ConfirmBatchDelete));
+        assert_eq!(app.delete_confirmation_text(), "delete 1");
+        handle_key_event(&mut app, KeyCode::Enter);
+        assert!(matches!(app.current_view, View::ConfirmBatchDelete));
+    }
+    
+    #[test]
+    fn test_key_mismatch_opens_recovery() {
+        let mut app = App::new();
+        app.apply_refresh(Err(secure_storage::SecureStorageError::KeyMismatch("test".to_string())));
+        assert!(matches!(app.current_view, View::VaultRecovery));
+        assert_eq!(app.recovery_step, RecoveryStep::Choose);
+        
+        // The backup path is kept when stepping back from the passphrase
+        handle_key_event(&mut app, KeyCode::Char('r'));
+        for c in "backup.json".chars() {
+            handle_key_event(&mut app, KeyCode::Char(c));
+        }
+        handle_key_event(&mut app, KeyCode::Enter);
+        assert_eq!(app.recovery_step, RecoveryStep::Passphrase(PathBuf::from("backup.json")));
+        assert!(app.input_buffer.is_empty());
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert_eq!(app.recovery_step, RecoveryStep::BackupPath);
+        assert_eq!(app.input_buffer, "backup.json");
+        handle_key_event(&mut app, KeyCode::Esc);
+        
+        // Starting fresh needs a confirmation, and 'n' backs out of it
+        handle_key_event(&mut app, KeyCode::Char('f'));
+        assert_eq!(app.recovery_step, RecoveryStep::ConfirmFresh);
+        handle_key_event(&mut app, KeyCode::Char('n'));
+        assert_eq!(app.recovery_step, RecoveryStep::Choose);
+        
+        // A later refresh failing the same way does not reset the step
+        handle_key_event(&mut app, KeyCode::Char('r'));
+        app.apply_refresh(Err(secure_storage::SecureStorageError::KeyMismatch("test".to_string())));
+        assert_eq!(app.recovery_step, RecoveryStep::BackupPath);
+        assert!(!app.should_quit);
+    }
+    
+    #[test]
+    fn test_toggle_wallet_marks() {
+        let mut app = App::new();
+        app.wallets = vec!["wallet1".to_string(), "wallet2".to_string(), "wallet3".to_string()];
+        app.filtered_wallets = vec![0, 1, 2];
+        app.selected_wallet = Some(0);
+        
+        handle_key_event(&mut app, KeyCode::Char(' '));
+        handle_key_event(&mut app, KeyCode::Down);
+        handle_key_event(&mut app, KeyCode::Down);
+        handle_key_event(&mut app, KeyCode::Char(' '));
+        assert_eq!(app.marked_wallets, HashSet::from([0, 2]));
+        
+        // Space again unmarks
+        handle_key_event(&mut app, KeyCode::Char(' '));
+        assert_eq!(app.marked_wallets, HashSet::from([0]));
+        
+        // Marks outlive a refresh by name, and drop wallets that disappeared
+        app.marked_wallets.insert(2);
+        app.apply_refresh(Ok((
+            vec!["wallet0".to_string(), "wallet1".to_string(), "wallet2".to_string()],
+            Vec::new(),
+            Vec::new(),
+        )));
+        assert_eq!(app.marked_wallets, HashSet::from([1]));
+        
+        // Esc clears all marks
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert!(app.marked_wallets.is_empty());
+    }
+    
+    #[test]
+    fn test_failed_reload_leaves_no_stale_indices() {
+        let mut app = App::new();
+        let names = vec!["wallet0".to_string(), "wallet1".to_string(), "wallet2".to_string()];
+        let details = names.iter().map(|name| sample_detail(name, Some(1_000_000_000), None)).collect();
+        app.apply_refresh(Ok((names, details, Vec::new())));
+        app.selected_wallet = Some(2);
+        app.marked_wallets.insert(1);
+        assert_eq!(app.filtered_wallets, vec![0, 1, 2]);
+        
+        // Reloads fail in the background too, e.g. when the keychain goes away
+        app.apply_refresh(Err(secure_storage::SecureStorageError::KeychainAccess("locked".to_string())));
+        assert!(app.wallets.is_empty());
+        assert!(app.filtered_wallets.is_empty());
+        assert!(app.wallet_details.is_empty());
+        assert!(app.marked_wallets.is_empty());
+        assert_eq!(app.selected_wallet, None);
+        render_to_text(&mut app);
+        
+        // A tampered or foreign vault file drops the list the same way
+        for error in [
+            secure_storage::SecureStorageError::Tampered("test".to_string()),
+            secure_storage::SecureStorageError::KeyMismatch("test".to_string()),
+        ] {
+            let names = vec!["wallet0".to_string(), "wallet1".to_string(), "wallet2".to_string()];
+            let details = names.iter().map(|name| sample_detail(name, Some(1_000_000_000), None)).collect();
+            app.current_view = View::WalletList;
+            app.apply_refresh(Ok((names, details, Vec::new())));
+            app.selected_wallet = Some(2);
+            app.marked_wallets.insert(2);
+            
+            app.apply_refresh(Err(error));
+            assert!(app.filtered_wallets.is_empty());
+            assert!(app.wallet_details.is_empty());
+            assert!(app.marked_wallets.is_empty());
+            assert_eq!(app.selected_wallet, None);
+            render_to_text(&mut app);
+        }
+    }
+    
+    #[test]
+    fn test_batch_delete_confirmation() {
+        let mut app = App::new();
+        app.wallets = vec!["wallet1".to_string(), "wallet2".to_string()];
+        app.filtered_wallets = vec![0, 1];
+        app.selected_wallet = Some(0);
+        
+        // Nothing marked: D only warns
+        handle_key_event(&mut app, KeyCode::Char('D'));
+        assert!(matches!(app.current_view, View::WalletList));
+        assert!(matches!(app.status_message, Some(StatusMessage { status_type: StatusType::Warning, .. })));
+        
+        handle_key_event(&mut app, KeyCode::Char(' '));
+        handle_key_event(&mut app, KeyCode::Char('D'));
+        assert!(matches!(app.current_view, View::ConfirmBatchDelete));
+        assert!(!app.confirm_action);
+        
+        // Cancelling keeps the marks
+        handle_key_event(&mut app, KeyCode::Char('n'));
+        assert!(matches!(app.current_view, View::WalletList));
+        assert_eq!(app.marked_wallets, HashSet::from([0]));
+        
+        // Lowercase d still deletes just the highlighted wallet
+        handle_key_event(&mut app, KeyCode::Char('d'));
+        assert!(matches!(app.current_view, View::ConfirmDelete));
+    }
+    
+    #[test]
+    fn test_remove_wallets_accounting() {
+        let names: Vec<String> = ["a", "b", "c", "d"].iter().map(|s| s.to_string()).collect();
+        let mut attempted = Vec::new();
+        let outcome = remove_wallets(&names, |name| {
+            attempted.push(name.to_string());
+            if name == "b" || name == "d" {
+                Err(WalletManagerError::NotFound("Wallet not found".to_string()))
+            } else {
+                Ok(())
+            }
+        });
+        
+        // Every wallet is attempted even after a failure
+        assert_eq!(attempted, names);
+        assert_eq!(outcome.removed, 2);
+        assert_eq!(
+            outcome.failed,
+            vec![
+                ("b".to_string(), "Wallet not found".to_string()),
+                ("d".to_string(), "Wallet not found".to_string()),
+            ]
+        );
+        assert_eq!(remove_wallets(&[], |_| Ok(())), BatchRemoval::default());
+    }
+    
+    #[test]
+    fn test_batch_form_add_and_remove_rows() {
+        let mut form = BatchForm::new(0);
+        assert_eq!(form.recipients.len(), 1);
+        
+        form.add_recipient();
+        form.add_recipient();
+        assert_eq!(form.recipients.len(), 3);
+        assert_eq!(form.focus, BatchField::Address(2));
+        
+        // Removing a middle row focuses the row that took its place
+        form.focus = BatchField::Amount(1);
+        form.recipients[2].address = "third".to_string();
+        form.remove_recipient();
+        assert_eq!(form.recipients.len(), 2);
+        assert_eq!(form.recipients[1].address, "third");
+        assert_eq!(form.focus, BatchField::Address(1));
+        
+        // Removing the last row moves focus up
+        form.remove_recipient();
+        assert_eq!(form.recipients.len(), 1);
+        assert_eq!(form.focus, BatchField::Address(0));
+        
+        // The only row is cleared rather than removed
+        form.recipients[0].amount = "1.5".to_string();
+        form.remove_recipient();
+        assert_eq!(form.recipients, vec![RecipientRow::default()]);
+        
+        // Delete does nothing outside the recipient rows
+        form.add_recipient();
+        form.focus = BatchField::TokenMint;
+        form.remove_recipient();
+        assert_eq!(form.recipients.len(), 2);
+    }
+    
+    #[test]
+    fn test_batch_form_focus_cycle() {
+        let mut form = BatchForm::new(0);
+        form.add_recipient();
+        form.focus = BatchField::Source;
+        
+        let expected = [
+            BatchField::TokenMint,
+            BatchField::Address(0),
+            BatchField::Amount(0),
+            BatchField::Address(1),
+            BatchField::Amount(1),
+            BatchField::Source,
+        ];
+        for field in expected {
+            form.focus_next();
+            assert_eq!(form.focus, field);
+        }
+        for field in expected.iter().rev().skip(1) {
+            form.focus_prev();
+            assert_eq!(form.focus, *field);
+        }
+    }
+    
+    #[test]
+    fn test_batch_form_validates_input() {
+        let mut form = BatchForm::new(0);
+        form.focus = BatchField::Address(0);
+        for c in "not_valid".chars() {
+            form.edit(KeyCode::Char(c));
+        }
+        assert!(form.recipients[0].address_error.is_some());
+        
+        // A valid address clears the error as soon as it is complete
+        form.recipients[0].address = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFi".to_string();
+        form.edit(KeyCode::Char('n'));
+        assert_eq!(form.recipients[0].address_error, None);
+        
+        // Amounts only take digits and a decimal point
+        form.focus = BatchField::Amount(0);
+        for c in "1x.5".chars() {
+            form.edit(KeyCode::Char(c));
+        }
+        assert_eq!(form.recipients[0].amount, "1.5");
+        
+        // Blank rows are skipped when building the transaction
+        form.add_recipient();
+        let batch = form.to_transaction("payer").unwrap();
+        assert_eq!(batch.source_wallet, "payer");
+        assert_eq!(batch.recipients, vec![("9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string(), 1.5)]);
+        assert_eq!(batch.token_mint, None);
+        assert!(!batch.allow_unsafe_recipients);
+        
+        // Confirming flagged recipients lasts only until the form changes
+        form.allow_unsafe_recipients = true;
+        assert!(form.to_transaction("payer").unwrap().allow_unsafe_recipients);
+        form.edit(KeyCode::Backspace);
+        assert!(!form.allow_unsafe_recipients);
+        
+        // A row with an address but no amount is rejected
+        form.recipients[1].address = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string();
+        assert!(form.to_transaction("payer").is_err());
+        
+        form.token_mint = "bad_mint".to_string();
+        form.recipients.pop();
+        assert!(form.to_transaction("payer").is_err());
+    }
+    
+    #[test]
+    fn test_save_simulation_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = App::new();
+        app.simulation_log_dir = dir.path().join("simulations");
+        app.current_view = View::TokenMixing;
+        
+        // Nothing to save before a plan exists
+        handle_key_event(&mut app, KeyCode::F(2));
+        assert!(matches!(app.status_message, Some(StatusMessage { status_type: StatusType::Warning, .. })));
+        assert!(!app.simulation_log_dir.exists());
+        
+        app.mixing_form.plan = Some("SIMULATION: 3-step plan\n".to_string());
+        handle_key_event(&mut app, KeyCode::F(2));
+        assert!(matches!(app.status_message, Some(StatusMessage { status_type: StatusType::Success, .. })));
+        let saved: Vec<_> = std::fs::read_dir(&app.simulation_log_dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        assert_eq!(saved.len(), 1);
+        assert!(saved[0].file_name().unwrap().to_string_lossy().starts_with("mixing-"));
+        assert_eq!(std::fs::read_to_string(&saved[0]).unwrap(), "SIMULATION: 3-step plan\n");
+    }
+    
+    #[test]
+    fn test_batch_source_cycles_through_wallets() {
+        let mut app = App::new();
+        app.wallets = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        app.filtered_wallets = vec![0, 1, 2];
+        app.selected_wallet = Some(1);
+        
+        handle_key_event(&mut app, KeyCode::Char('b'));
+        assert!(matches!(app.current_view, View::BatchOperations));
+        assert_eq!(app.batch_form.source, 1);
+        
+        handle_key_event(&mut app, KeyCode::Right);
+        handle_key_event(&mut app, KeyCode::Right);
+        assert_eq!(app.batch_form.source, 0);
+        handle_key_event(&mut app, KeyCode::Left);
+        assert_eq!(app.batch_form.source, 2);
+        
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert!(matches!(app.current_view, View::WalletList));
+    }
+    
+    #[test]
+    fn test_token_mixing_view_transitions() {
+        let mut app = App::new();
+        app.wallets = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        app.filtered_wallets = vec![0, 1, 2];
+        app.selected_wallet = Some(1);
+        
+        handle_key_event(&mut app, KeyCode::Char('t'));
+        assert!(matches!(app.current_view, View::TokenMixing));
+        assert_eq!(app.mixing_form.sources, HashSet::from([1]));
+        
+        // Space toggles the wallet under the cursor
+        handle_key_event(&mut app, KeyCode::Down);
+        handle_key_event(&mut app, KeyCode::Char(' '));
+        handle_key_event(&mut app, KeyCode::Up);
+        handle_key_event(&mut app, KeyCode::Char(' '));
+        assert_eq!(app.mixing_form.sources, HashSet::from([2]));
+        
+        // Typing goes to the focused field once focus leaves the source list
+        handle_key_event(&mut app, KeyCode::Tab);
+        handle_key_event(&mut app, KeyCode::Char('x'));
+        assert_eq!(app.mixing_form.destinations, "x");
+        assert_eq!(app.mixing_form.sources, HashSet::from([2]));
+        
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert!(matches!(app.current_view, View::WalletList));
+        
+        // Marked wallets become the preselected sources
+        app.marked_wallets = HashSet::from([0, 2]);
+        handle_key_event(&mut app, KeyCode::Char('t'));
+        assert_eq!(app.mixing_form.sources, HashSet::from([0, 2]));
+    }
+    
+    #[test]
+    fn test_token_mixing_requires_sources_and_destinations() {
+        let wallets = vec!["a".to_string(), "b".to_string()];
+        let mut form = MixingForm::new(HashSet::new(), 0);
+        form.total_amount = "2".to_string();
+        assert!(form.to_plan(&wallets).unwrap_err().contains("source wallet"));
+        
+        form.sources.insert(1);
+        assert!(form.to_plan(&wallets).unwrap_err().contains("destination"));
+        
+        // Separators alone do not count as destinations
+        form.destinations = " , ,".to_string();
+        assert!(form.to_plan(&wallets).unwrap_err().contains("destination"));
+        
+        form.destinations = "dest1, dest2,".to_string();
+        form.steps = "0".to_string();
+        assert!(form.to_plan(&wallets).is_err());
+        
+        form.steps = "3".to_string();
+        let plan = form.to_plan(&wallets).unwrap();
+        assert_eq!(plan.source_wallets, vec!["b".to_string()]);
+        assert_eq!(plan.destination_addresses, vec!["dest1".to_string(), "dest2".to_string()]);
+        assert_eq!(plan.total_amount, 2.0);
+        assert_eq!(plan.steps, 3);
+        assert_eq!(plan.seed, None);
+        
+        form.seed = "42".to_string();
+        assert_eq!(form.to_plan(&wallets).unwrap().seed, Some(42));
+        
+        // An empty selection is reported without leaving the view
+        let mut app = App::new();
+        app.wallets = wallets;
+        app.current_view = View::TokenMixing;
+        handle_key_event(&mut app, KeyCode::Enter);
+        assert!(matches!(app.current_view, View::TokenMixing));
+        assert!(matches!(app.status_message, Some(StatusMessage { status_type: StatusType::Warning, .. })));
+        assert!(app.mixing_form.plan.is_none());
+    }
+    
+    #[test]
+    fn test_airdrop_action_refused_on_mainnet() {
+        let mut app = App::new();
+        app.wallets = vec!["wallet1".to_string()];
+        app.filtered_wallets = vec![0];
+        app.selected_wallet = Some(0);
+        app.current_view = View::WalletDetail;
+        app.rpc_url = rpc::DEFAULT_RPC_URL.to_string();
+        
+        handle_key_event(&mut app, KeyCode::Char('a'));
+        assert!(app.airdrop_rx.is_none());
+        assert!(matches!(app.status_message, Some(StatusMessage { status_type: StatusType::Warning, .. })));
+        assert!(matches!(app.current_view, View::WalletDetail));
+    }
+    
+    #[test]
+    fn test_airdrop_result_reaches_status_bar() {
+        let mut app = App::new();
+        app.wallet_details = vec![sample_detail("wallet1", None, None)];
+        app.wallet_details[0].transactions = Some(Vec::new());
+        
+        let (tx, rx) = mpsc::channel();
+        app.airdrop_rx = Some(rx);
+        tx.send(("wallet1".to_string(), Ok("sig".to_string()))).unwrap();
+        app.poll_airdrop();
+        assert!(app.airdrop_rx.is_none());
+        assert!(matches!(app.status_message, Some(StatusMessage { status_type: StatusType::Success, .. })));
+        // History is fetched again so the airdrop shows up
+        assert!(app.wallet_details[0].transactions.is_none());
+        
+        let (tx, rx) = mpsc::channel();
+        app.airdrop_rx = Some(rx);
+        tx.send(("wallet1".to_string(), Err("rate limited".to_string()))).unwrap();
+        app.poll_airdrop();
+        assert!(matches!(app.status_message, Some(StatusMessage { status_type: StatusType::Error, .. })));
+    }
+    
+    #[test]
+    fn test_rename_view_transitions() {
+        let mut app = App::new();
+        app.wallets = vec!["wallet1".to_string(), "wallet2".to_string()];
+        app.filtered_wallets = vec![0, 1];
+        app.selected_wallet = Some(1);
+        app.current_view = View::WalletDetail;
+
+        // 'n' opens the rename view prefilled with the current name
+        handle_key_event(&mut app, KeyCode::Char('n'));
+        assert!(matches!(app.current_view, View::RenameWallet));
+        assert_eq!(app.input_buffer, "wallet2");
+
+        handle_key_event(&mut app, KeyCode::Backspace);
+        handle_key_event(&mut app, KeyCode::Char('9'));
+        assert_eq!(app.input_buffer, "wallet9");
+
+        // Esc returns to the detail view and discards the input
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert!(matches!(app.current_view, View::WalletDetail));
+        assert!(app.input_buffer.is_empty());
+    }
+
+    #[test]
+    fn test_reveal_secret_requires_typed_confirmation() {
+        let mut app = App::new();
+        app.wallets = vec!["main".to_string()];
+        app.selected_wallet = Some(0);
+        app.current_view = View::WalletDetail;
+
+        handle_key_event(&mut app, KeyCode::Char('k'));
+        assert!(matches!(app.current_view, View::RevealSecret));
+
+        // Anything but the exact confirmation keeps the secret hidden
+        for c in "reveal".chars() {
+            handle_key_event(&mut app, KeyCode::Char(c));
+        }
+        handle_key_event(&mut app, KeyCode::Enter);
+        assert!(app.revealed_secret.is_none());
+        assert!(matches!(app.status_message, Some(StatusMessage { status_type: StatusType::Warning, .. })));
+        assert!(matches!(app.current_view, View::RevealSecret));
+
+        // Esc before confirming goes back to the details
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert!(matches!(app.current_view, View::WalletDetail));
+        assert!(app.input_buffer.is_empty());
+    }
+
+    #[test]
+    fn test_leaving_reveal_view_clears_secret() {
+        let mut app = App::new();
+        app.wallets = vec!["main".to_string()];
+        app.selected_wallet = Some(0);
+        app.current_view = View::RevealSecret;
+        app.revealed_secret = Some(Zeroizing::new(vec![7u8; 64]));
+
+        // Once revealed, 's' toggles the mask
+        handle_key_event(&mut app, KeyCode::Char('s'));
+        assert!(app.secret_visible);
+
+        let screen = render_to_text(&mut app);
+        assert!(screen.contains("[7,7,7"), "Secret not shown: {}", screen);
+
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert!(matches!(app.current_view, View::WalletDetail));
+        assert!(app.revealed_secret.is_none());
+        assert!(!app.secret_visible);
+
+        // Coming back asks for the confirmation again instead of showing the key
+        app.current_view = View::RevealSecret;
+        let screen = render_to_text(&mut app);
+        assert!(!screen.contains("7,7"), "Secret still shown: {}", screen);
+        assert!(screen.contains(REVEAL_CONFIRMATION));
+    }
+
+    #[test]
+    fn test_secret_bytes_format_as_a_keypair_file() {
+        let text: Zeroizing<String> = format_secret_bytes(&[0, 7, 42, 255]);
+        assert_eq!(text.as_str(), "[0,7,42,255]");
+        assert_eq!(format_secret_bytes(&[]).as_str(), "[]");
+
+        // Whatever the bytes, the buffer sized up front is never outgrown
+        let key_bytes = [255u8; 64];
+        let text = format_secret_bytes(&key_bytes);
+        assert_eq!(text.capacity(), 2 + key_bytes.len() * 4);
+        assert_eq!(serde_json::from_str::<Vec<u8>>(&text).unwrap(), key_bytes.to_vec());
+    }
+
+    // Draws the whole UI into an in-memory terminal and returns its text
+    fn render_to_text(app: &mut App) -> String {
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|frame| ui(frame, app)).unwrap();
+        terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn test_scan_view_transitions() {
+        let dir = tempfile::tempdir().unwrap();
+        let key_bytes = solana_sdk::signer::keypair::Keypair::new().to_bytes().to_vec();
+        std::fs::write(dir.path().join("found.json"), serde_json::to_string(&key_bytes).unwrap()).unwrap();
+        std::fs::write(dir.path().join("other.json"), "{}").unwrap();
+
+        let mut app = App::new();
+        handle_key_event(&mut app, KeyCode::Char('f'));
+        assert!(matches!(app.current_view, View::ScanWallets));
+
+        // A path that is not a directory does not start a scan
+        app.input_buffer = dir.path().join("missing").to_string_lossy().into_owned();
+        handle_key_event(&mut app, KeyCode::Enter);
+        assert!(app.scan_thread.is_none());
+
+        app.input_buffer = dir.path().to_string_lossy().into_owned();
+        handle_key_event(&mut app, KeyCode::Enter);
+        assert!(app.scan_thread.is_some());
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while app.scan_thread.is_some() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+            app.update_scan_status();
+        }
+        assert_eq!(app.scan_results.len(), 1);
+        assert!(app.scan_results[0].ends_with("found.json"));
+        assert!(matches!(app.current_view, View::ScanWallets));
+
+        // Esc leaves the view and forgets the results
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert!(matches!(app.current_view, View::WalletList));
+        assert!(app.scan_results.is_empty());
+    }
+
+    #[test]
+    fn test_add_wallet_reports_what_the_file_contains() {
+        let dir = tempfile::tempdir().unwrap();
+        let key_path = dir.path().join("short.json");
+        std::fs::write(&key_path, serde_json::to_string(&[7u8; 31]).unwrap()).unwrap();
+
+        let mut app = App::new();
+        app.add_wallet(key_path.to_string_lossy().into_owned());
+        let status = app.status_message.as_ref().unwrap();
+        assert!(matches!(status.status_type, StatusType::Error));
+        assert!(status.message.contains("array of 31 numbers — expected 64 (keypair) or 32"), "{}", status.message);
+    }
+
+    #[test]
+    fn test_cancel_running_scan() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = App::new();
+        app.current_view = View::ScanWallets;
+        app.input_buffer = dir.path().to_string_lossy().into_owned();
+        handle_key_event(&mut app, KeyCode::Enter);
+        assert!(app.scan_thread.is_some());
+
+        // Esc during a scan stops it but stays on the scan view
+        let cancelled = Arc::clone(&app.scan_cancelled);
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert!(cancelled.load(Ordering::SeqCst));
+        assert!(app.scan_thread.is_none());
+        assert!(matches!(app.current_view, View::ScanWallets));
+        thread::sleep(Duration::from_millis(50));
+        app.update_scan_status();
+        assert!(app.scan_results.is_empty());
+    }
+
+    #[test]
+    fn test_seed_import_previews_the_chosen_account() {
+        let mut app = App::new();
+        handle_key_event(&mut app, KeyCode::Char('m'));
+        assert!(matches!(app.current_view, View::ImportSeed));
+        
+        let phrase = "neither lonely flavor argue grass remind eye tag avocado spot unusual intact";
+        for c in phrase.chars() {
+            handle_key_event(&mut app, KeyCode::Char(c));
+        }
+        assert_eq!(app.seed_form.phrase.as_str(), phrase);
+        
+        handle_key_event(&mut app, KeyCode::Enter);
+        let first = app.seed_form.preview.expect("account 0 previewed");
+        assert_eq!(first.to_string(), "5vftMkHL72JaJG6ExQfGAsT2uGVHpRR7oTNUPMs68Y2N");
+        
+        // Editing the index drops the preview, and the new one is a different address
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert!(matches!(app.current_view, View::ImportSeed));
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.seed_form.focus, SeedField::Account);
+        handle_key_event(&mut app, KeyCode::Backspace);
+        handle_key_event(&mut app, KeyCode::Char('x'));
+        handle_key_event(&mut app, KeyCode::Char('3'));
+        assert_eq!(app.seed_form.account, "3");
+        assert!(app.seed_form.preview.is_none());
+        handle_key_event(&mut app, KeyCode::Enter);
+        let third = app.seed_form.preview.expect("account 3 previewed");
+        assert_ne!(third, first);
+        assert_eq!(third, wallet_manager::seed_account_pubkey(phrase, 3).unwrap());
+        
+        // Implausible indexes are refused without a preview
+        handle_key_event(&mut app, KeyCode::Esc);
+        app.seed_form.account = (MAX_SEED_ACCOUNT_INDEX + 1).to_string();
+        handle_key_event(&mut app, KeyCode::Enter);
+        assert!(app.seed_form.preview.is_none());
+        assert!(matches!(app.status_message.as_ref().map(|m| &m.status_type), Some(StatusType::Warning)));
+        
+        // Leaving the form forgets the phrase
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert!(matches!(app.current_view, View::WalletList));
+        assert!(app.seed_form.phrase.is_empty());
+    }
+    
+    #[test]
+    fn test_vanity_form_field_cycling() {
+        let mut app = App::new();
+        handle_key_event(&mut app, KeyCode::Char('v'));
+        assert!(matches!(app.current_view, View::CreateVanityWallet));
+        assert_eq!(app.vanity_focus, VanityField::WalletName);
+
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.vanity_focus, VanityField::Prefix);
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.vanity_focus, VanityField::Suffix);
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.vanity_focus, VanityField::Timeout);
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.vanity_focus, VanityField::ThreadCount);
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.vanity_focus, VanityField::WalletName);
+
+        handle_key_event(&mut app, KeyCode::BackTab);
+        assert_eq!(app.vanity_focus, VanityField::ThreadCount);
+        handle_key_event(&mut app, KeyCode::BackTab);
+        assert_eq!(app.vanity_focus, VanityField::Timeout);
+    }
+
+    #[test]
+    fn test_vanity_timeout_rejects_letters_and_clamps() {
+        let mut app = App::new();
+        handle_key_event(&mut app, KeyCode::Char('v'));
+        app.vanity_focus = VanityField::Timeout;
+        app.vanity_timeout_input = "12".to_string();
+
+        handle_key_event(&mut app, KeyCode::Char('a'));
+        assert_eq!(app.vanity_timeout_input, "12");
+        assert!(matches!(app.status_message.as_ref().map(|m| &m.status_type), Some(StatusType::Warning)));
+
+        app.status_message = None;
+        for c in "34567".chars() {
+            handle_key_event(&mut app, KeyCode::Char(c));
+        }
+        assert_eq!(app.vanity_timeout_input, MAX_VANITY_TIMEOUT_SECONDS.to_string());
+        assert!(matches!(app.status_message.as_ref().map(|m| &m.status_type), Some(StatusType::Warning)));
+
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.vanity_focus, VanityField::ThreadCount);
+        assert_eq!(app.vanity_config.timeout_seconds, MAX_VANITY_TIMEOUT_SECONDS);
+    }
+
+    #[test]
+    fn test_vanity_form_numeric_validation() {
+        let mut app = App::new();
+        handle_key_event(&mut app, KeyCode::Char('v'));
+        handle_key_event(&mut app, KeyCode::Tab);
+        handle_key_event(&mut app, KeyCode::Tab);
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.vanity_focus, VanityField::Timeout);
+
+        // Non-digits are rejected with a warning
+        app.vanity_timeout_input.clear();
+        handle_key_event(&mut app, KeyCode::Char('x'));
+        assert!(app.vanity_timeout_input.is_empty());
+        assert!(matches!(app.status_message.as_ref().map(|m| &m.status_type), Some(StatusType::Warning)));
+
+        // A zero timeout keeps the focus on the field
+        handle_key_event(&mut app, KeyCode::Char('0'));
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.vanity_focus, VanityField::Timeout);
+
+        handle_key_event(&mut app, KeyCode::Backspace);
+        handle_key_event(&mut app, KeyCode::Char('4'));
+        handle_key_event(&mut app, KeyCode::Char('5'));
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.vanity_focus, VanityField::ThreadCount);
+        assert_eq!(app.vanity_config.timeout_seconds, 45);
+
+        app.vanity_threads_input = (num_cpus::get() + 1).to_string();
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.vanity_focus, VanityField::ThreadCount);
+
+        app.vanity_threads_input = "1".to_string();
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.vanity_focus, VanityField::WalletName);
+        assert_eq!(app.vanity_config.thread_count, 1);
+    }
+
+    #[test]
+    fn test_vanity_prefix_from_config_and_form() {
+        let mut config = Config::default();
+        config.vanity.default_prefix = "sol".to_string();
+        let mut app = App::with_config(config);
+        assert_eq!(app.vanity_config.prefix, "sol");
+
+        handle_key_event(&mut app, KeyCode::Char('v'));
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.vanity_focus, VanityField::Prefix);
+
+        // A non-base58 prefix is rejected when leaving the field
+        app.vanity_config.prefix.clear();
+        for c in "ab0".chars() {
+            handle_key_event(&mut app, KeyCode::Char(c));
+        }
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.vanity_focus, VanityField::Prefix);
+
+        handle_key_event(&mut app, KeyCode::Backspace);
+        handle_key_event(&mut app, KeyCode::Char('c'));
+        app.vanity_config.timeout_seconds = 1;
+        app.vanity_config.thread_count = 1;
+        handle_key_event(&mut app, KeyCode::Enter);
+
+        // The three-character prefix is what generation runs with
+        assert!(matches!(app.current_view, View::VanityProgress));
+        assert_eq!(app.vanity_config.prefix, "abc");
+        assert!(app.status_message.as_ref().unwrap().message.contains("'abc'"));
+        app.vanity_thread.take().unwrap().join().unwrap();
+
+        // Leaving and reopening the form keeps the chosen prefix
+        app.current_view = View::WalletList;
+        handle_key_event(&mut app, KeyCode::Char('v'));
+        assert_eq!(app.vanity_config.prefix, "abc");
+    }
+
+    #[test]
+    fn test_long_vanity_search_needs_confirmation() {
+        let mut app = App::new();
+        handle_key_event(&mut app, KeyCode::Char('v'));
+        app.vanity_config.prefix = "abcdef".to_string();
+        app.vanity_config.thread_count = 1;
+        app.vanity_config.timeout_seconds = 60;
+        
+        // The first Enter only shows the estimate
+        handle_key_event(&mut app, KeyCode::Enter);
+        assert!(matches!(app.current_view, View::CreateVanityWallet));
+        assert!(app.vanity_confirming);
+        assert!(vanity_confirmation(&app.vanity_config).contains("almost certainly time out"));
+        
+        // Esc backs out of the confirmation but stays in the form
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert!(!app.vanity_confirming);
+        assert!(matches!(app.current_view, View::CreateVanityWallet));
+        
+        // Editing the form also drops a pending confirmation
+        handle_key_event(&mut app, KeyCode::Enter);
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert!(!app.vanity_confirming);
+        assert!(!vanity_confirmation(&VanityConfig { prefix: "ab".to_string(), ..app.vanity_config.clone() })
+            .contains("time out"));
+    }
+    
+    #[test]
+    fn test_second_vanity_save_keeps_the_first_key() {
+        let (temp_dir, test_service_name, _env_guard) = secure_storage::test_env::setup_test_env();
+        let mut app = App::new();
+        let first = solana_sdk::signer::keypair::Keypair::new();
+        let second = solana_sdk::signer::keypair::Keypair::new();
+        
+        // Both runs use the default name, so the second one must not replace the first
+        app.save_vanity_wallet(&first);
+        app.save_vanity_wallet(&second);
+        assert_eq!(wallet_manager::get_wallet_pubkey("ai_wallet").unwrap(), Some(first.pubkey()));
+        assert_eq!(wallet_manager::get_wallet_pubkey("ai_wallet_2").unwrap(), Some(second.pubkey()));
+        assert!(app.status_message.as_ref().unwrap().message.contains("'ai_wallet_2'"));
+        
+        secure_storage::test_env::teardown_test_env(temp_dir, &test_service_name);
+    }
+    
+    #[test]
+    fn test_vanity_save_leaves_no_plaintext_in_temp_dir() {
+        let (temp_dir, test_service_name, _env_guard) = secure_storage::test_env::setup_test_env();
+        let files_in_temp_dir = || -> HashSet<PathBuf> {
+            std::fs::read_dir(std::env::temp_dir()).unwrap()
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file())
+                .collect()
+        };
+        let before = files_in_temp_dir();
+        let mut app = App::new();
+        app.vanity_wallet_name = format!("vanity_no_temp_{}", std::process::id());
+        let keypair = solana_sdk::signer::keypair::Keypair::new();
+        app.save_vanity_wallet(&keypair);
+        
+        // Saving used to go through a key file in the temp dir
+        let residual = std::env::temp_dir().join(format!("{}.json", app.vanity_wallet_name));
+        assert!(!residual.exists(), "Plaintext key left at {}", residual.display());
+        let secret_json = serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap();
+        for path in files_in_temp_dir().difference(&before) {
+            let contents = std::fs::read(path).unwrap_or_default();
+            assert!(!String::from_utf8_lossy(&contents).contains(&secret_json), "Plaintext key left at {}", path.display());
+        }
+        assert_eq!(wallet_manager::get_wallet_pubkey(&app.vanity_wallet_name).unwrap(), Some(keypair.pubkey()));
+        
+        secure_storage::test_env::teardown_test_env(temp_dir, &test_service_name);
+    }
+    
+    #[test]
+    fn test_vanity_suffix_multiplies_the_estimate() {
+        let mut app = App::new();
+        handle_key_event(&mut app, KeyCode::Char('v'));
+        app.vanity_focus = VanityField::Suffix;
+        app.vanity_config.prefix = "ab".to_string();
+        app.vanity_config.thread_count = 1;
+        
+        // An invalid suffix keeps the focus on the field
+        handle_key_event(&mut app, KeyCode::Char('0'));
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.vanity_focus, VanityField::Suffix);
+        
+        handle_key_event(&mut app, KeyCode::Backspace);
+        for c in "cd".chars() {
+            handle_key_event(&mut app, KeyCode::Char(c));
+        }
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.vanity_focus, VanityField::Timeout);
+        assert_eq!(app.vanity_config.suffix, "cd");
+        
+        // Two plus two characters is as hard as a four-character prefix
+        assert_eq!(app.vanity_config.expected_attempts(), vanity_wallet::estimated_attempts("abcd"));
+        let text = vanity_confirmation(&app.vanity_config);
+        assert!(text.contains("prefix 'ab' and suffix 'cd'"));
+        assert!(text.contains("for the suffix"));
+        
+        // A search for how the address ends takes no second suffix
+        app.vanity_config.match_mode = vanity_wallet::Match::Suffix;
+        app.vanity_focus = VanityField::Suffix;
+        handle_key_event(&mut app, KeyCode::Tab);
+        assert_eq!(app.vanity_focus, VanityField::Suffix);
+        assert!(app.status_message.as_ref().unwrap().message.contains("second suffix"));
+        app.start_vanity_wallet_creation();
+        assert!(app.vanity_thread.is_none());
+    }
+    
+    #[test]
+    fn test_space_pauses_and_resumes_vanity_search() {
+        let mut app = App::new();
+        app.current_view = View::VanityProgress;
+        
+        handle_key_event(&mut app, KeyCode::Char(' '));
+        assert!(app.vanity_paused.load(Ordering::SeqCst));
+        handle_key_event(&mut app, KeyCode::Char(' '));
+        assert!(!app.vanity_paused.load(Ordering::SeqCst));
+        
+        // Cancelling a paused search wakes the workers so they can stop
+        handle_key_event(&mut app, KeyCode::Char(' '));
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert!(!app.vanity_paused.load(Ordering::SeqCst));
+        assert!(app.vanity_cancelled.load(Ordering::SeqCst));
+        assert!(matches!(app.current_view, View::WalletList));
+    }
+    
+    #[test]
+    fn test_changed_vanity_settings_are_saved_on_leaving_the_form() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        let mut file_config = Config::default();
+        file_config.vanity.timeout_seconds = 90;
+        file_config.vanity.max_threads = 1;
+        config::save_config_to_file(&file_config, &config_path).unwrap();
+        // Thread counts are capped at the cores, so use all of them as the new value
+        let cores = num_cpus::get();
+        
+        let mut app = App::with_config(file_config);
+        app.settings_path = Some(config_path.clone());
+        
+        // Leaving the form unchanged writes nothing
+        handle_key_event(&mut app, KeyCode::Char('v'));
+        handle_key_event(&mut app, KeyCode::Esc);
+        assert_eq!(app.saved_settings, app.session_settings());
+        
+        handle_key_event(&mut app, KeyCode::Char('v'));
+        handle_key_event(&mut app, KeyCode::Tab);
+        app.vanity_config.prefix = "sol".to_string();
+        app.vanity_threads_input = cores.to_string();
+        app.vanity_focus = VanityField::ThreadCount;
+        assert!(app.commit_vanity_field());
+        handle_key_event(&mut app, KeyCode::Esc);
+        
+        let saved = config::load_config_from_file(&config_path).unwrap();
+        assert_eq!(saved.vanity.default_prefix, "sol");
+        assert_eq!(saved.vanity.max_threads, cores);
+        assert_eq!(saved.vanity.timeout_seconds, 90);
+        assert_eq!(app.saved_settings, app.session_settings());
+        
+        // A new session starts from the saved values
+        let restored = App::with_config(saved);
+        assert_eq!(restored.vanity_config.prefix, "sol");
+        assert_eq!(restored.vanity_config.thread_count, cores);
+    }
+    
+    #[test]
+    fn test_vanity_progress_fraction() {
+        assert_eq!(vanity_progress_fraction(0, 58.0), Some(0.0));
+        assert_eq!(vanity_progress_fraction(29, 58.0), Some(0.5));
+        assert_eq!(vanity_progress_fraction(58, 58.0), Some(1.0));
+
+        // Exceeding the estimate switches to the indeterminate display
+        assert_eq!(vanity_progress_fraction(59, 58.0), None);
+
+        // A degenerate estimate never yields a bogus ratio
+        assert_eq!(vanity_progress_fraction(10, 0.0), None);
+        assert_eq!(vanity_progress_fraction(10, f64::INFINITY), None);
+    }
+
     // More tests would require mocking wallet_manager or having a test setup for it.
 }
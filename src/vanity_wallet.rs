@@ -1,19 +1,65 @@
 // vanity_wallet.rs
 
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signer::keypair::Keypair;
 use solana_sdk::signature::Signer;
-use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::thread;
 use std::time::{Duration, Instant};
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::io;
+use std::path::{Path, PathBuf};
+
+/// Characters that can appear in a base58-encoded Solana address
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Attempts a worker counts locally before adding them to the shared counter,
+/// so threads don't contend on it for every keypair
+const ATTEMPT_BATCH: u64 = 256;
+
+/// How often parked workers check whether the search was resumed
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Conservative keypair generation rate of one thread, used to estimate run
+/// times before a search has measured its own speed
+pub const ESTIMATED_ATTEMPTS_PER_THREAD: f64 = 25_000.0;
+
+/// Most threads picked automatically, to avoid excessive CPU usage
+const AUTO_THREAD_LIMIT: usize = 8;
+
+/// A prefix no address can start with ('0' is not a base58 character), used
+/// to keep the generator busy for a whole benchmark run
+const UNREACHABLE_PREFIX: &str = "impossible1234567890";
+
+/// File in the data directory holding lifetime search statistics
+const VANITY_STATS_FILE_NAME: &str = "vanity_stats.json";
+
+/// Where in the address the target string has to appear
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Match {
+    /// The address starts with the target
+    #[default]
+    Prefix,
+    /// The address ends with the target
+    Suffix,
+    /// The target appears anywhere in the address
+    Contains,
+}
 
 /// Configuration for vanity address generation
 #[derive(Clone)]
 pub struct VanityConfig {
     /// The prefix that the address should start with (case-insensitive)
     pub prefix: String,
+    /// Prefixes any of which is acceptable; when non-empty this replaces `prefix`
+    pub prefixes: Vec<String>,
+    /// Where the prefix (or each of `prefixes`) has to appear in the address
+    pub match_mode: Match,
+    /// What the address must also end with (case-insensitive); empty for no suffix
+    pub suffix: String,
     /// Maximum time to spend searching (in seconds)
     pub timeout_seconds: u64,
     /// Number of threads to use for grinding
@@ -26,14 +72,84 @@ impl Default for VanityConfig {
     fn default() -> Self {
         VanityConfig {
             prefix: "ai".to_string(),
+            prefixes: Vec::new(),
+            match_mode: Match::Prefix,
+            suffix: String::new(),
             timeout_seconds: 60,
-            // Limit to 8 threads to avoid excessive CPU usage
-            thread_count: num_cpus::get().min(8),
+            thread_count: configured_thread_count(0),
             progress_interval_ms: 500,
         }
     }
 }
 
+impl VanityConfig {
+    /// The prefixes a generated address may start with: `prefixes` when set,
+    /// otherwise just `prefix`
+    pub fn accepted_prefixes(&self) -> Vec<String> {
+        if self.prefixes.is_empty() {
+            vec![self.prefix.clone()]
+        } else {
+            self.prefixes.clone()
+        }
+    }
+    
+    /// Checks the extra suffix: it has to be base58, and a search that
+    /// already matches the end of the address in `match_mode` cannot take one.
+    pub fn validate_suffix(&self) -> Result<(), VanityError> {
+        if self.suffix.is_empty() {
+            return Ok(());
+        }
+        if self.match_mode == Match::Suffix {
+            return Err(VanityError::InvalidPrefix(
+                "a suffix search already matches the end of the address, so it cannot take a second suffix".to_string(),
+            ));
+        }
+        validate_prefix(&self.suffix)
+    }
+    
+    /// Expected attempts to match the prefix part alone. Any of the accepted
+    /// prefixes will do, so their chances add up.
+    pub fn prefix_attempts(&self) -> f64 {
+        match self.accepted_prefixes().as_slice() {
+            [prefix] => estimated_attempts_for(prefix, self.match_mode),
+            prefixes => {
+                let chance: f64 = prefixes
+                    .iter()
+                    .map(|prefix| 1.0 / estimated_attempts_for(prefix, self.match_mode))
+                    .sum();
+                (1.0 / chance).max(1.0)
+            },
+        }
+    }
+    
+    /// Expected attempts to find a matching address: those of the prefix part
+    /// multiplied by those of the suffix, which sits at the other end
+    pub fn expected_attempts(&self) -> f64 {
+        self.prefix_attempts() * estimate_difficulty_combined("", &self.suffix, false) as f64
+    }
+    
+    /// What the search looks for, e.g. "prefix 'ab'" or "prefix 'ab' and suffix 'cd'"
+    pub fn target_description(&self) -> String {
+        let target = match self.match_mode {
+            Match::Prefix => "prefix",
+            Match::Suffix => "suffix",
+            Match::Contains => "text",
+        };
+        let mut description = format!("{} '{}'", target, self.accepted_prefixes().join("|"));
+        if !self.suffix.is_empty() {
+            description.push_str(&format!(" and suffix '{}'", self.suffix));
+        }
+        description
+    }
+}
+
+/// A generated keypair together with the accepted prefix its address starts with
+#[derive(Debug)]
+pub struct VanityMatch {
+    pub keypair: Keypair,
+    pub prefix: String,
+}
+
 /// Status of the vanity address generation process
 #[derive(Clone, Debug)]
 pub struct VanityStatus {
@@ -45,6 +161,8 @@ pub struct VanityStatus {
     pub success: bool,
     /// The generated public key as string (if successful)
     pub pubkey: Option<String>,
+    /// The accepted prefix the generated address starts with (if successful)
+    pub matched_prefix: Option<String>,
     /// Time elapsed in seconds
     pub elapsed_seconds: f64,
     /// Attempts per second
@@ -58,6 +176,8 @@ pub enum VanityError {
     Timeout,
     /// The process was cancelled
     Cancelled,
+    /// The requested prefix can never appear in an address
+    InvalidPrefix(String),
     /// An I/O error occurred
     IoError(String),
 }
@@ -67,6 +187,7 @@ impl std::fmt::Display for VanityError {
         match self {
             VanityError::Timeout => write!(f, "Vanity address generation timed out"),
             VanityError::Cancelled => write!(f, "Vanity address generation was cancelled"),
+            VanityError::InvalidPrefix(msg) => write!(f, "Invalid vanity prefix: {}", msg),
             VanityError::IoError(e) => write!(f, "I/O error: {}", e),
         }
     }
@@ -80,49 +201,312 @@ impl From<io::Error> for VanityError {
     }
 }
 
-/// Generate a keypair with a vanity address that starts with the specified prefix
-pub fn generate_vanity_keypair(config: &VanityConfig) -> Result<Keypair, VanityError> {
-    // Clone all values needed by threads to avoid lifetime issues
-    let prefix = config.prefix.to_lowercase();
-    let timeout_secs = config.timeout_seconds;
-    let thread_count = config.thread_count;
-    let progress_interval_ms = config.progress_interval_ms;
+/// Upper bound on the case variants a prefix may expand to before the matcher
+/// gives up on precomputed ranges and encodes every key instead
+const MAX_PREFIX_VARIANTS: usize = 4096;
+
+// Big-endian integer wide enough for 58^44, the largest bound a range needs
+type WideInt = [u8; 34];
+
+fn wide_mul_add(value: &mut WideInt, mul: u32, add: u32) {
+    let mut carry = add;
+    for byte in value.iter_mut().rev() {
+        let product = *byte as u32 * mul + carry;
+        *byte = product as u8;
+        carry = product >> 8;
+    }
+}
+
+/// Decides whether a public key's base58 form starts with a prefix (ignoring
+/// case) without encoding the key.
+///
+/// A key without leading zero bytes encodes to 43 or 44 digits, so it starts
+/// with the digits of `p` exactly when its 256-bit value lies in
+/// `[p * 58^(L-k), (p+1) * 58^(L-k))` for one of those lengths `L`. Every case
+/// variant of the prefix contributes such ranges, and a key is checked with a
+/// binary search over them. Keys with a leading zero byte (which encode with a
+/// leading '1') are rare and fall back to encoding.
+///
+/// Suffix and contains matches have no such shortcut and always encode, as
+/// does checking an extra suffix once the prefix has matched.
+struct PrefixMatcher {
+    prefix: String,
+    mode: Match,
+    // Sorted, disjoint half-open ranges; None means always encode instead
+    ranges: Option<Vec<(WideInt, WideInt)>>,
+    suffix: String, // Lowercase suffix the address must also end with; empty for none
+}
+
+impl PrefixMatcher {
+    fn new(prefix: &str, mode: Match) -> Self {
+        let prefix = prefix.to_lowercase();
+        if mode != Match::Prefix {
+            return PrefixMatcher { prefix, mode, ranges: None, suffix: String::new() };
+        }
+        
+        // Digit values each prefix character may stand for
+        let candidates: Vec<Vec<u32>> = prefix
+            .chars()
+            .map(|c| {
+                BASE58_ALPHABET
+                    .chars()
+                    .enumerate()
+                    .filter(|(_, a)| a.to_ascii_lowercase() == c)
+                    .map(|(digit, _)| digit as u32)
+                    .collect()
+            })
+            .collect();
+        
+        let variant_count = candidates
+            .iter()
+            .try_fold(1usize, |count, digits| count.checked_mul(digits.len()));
+        let ranges = match variant_count {
+            Some(count) if count <= MAX_PREFIX_VARIANTS => Some(Self::build_ranges(&candidates)),
+            _ => None,
+        };
+        
+        PrefixMatcher { prefix, mode, ranges, suffix: String::new() }
+    }
     
-    let start_time = Instant::now();
-    let timeout = Duration::from_secs(timeout_secs);
+    // Also requires the address to end with `suffix`
+    fn with_suffix(mut self, suffix: &str) -> Self {
+        self.suffix = suffix.to_lowercase();
+        self
+    }
+    
+    fn build_ranges(candidates: &[Vec<u32>]) -> Vec<(WideInt, WideInt)> {
+        // Expand every combination of candidate digits
+        let mut variants: Vec<Vec<u32>> = vec![Vec::new()];
+        for digits in candidates {
+            variants = variants
+                .iter()
+                .flat_map(|variant| digits.iter().map(move |&d| {
+                    let mut next = variant.clone();
+                    next.push(d);
+                    next
+                }))
+                .collect();
+        }
+        
+        let mut ranges = Vec::new();
+        for variant in variants {
+            // An empty prefix matches every key, i.e. all of [0, 2^256)
+            if variant.is_empty() {
+                let mut all = [0u8; 34];
+                all[1] = 1;
+                ranges.push(([0u8; 34], all));
+                continue;
+            }
+            // A leading '1' only comes from a leading zero byte, handled by the fallback
+            if variant[0] == 0 {
+                continue;
+            }
+            for length in [43, 44] {
+                if variant.len() > length {
+                    continue;
+                }
+                let mut low = [0u8; 34];
+                for &digit in &variant {
+                    wide_mul_add(&mut low, 58, digit);
+                }
+                let mut high = low;
+                wide_mul_add(&mut high, 1, 1);
+                for _ in 0..length - variant.len() {
+                    wide_mul_add(&mut low, 58, 0);
+                    wide_mul_add(&mut high, 58, 0);
+                }
+                ranges.push((low, high));
+            }
+        }
+        ranges.sort();
+        ranges
+    }
+    
+    fn matches(&self, pubkey: &Pubkey) -> bool {
+        self.matches_target(pubkey)
+            && (self.suffix.is_empty() || pubkey.to_string().to_lowercase().ends_with(&self.suffix))
+    }
+    
+    fn matches_target(&self, pubkey: &Pubkey) -> bool {
+        let bytes = pubkey.to_bytes();
+        match &self.ranges {
+            Some(ranges) if bytes[0] != 0 => {
+                let mut value = [0u8; 34];
+                value[2..].copy_from_slice(&bytes);
+                let index = ranges.partition_point(|(low, _)| *low <= value);
+                index > 0 && value < ranges[index - 1].1
+            },
+            _ => {
+                let address = pubkey.to_string().to_lowercase();
+                match self.mode {
+                    Match::Prefix => address.starts_with(&self.prefix),
+                    Match::Suffix => address.ends_with(&self.prefix),
+                    Match::Contains => address.contains(&self.prefix),
+                }
+            },
+        }
+    }
+}
+
+// Flags shared by the workers of one search
+#[derive(Clone, Copy)]
+struct SearchFlags<'a> {
+    found: &'a AtomicBool,     // Set by the worker that finds a match
+    cancelled: &'a AtomicBool, // Set to stop the search
+    paused: &'a AtomicBool,    // Set while the workers should park
+}
+
+impl SearchFlags<'_> {
+    fn stopped(&self) -> bool {
+        self.found.load(Ordering::SeqCst) || self.cancelled.load(Ordering::SeqCst)
+    }
+    
+    // Parks the calling thread while the search is paused but not stopped,
+    // returning how long it waited
+    fn wait_while_paused(&self) -> Duration {
+        let start = Instant::now();
+        while self.paused.load(Ordering::SeqCst) && !self.stopped() {
+            thread::sleep(PAUSE_POLL_INTERVAL);
+        }
+        start.elapsed()
+    }
+}
+
+// Grinds keypairs on the current thread until one matches, another worker sets `found`, the search is cancelled, or the
+// deadline passes. Attempts are added to `attempts` in batches and in full
+// before returning; the return value includes this worker's own total.
+// While the search is paused the worker parks between batches, and the time
+// spent parked does not count towards the deadline.
+fn search_worker(
+    matchers: &[PrefixMatcher],
+    mut deadline: Instant,
+    flags: SearchFlags,
+    attempts: &AtomicU64,
+) -> (Option<(Keypair, usize)>, u64) {
+    let mut total = 0u64;
+    let mut unreported = 0u64;
+    let mut result = None;
     
+    loop {
+        // Generate a new random keypair
+        let keypair = Keypair::new();
+        total += 1;
+        unreported += 1;
+        
+        // Check if the address starts with any of the desired prefixes
+        let pubkey = keypair.pubkey();
+        if let Some(index) = matchers.iter().position(|matcher| matcher.matches(&pubkey)) {
+            flags.found.store(true, Ordering::SeqCst);
+            result = Some((keypair, index));
+            break;
+        }
+        
+        if unreported == ATTEMPT_BATCH {
+            attempts.fetch_add(unreported, Ordering::Relaxed);
+            unreported = 0;
+            
+            deadline += flags.wait_while_paused();
+            if flags.stopped() || Instant::now() >= deadline {
+                break;
+            }
+            // Yield to allow other threads to run, especially important for cancellation
+            thread::yield_now();
+        }
+    }
+    
+    attempts.fetch_add(unreported, Ordering::Relaxed);
+    (result, total)
+}
+
+// Runs `thread_count` search workers and returns the first match along with
+// the prefix it matched
+fn run_search(
+    prefixes: &[String],
+    mode: Match,
+    suffix: &str,
+    thread_count: usize,
+    deadline: Instant,
+    flags: SearchFlags,
+    attempts: &AtomicU64,
+) -> Option<VanityMatch> {
     // Create a thread pool with the specified number of threads
     let pool = rayon::ThreadPoolBuilder::new()
         .num_threads(thread_count)
         .build()
         .unwrap();
     
+    let matchers: Vec<PrefixMatcher> = prefixes
+        .iter()
+        .map(|prefix| PrefixMatcher::new(prefix, mode).with_suffix(suffix))
+        .collect();
+    pool.install(|| {
+        (0..thread_count)
+            .into_par_iter()
+            .find_map_any(|_| search_worker(&matchers, deadline, flags, attempts).0)
+    })
+    .map(|(keypair, index)| VanityMatch {
+        keypair,
+        prefix: prefixes[index].clone(),
+    })
+}
+
+/// Generate a keypair with a vanity address that starts with (or, depending on
+/// `config.match_mode`, ends with or contains) the specified prefix.
+/// Setting `cancelled` (see `cancel_vanity_generation`) stops the search with
+/// `VanityError::Cancelled`.
+pub fn generate_vanity_keypair(
+    config: &VanityConfig,
+    cancelled: &Arc<AtomicBool>,
+) -> Result<Keypair, VanityError> {
+    generate_vanity_match(config, cancelled).map(|found| found.keypair)
+}
+
+/// Like `generate_vanity_keypair`, but accepts an address starting with any
+/// of `config.accepted_prefixes()` and reports which one it matched.
+pub fn generate_vanity_match(
+    config: &VanityConfig,
+    cancelled: &Arc<AtomicBool>,
+) -> Result<VanityMatch, VanityError> {
+    config.validate_suffix()?;
+    
+    // Clone all values needed by threads to avoid lifetime issues
+    let prefixes = config.accepted_prefixes();
+    let target = config.target_description();
+    let timeout_secs = config.timeout_seconds;
+    let thread_count = config.thread_count;
+    let progress_interval_ms = config.progress_interval_ms;
+    
+    let start_time = Instant::now();
+    let timeout = Duration::from_secs(timeout_secs);
+    
     // Shared state for tracking attempts and result
-    let attempts = Arc::new(Mutex::new(0u64));
-    let result = Arc::new(Mutex::new(None::<Keypair>));
+    let attempts = Arc::new(AtomicU64::new(0));
     let found = Arc::new(AtomicBool::new(false));
     
     // Progress reporting thread
     let attempts_clone = Arc::clone(&attempts);
     let found_clone = Arc::clone(&found);
-    let prefix_clone = prefix.clone();
+    let cancelled_clone = Arc::clone(cancelled);
+    let target_clone = target.clone();
     
     let progress_handle = thread::spawn(move || {
         let progress_interval = Duration::from_millis(progress_interval_ms);
         let mut last_report = Instant::now();
         let mut last_attempts = 0;
         
-        while !found_clone.load(Ordering::SeqCst) && start_time.elapsed() < timeout {
+        while !found_clone.load(Ordering::SeqCst) && 
+              !cancelled_clone.load(Ordering::SeqCst) && 
+              start_time.elapsed() < timeout {
             thread::sleep(progress_interval);
             
-            let current_attempts = *attempts_clone.lock().unwrap();
+            let current_attempts = attempts_clone.load(Ordering::Relaxed);
             let elapsed = last_report.elapsed();
             let attempts_since_last = current_attempts - last_attempts;
             let attempts_per_second = attempts_since_last as f64 / elapsed.as_secs_f64();
             
-            println!(
-                "Searching for vanity address with prefix '{}': {} attempts, {:.2} attempts/sec",
-                prefix_clone, current_attempts, attempts_per_second
+            eprintln!(
+                "Searching for vanity address with {}: {} attempts, {:.2} attempts/sec",
+                target_clone, current_attempts, attempts_per_second
             );
             
             last_attempts = current_attempts;
@@ -131,62 +515,50 @@ pub fn generate_vanity_keypair(config: &VanityConfig) -> Result<Keypair, VanityE
     });
     
     // Vanity address generation
-    pool.install(|| {
-        (0..thread_count).into_par_iter().try_for_each(|_| {
-            let attempts_ref = Arc::clone(&attempts);
-            let result_ref = Arc::clone(&result);
-            let found_ref = Arc::clone(&found);
-            let prefix_ref = prefix.clone();
-            
-            while !found_ref.load(Ordering::SeqCst) && start_time.elapsed() < timeout {
-                // Generate a new random keypair
-                let keypair = Keypair::new();
-                let pubkey = keypair.pubkey().to_string();
-                
-                // Increment attempt counter
-                {
-                    let mut attempts = attempts_ref.lock().unwrap();
-                    *attempts += 1;
-                }
-                
-                // Check if the address starts with the desired prefix
-                if pubkey.to_lowercase().starts_with(&prefix_ref) {
-                    // We found a match!
-                    let mut result = result_ref.lock().unwrap();
-                    *result = Some(keypair);
-                    
-                    found_ref.store(true, Ordering::SeqCst);
-                    
-                    return Err(());  // Break out of the parallel loop
-                }
-            }
-            
-            Ok(())
-        })
-        .ok();
-    });
+    let flags = SearchFlags { found: &found, cancelled, paused: &AtomicBool::new(false) };
+    let result = run_search(&prefixes, config.match_mode, &config.suffix, thread_count, start_time + timeout, flags, &attempts);
     
-    // Wait for progress thread to finish
+    // Stop and wait for progress thread to finish
+    found.store(true, Ordering::SeqCst);
     let _ = progress_handle.join();
     
     // Check if we found a keypair
-    let mut result_guard = result.lock().unwrap();
-    match result_guard.take() {
-        Some(keypair) => Ok(keypair),
+    match result {
+        Some(found) => Ok(found),
+        None if cancelled.load(Ordering::SeqCst) => Err(VanityError::Cancelled),
         None => Err(VanityError::Timeout),
     }
 }
 
-/// Generate a vanity keypair with progress updates
+/// Generate a vanity keypair with progress updates. Setting `cancelled` stops
+/// the search with `VanityError::Cancelled` after a final status update.
 pub fn generate_vanity_keypair_with_progress<F>(
     config: &VanityConfig,
+    cancelled: &Arc<AtomicBool>,
+    progress_callback: F,
+) -> Result<Keypair, VanityError>
+where
+    F: Fn(&VanityStatus) + Send + Sync + 'static,
+{
+    generate_vanity_keypair_pausable(config, cancelled, &Arc::new(AtomicBool::new(false)), progress_callback)
+}
+
+/// Like `generate_vanity_keypair_with_progress`, but the search stops while
+/// `paused` is set and picks up where it left off once it is cleared. Paused
+/// time counts neither towards the timeout nor the reported speed.
+pub fn generate_vanity_keypair_pausable<F>(
+    config: &VanityConfig,
+    cancelled: &Arc<AtomicBool>,
+    paused: &Arc<AtomicBool>,
     progress_callback: F,
 ) -> Result<Keypair, VanityError>
 where
     F: Fn(&VanityStatus) + Send + Sync + 'static,
 {
+    config.validate_suffix()?;
+    
     // Clone all values needed by threads to avoid lifetime issues
-    let prefix = config.prefix.to_lowercase();
+    let prefixes = config.accepted_prefixes();
     let timeout_secs = config.timeout_seconds;
     let thread_count = config.thread_count;
     let progress_interval_ms = config.progress_interval_ms;
@@ -195,10 +567,8 @@ where
     let timeout = Duration::from_secs(timeout_secs);
     
     // Shared state for tracking attempts and result
-    let attempts = Arc::new(Mutex::new(0u64));
-    let result = Arc::new(Mutex::new(None::<Keypair>));
+    let attempts = Arc::new(AtomicU64::new(0));
     let found = Arc::new(AtomicBool::new(false));
-    let cancelled = Arc::new(AtomicBool::new(false));
     
     // Create a shared callback that can be used in multiple threads
     let callback = Arc::new(progress_callback);
@@ -206,29 +576,40 @@ where
     // Progress reporting thread
     let attempts_clone = Arc::clone(&attempts);
     let found_clone = Arc::clone(&found);
-    let cancelled_clone = Arc::clone(&cancelled);
+    let cancelled_clone = Arc::clone(cancelled);
+    let paused_clone = Arc::clone(paused);
     let callback_clone = Arc::clone(&callback);
     
+    // Time spent paused, kept by the progress thread and left out of the elapsed time
+    let paused_ms = Arc::new(AtomicU64::new(0));
+    let paused_ms_clone = Arc::clone(&paused_ms);
+    
     let progress_handle = thread::spawn(move || {
         let progress_interval = Duration::from_millis(progress_interval_ms);
         let mut last_attempts = 0;
         let mut last_time = Instant::now();
+        let mut paused_time = Duration::ZERO;
         
         while !found_clone.load(Ordering::SeqCst) && 
               !cancelled_clone.load(Ordering::SeqCst) && 
-              start_time.elapsed() < timeout {
+              start_time.elapsed() < timeout + paused_time {
             thread::sleep(progress_interval);
             
-            let current_attempts = *attempts_clone.lock().unwrap();
-            let elapsed = start_time.elapsed();
+            let current_attempts = attempts_clone.load(Ordering::Relaxed);
+            let is_paused = paused_clone.load(Ordering::SeqCst);
+            if is_paused {
+                paused_time += last_time.elapsed();
+                paused_ms_clone.store(paused_time.as_millis() as u64, Ordering::SeqCst);
+            }
+            let elapsed = start_time.elapsed().saturating_sub(paused_time);
             let elapsed_seconds = elapsed.as_secs_f64();
             
             let time_diff = last_time.elapsed().as_secs_f64();
             let attempts_diff = current_attempts - last_attempts;
-            let attempts_per_second = if time_diff > 0.0 {
-                attempts_diff as f64 / time_diff
-            } else {
+            let attempts_per_second = if is_paused || time_diff <= 0.0 {
                 0.0
+            } else {
+                attempts_diff as f64 / time_diff
             };
             
             // Call the progress callback
@@ -237,6 +618,7 @@ where
                 completed: false,
                 success: false,
                 pubkey: None,
+                matched_prefix: None,
                 elapsed_seconds,
                 attempts_per_second,
             });
@@ -246,71 +628,21 @@ where
         }
     });
     
-    // Create a thread pool with the specified number of threads
-    let pool = rayon::ThreadPoolBuilder::new()
-        .num_threads(thread_count)
-        .build()
-        .unwrap();
-    
     // Vanity address generation
-    pool.install(|| {
-        (0..thread_count).into_par_iter().try_for_each(|_| {
-            let attempts_ref = Arc::clone(&attempts);
-            let result_ref = Arc::clone(&result);
-            let found_ref = Arc::clone(&found);
-            let cancelled_ref = Arc::clone(&cancelled);
-            let prefix_ref = prefix.clone();
-            
-            let mut counter = 0;
-            
-            while !found_ref.load(Ordering::SeqCst) && 
-                  !cancelled_ref.load(Ordering::SeqCst) && 
-                  start_time.elapsed() < timeout {
-                // Generate a new random keypair
-                let keypair = Keypair::new();
-                let pubkey = keypair.pubkey().to_string();
-                
-                // Increment attempt counter
-                {
-                    let mut attempts = attempts_ref.lock().unwrap();
-                    *attempts += 1;
-                }
-                
-                // Check if the address starts with the desired prefix
-                if pubkey.to_lowercase().starts_with(&prefix_ref) {
-                    // We found a match!
-                    let mut result = result_ref.lock().unwrap();
-                    *result = Some(keypair);
-                    
-                    found_ref.store(true, Ordering::SeqCst);
-                    
-                    return Err(());  // Break out of the parallel loop
-                }
-                
-                // Check for cancellation more frequently (every 100 attempts)
-                counter += 1;
-                if counter % 100 == 0 {
-                    if cancelled_ref.load(Ordering::SeqCst) {
-                        return Err(());  // Break out if cancelled
-                    }
-                    // Yield to allow other threads to run, especially important for cancellation
-                    std::thread::yield_now();
-                }
-            }
-            
-            Ok(())
-        })
-        .ok();
-    });
+    let flags = SearchFlags { found: &found, cancelled, paused };
+    let result = run_search(&prefixes, config.match_mode, &config.suffix, thread_count, start_time + timeout, flags, &attempts);
+    
+    // Stop the progress thread whether or not a match was found
+    found.store(true, Ordering::SeqCst);
     
     // Wait for progress thread to finish
     let _ = progress_handle.join();
     
-    // Check if the operation was cancelled first
-    if cancelled.load(Ordering::SeqCst) {
+    // A cancellation that raced a match still returns the match
+    if result.is_none() && cancelled.load(Ordering::SeqCst) {
         // Final progress update with cancellation
-        let total_attempts = *attempts.lock().unwrap();
-        let elapsed = start_time.elapsed();
+        let total_attempts = attempts.load(Ordering::Relaxed);
+        let elapsed = start_time.elapsed().saturating_sub(Duration::from_millis(paused_ms.load(Ordering::SeqCst)));
         let elapsed_seconds = elapsed.as_secs_f64();
         let attempts_per_second = if elapsed_seconds > 0.0 {
             total_attempts as f64 / elapsed_seconds
@@ -323,6 +655,7 @@ where
             completed: true,
             success: false,
             pubkey: None,
+            matched_prefix: None,
             elapsed_seconds,
             attempts_per_second,
         });
@@ -331,8 +664,8 @@ where
     }
     
     // Use the original callback reference for the final updates
-    let total_attempts = *attempts.lock().unwrap();
-    let elapsed = start_time.elapsed();
+    let total_attempts = attempts.load(Ordering::Relaxed);
+    let elapsed = start_time.elapsed().saturating_sub(Duration::from_millis(paused_ms.load(Ordering::SeqCst)));
     let elapsed_seconds = elapsed.as_secs_f64();
     let attempts_per_second = if elapsed_seconds > 0.0 {
         total_attempts as f64 / elapsed_seconds
@@ -340,15 +673,15 @@ where
         0.0
     };
     
-    let mut result_guard = result.lock().unwrap();
-    match result_guard.take() {
-        Some(keypair) => {
+    match result {
+        Some(VanityMatch { keypair, prefix }) => {
             // Final progress update with success
             callback(&VanityStatus {
                 attempts: total_attempts,
                 completed: true,
                 success: true,
                 pubkey: Some(keypair.pubkey().to_string()),
+                matched_prefix: Some(prefix),
                 elapsed_seconds,
                 attempts_per_second,
             });
@@ -362,6 +695,7 @@ where
                 completed: true,
                 success: false,
                 pubkey: None,
+                matched_prefix: None,
                 elapsed_seconds,
                 attempts_per_second,
             });
@@ -371,14 +705,225 @@ where
     }
 }
 
+// Number of base58 characters a prefix character matches, given that
+// prefix matching ignores case
+fn base58_matches(c: char) -> usize {
+    BASE58_ALPHABET
+        .chars()
+        .filter(|candidate| candidate.eq_ignore_ascii_case(&c))
+        .count()
+}
+
+/// Check that a prefix can actually occur in a base58 address. Matching is
+/// case-insensitive, so a character is accepted if either case is valid.
+pub fn validate_prefix(prefix: &str) -> Result<(), VanityError> {
+    if prefix.is_empty() {
+        return Err(VanityError::InvalidPrefix("prefix must not be empty".to_string()));
+    }
+    if let Some(c) = prefix.chars().find(|&c| base58_matches(c) == 0) {
+        return Err(VanityError::InvalidPrefix(format!(
+            "'{}' is not a base58 character",
+            c
+        )));
+    }
+    Ok(())
+}
+
+/// Expected number of attempts needed to find an address with the given prefix
+pub fn estimated_attempts(prefix: &str) -> f64 {
+    prefix
+        .chars()
+        .map(|c| BASE58_ALPHABET.len() as f64 / base58_matches(c).max(1) as f64)
+        .product()
+}
+
+/// Expected attempts to find an address that starts with `prefix` and ends
+/// with `suffix`. The two ends are independent, so their odds multiply: a
+/// two-character prefix plus a two-character suffix is as hard as a
+/// four-character prefix, far harder than either alone. Computed exactly in
+/// integers and saturating at `u64::MAX` for targets too long to count.
+pub fn estimate_difficulty_combined(prefix: &str, suffix: &str, case_sensitive: bool) -> u64 {
+    let mut numerator: u128 = 1;
+    let mut denominator: u128 = 1;
+    for c in prefix.chars().chain(suffix.chars()) {
+        let accepted = if case_sensitive {
+            usize::from(BASE58_ALPHABET.contains(c))
+        } else {
+            base58_matches(c)
+        };
+        // At most two characters are accepted, so the numerator overflows first
+        numerator = match numerator.checked_mul(BASE58_ALPHABET.len() as u128) {
+            Some(numerator) => numerator,
+            None => return u64::MAX,
+        };
+        denominator *= accepted.max(1) as u128;
+    }
+    u64::try_from(numerator / denominator).unwrap_or(u64::MAX)
+}
+
+/// Expected number of attempts needed to find an address with `target` in the
+/// position `mode` asks for. A suffix is as hard as a prefix of the same
+/// length, but "contains" is much easier: every one of the roughly
+/// `44 - len + 1` positions is another chance to match.
+pub fn estimated_attempts_for(target: &str, mode: Match) -> f64 {
+    let attempts = estimated_attempts(target);
+    match mode {
+        Match::Prefix | Match::Suffix => attempts,
+        Match::Contains => {
+            let positions = 44usize.saturating_sub(target.chars().count()) + 1;
+            (attempts / positions as f64).max(1.0)
+        },
+    }
+}
+
+/// Expected seconds to find `prefix` with `thread_count` threads at the
+/// estimated per-thread rate
+pub fn estimated_seconds(prefix: &str, thread_count: usize) -> f64 {
+    estimated_attempts(prefix) / (ESTIMATED_ATTEMPTS_PER_THREAD * thread_count.max(1) as f64)
+}
+
+/// Whether a search needing `expected_attempts` on average will probably run
+/// out of time, i.e. its expected duration exceeds `timeout_seconds`
+pub fn likely_to_time_out(expected_attempts: f64, attempts_per_second: f64, timeout_seconds: u64) -> bool {
+    if attempts_per_second <= 0.0 {
+        return true;
+    }
+    expected_attempts / attempts_per_second > timeout_seconds as f64
+}
+
+/// Renders a rough duration in the largest sensible unit
+pub fn format_estimated_duration(seconds: f64) -> String {
+    if seconds < 60.0 {
+        "less than a minute".to_string()
+    } else if seconds < 3600.0 {
+        format!("{:.0} minutes", seconds / 60.0)
+    } else if seconds < 86_400.0 {
+        format!("{:.0} hours", seconds / 3600.0)
+    } else if seconds < 31_536_000.0 {
+        format!("{:.0} days", seconds / 86_400.0)
+    } else {
+        format!("{:.0} years", seconds / 31_536_000.0)
+    }
+}
+
+/// Measure this machine's keypair generation rate by grinding for an
+/// unreachable prefix for `seconds` on `thread_count` threads. Returns the
+/// average attempts per second over the whole run.
+pub fn benchmark_attempts_per_second(seconds: u64, thread_count: usize) -> f64 {
+    let config = VanityConfig {
+        prefix: UNREACHABLE_PREFIX.to_string(),
+        prefixes: Vec::new(),
+        match_mode: Match::Prefix,
+        suffix: String::new(),
+        timeout_seconds: seconds,
+        thread_count: thread_count.max(1),
+        progress_interval_ms: 100,
+    };
+    
+    let rate = Arc::new(std::sync::Mutex::new(0.0));
+    let rate_clone = Arc::clone(&rate);
+    // The search always times out; only the final status matters
+    let _ = generate_vanity_keypair_with_progress(
+        &config,
+        &Arc::new(AtomicBool::new(false)),
+        move |status| {
+            if status.completed {
+                *rate_clone.lock().unwrap() = status.attempts_per_second;
+            }
+        }
+    );
+    
+    let rate = *rate.lock().unwrap();
+    rate
+}
+
+/// Worker threads for the `max_threads` setting on this machine: 0 picks
+/// one per core up to 8, anything else is capped at the core count.
+pub fn configured_thread_count(max_threads: usize) -> usize {
+    thread_count_for(max_threads, num_cpus::get())
+}
+
+fn thread_count_for(max_threads: usize, cores: usize) -> usize {
+    let cores = cores.max(1);
+    match max_threads {
+        0 => cores.min(AUTO_THREAD_LIMIT),
+        threads => threads.min(cores),
+    }
+}
+
 /// Cancel an ongoing vanity address generation process
 pub fn cancel_vanity_generation(cancelled: &Arc<AtomicBool>) {
     cancelled.store(true, Ordering::SeqCst);
 }
 
+/// Lifetime totals for one prefix, summed over every finished search
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PrefixStats {
+    /// Keypairs generated across all runs
+    pub attempts: u64,
+    /// Time spent searching across all runs, in seconds
+    pub seconds: f64,
+    /// Number of runs recorded
+    pub runs: u64,
+}
+
+/// Lifetime statistics keyed by lowercase prefix, since matching ignores case
+pub type VanityStats = HashMap<String, PrefixStats>;
+
+/// Location of the statistics file inside `data_dir`
+pub fn vanity_stats_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(VANITY_STATS_FILE_NAME)
+}
+
+/// Reads the statistics file at `path`; a missing file means no runs yet
+pub fn load_vanity_stats(path: &Path) -> io::Result<VanityStats> {
+    match std::fs::read(path) {
+        Ok(content) => serde_json::from_slice(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(VanityStats::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Adds a finished run to the totals for `prefix` and returns the new totals
+pub fn record_vanity_run(path: &Path, prefix: &str, attempts: u64, elapsed_seconds: f64) -> io::Result<PrefixStats> {
+    let mut stats = load_vanity_stats(path)?;
+    let entry = stats.entry(prefix.to_lowercase()).or_default();
+    entry.attempts = entry.attempts.saturating_add(attempts);
+    entry.seconds += elapsed_seconds.max(0.0);
+    entry.runs += 1;
+    let totals = entry.clone();
+    
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let content = serde_json::to_vec_pretty(&stats)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    std::fs::write(path, content)?;
+    Ok(totals)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Mutex;
+    
+    #[test]
+    fn test_format_estimated_duration() {
+        assert_eq!(format_estimated_duration(0.2), "less than a minute");
+        assert_eq!(format_estimated_duration(90.0), "2 minutes");
+        assert_eq!(format_estimated_duration(7200.0), "2 hours");
+        assert_eq!(format_estimated_duration(3.0 * 86_400.0), "3 days");
+        assert_eq!(format_estimated_duration(2.0 * 31_536_000.0), "2 years");
+    }
+    
+    #[test]
+    fn test_benchmark_reports_positive_rate() {
+        let started = Instant::now();
+        let rate = benchmark_attempts_per_second(1, 2);
+        assert!(rate > 0.0, "Benchmark should measure a positive rate, got {}", rate);
+        assert!(started.elapsed() < Duration::from_secs(5), "Benchmark should stop after its run time");
+    }
     
     #[test]
     fn test_vanity_generation_basic() {
@@ -387,9 +932,10 @@ mod tests {
             timeout_seconds: 10,
             thread_count: 2,
             progress_interval_ms: 100,
+            ..VanityConfig::default()
         };
         
-        let result = generate_vanity_keypair(&config);
+        let result = generate_vanity_keypair(&config, &Arc::new(AtomicBool::new(false)));
         assert!(result.is_ok(), "Should find an address starting with 'a'");
         
         let keypair = result.unwrap();
@@ -405,6 +951,7 @@ mod tests {
             timeout_seconds: 10,
             thread_count: 2,
             progress_interval_ms: 100,
+            ..VanityConfig::default()
         };
         
         let progress_updates = Arc::new(Mutex::new(Vec::new()));
@@ -412,6 +959,7 @@ mod tests {
         
         let result = generate_vanity_keypair_with_progress(
             &config,
+            &Arc::new(AtomicBool::new(false)),
             move |status| {
                 let mut updates = progress_updates_clone.lock().unwrap();
                 updates.push(status.attempts);
@@ -429,6 +977,117 @@ mod tests {
         assert!(!updates.is_empty(), "Should have received progress updates");
     }
     
+    #[test]
+    fn test_vanity_generation_accepts_any_of_several_prefixes() {
+        let config = VanityConfig {
+            prefixes: vec!["a".to_string(), "b".to_string()],
+            timeout_seconds: 10,
+            thread_count: 2,
+            progress_interval_ms: 100,
+            ..VanityConfig::default()
+        };
+        
+        let found = generate_vanity_match(&config, &Arc::new(AtomicBool::new(false))).unwrap();
+        assert!(found.prefix == "a" || found.prefix == "b");
+        assert!(found.keypair.pubkey().to_string().to_lowercase().starts_with(&found.prefix));
+        
+        // An unreachable prefix does not stop the reachable one from winning
+        let config = VanityConfig {
+            prefixes: vec!["impossible1234567890".to_string(), "c".to_string()],
+            ..config
+        };
+        let found = generate_vanity_match(&config, &Arc::new(AtomicBool::new(false))).unwrap();
+        assert_eq!(found.prefix, "c");
+        assert!(found.keypair.pubkey().to_string().to_lowercase().starts_with('c'));
+    }
+
+    #[test]
+    fn test_success_status_reports_matched_prefix() {
+        let config = VanityConfig {
+            prefixes: vec!["impossible1234567890".to_string(), "b".to_string()],
+            timeout_seconds: 10,
+            thread_count: 2,
+            progress_interval_ms: 100,
+            ..VanityConfig::default()
+        };
+
+        let final_status = Arc::new(Mutex::new(None));
+        let final_status_clone = Arc::clone(&final_status);
+        let keypair = generate_vanity_keypair_with_progress(
+            &config,
+            &Arc::new(AtomicBool::new(false)),
+            move |status| {
+                if status.completed {
+                    *final_status_clone.lock().unwrap() = Some(status.clone());
+                }
+            }
+        ).unwrap();
+
+        let status = final_status.lock().unwrap().clone().expect("Should report a final status");
+        assert!(status.success);
+        assert_eq!(status.matched_prefix.as_deref(), Some("b"));
+        assert_eq!(status.pubkey, Some(keypair.pubkey().to_string()));
+    }
+
+    #[test]
+    fn test_vanity_generation_match_modes() {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        for (mode, target) in [(Match::Prefix, "a"), (Match::Suffix, "a"), (Match::Contains, "ab")] {
+            let config = VanityConfig {
+                prefix: target.to_string(),
+                match_mode: mode,
+                timeout_seconds: 10,
+                thread_count: 2,
+                progress_interval_ms: 100,
+                ..VanityConfig::default()
+            };
+            let address = generate_vanity_keypair(&config, &cancelled).unwrap().pubkey().to_string().to_lowercase();
+            let matched = match mode {
+                Match::Prefix => address.starts_with(target),
+                Match::Suffix => address.ends_with(target),
+                Match::Contains => address.contains(target),
+            };
+            assert!(matched, "{} does not match '{}' as {:?}", address, target, mode);
+        }
+    }
+    
+    #[test]
+    fn test_matcher_modes_on_known_key() {
+        let key = Pubkey::new_unique();
+        let address = key.to_string();
+        let middle = &address[10..14];
+        let last = &address[address.len() - 3..];
+        
+        assert!(PrefixMatcher::new(last, Match::Suffix).matches(&key));
+        assert!(PrefixMatcher::new(&last.to_uppercase(), Match::Suffix).matches(&key));
+        assert!(PrefixMatcher::new(middle, Match::Contains).matches(&key));
+        assert!(PrefixMatcher::new(&address[..4], Match::Contains).matches(&key));
+        assert!(!PrefixMatcher::new("a0", Match::Contains).matches(&key));
+    }
+    
+    #[test]
+    fn test_contains_is_easier_than_prefix() {
+        let prefix = estimated_attempts_for("abcd", Match::Prefix);
+        assert_eq!(estimated_attempts_for("abcd", Match::Suffix), prefix);
+        assert!(estimated_attempts_for("abcd", Match::Contains) < prefix / 10.0);
+        assert!(estimated_attempts_for("a", Match::Contains) >= 1.0);
+    }
+    
+    #[test]
+    fn test_single_prefix_is_wrapped() {
+        let config = VanityConfig {
+            prefix: "sol".to_string(),
+            ..VanityConfig::default()
+        };
+        assert_eq!(config.accepted_prefixes(), vec!["sol".to_string()]);
+        
+        let config = VanityConfig {
+            prefixes: vec!["ai".to_string(), "gm".to_string()],
+            ..config
+        };
+        assert_eq!(config.accepted_prefixes(), vec!["ai".to_string(), "gm".to_string()]);
+    }
+    
     #[test]
     fn test_vanity_generation_timeout() {
         let config = VanityConfig {
@@ -437,9 +1096,10 @@ mod tests {
             timeout_seconds: 1,  // Short timeout
             thread_count: 1,
             progress_interval_ms: 100,
+            ..VanityConfig::default()
         };
         
-        let result = generate_vanity_keypair(&config);
+        let result = generate_vanity_keypair(&config, &Arc::new(AtomicBool::new(false)));
         assert!(matches!(result, Err(VanityError::Timeout)), 
                 "Should timeout when searching for an unlikely prefix");
     }
@@ -453,6 +1113,7 @@ mod tests {
             timeout_seconds: 30,  // Long timeout to ensure we don't hit it
             thread_count: 1,
             progress_interval_ms: 50,  // Faster progress updates
+            ..VanityConfig::default()
         };
         
         // Use AtomicBool for thread-safe cancellation
@@ -467,6 +1128,7 @@ mod tests {
         let handle = thread::spawn(move || {
             let result = generate_vanity_keypair_with_progress(
                 &config,
+                &cancelled,
                 move |status| {
                     // Check if this is a completion callback with cancelled status
                     if status.completed && !status.success {
@@ -497,6 +1159,82 @@ mod tests {
                 "Cancellation callback should have been triggered");
     }
     
+    #[test]
+    fn test_vanity_generation_pause_and_resume() {
+        let config = VanityConfig {
+            prefix: UNREACHABLE_PREFIX.to_string(),
+            timeout_seconds: 30,
+            thread_count: 2,
+            progress_interval_ms: 50,
+            ..VanityConfig::default()
+        };
+        let thread_count = config.thread_count as u64;
+        
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let paused = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = std::sync::mpsc::channel();
+        
+        let handle = {
+            let cancelled = Arc::clone(&cancelled);
+            let paused = Arc::clone(&paused);
+            thread::spawn(move || {
+                generate_vanity_keypair_pausable(&config, &cancelled, &paused, move |status| {
+                    let _ = tx.send((status.attempts, status.attempts_per_second));
+                })
+            })
+        };
+        let next_report = || rx.recv_timeout(Duration::from_secs(10)).expect("No progress report");
+        
+        // Wait for the search to get going
+        while next_report().0 == 0 {}
+        
+        // Reports queued or under way may have counted before the pause. Once
+        // they are skipped, the next one counts after it, and from then on each
+        // worker finishes at most the batch it was in.
+        paused.store(true, Ordering::SeqCst);
+        while rx.try_recv().is_ok() {}
+        next_report();
+        let (attempts_at_pause, rate_at_pause) = next_report();
+        assert_eq!(rate_at_pause, 0.0);
+        let limit = attempts_at_pause + thread_count * ATTEMPT_BATCH;
+        for _ in 0..5 {
+            let (attempts_while_paused, rate_while_paused) = next_report();
+            assert!(attempts_while_paused <= limit, "No attempts should be made while paused");
+            assert_eq!(rate_while_paused, 0.0);
+        }
+        
+        paused.store(false, Ordering::SeqCst);
+        while next_report().0 <= limit {}
+        
+        cancel_vanity_generation(&cancelled);
+        assert!(matches!(handle.join().unwrap(), Err(VanityError::Cancelled)));
+    }
+    
+    #[test]
+    fn test_vanity_generation_cancel_without_progress() {
+        let config = VanityConfig {
+            prefix: "impossible1234567890".to_string(),
+            timeout_seconds: 30,
+            thread_count: 2,
+            progress_interval_ms: 50,
+            ..VanityConfig::default()
+        };
+        
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let cancelled_clone = Arc::clone(&cancelled);
+        let started = Instant::now();
+        
+        let handle = thread::spawn(move || generate_vanity_keypair(&config, &cancelled_clone));
+        
+        thread::sleep(Duration::from_millis(200));
+        cancel_vanity_generation(&cancelled);
+        
+        let result = handle.join().unwrap();
+        assert!(matches!(result, Err(VanityError::Cancelled)),
+                "Should be cancelled when cancel_vanity_generation is called");
+        assert!(started.elapsed() < Duration::from_secs(5), "Cancellation should stop the search promptly");
+    }
+    
     #[test]
     fn test_vanity_generation_case_insensitive() {
         let config = VanityConfig {
@@ -504,9 +1242,10 @@ mod tests {
             timeout_seconds: 10,
             thread_count: 2,
             progress_interval_ms: 100,
+            ..VanityConfig::default()
         };
         
-        let result = generate_vanity_keypair(&config);
+        let result = generate_vanity_keypair(&config, &Arc::new(AtomicBool::new(false)));
         assert!(result.is_ok(), "Should find an address starting with 'a' or 'A'");
         
         let keypair = result.unwrap();
@@ -522,10 +1261,11 @@ mod tests {
             timeout_seconds: 2,
             thread_count: 4,  // Use multiple threads
             progress_interval_ms: 100,
+            ..VanityConfig::default()
         };
         
         let start = Instant::now();
-        let result = generate_vanity_keypair(&config);
+        let result = generate_vanity_keypair(&config, &Arc::new(AtomicBool::new(false)));
         let duration = start.elapsed();
         
         assert!(result.is_ok(), "Should find an address starting with 'a'");
@@ -535,4 +1275,235 @@ mod tests {
         assert!(duration < Duration::from_secs(5), 
                 "Should find common prefix quickly with multiple threads");
     }
+    
+    #[test]
+    fn test_attempt_counter_is_accurate() {
+        // Several workers searching for an impossible prefix for a short while
+        let attempts = AtomicU64::new(0);
+        let found = AtomicBool::new(false);
+        let cancelled = AtomicBool::new(false);
+        let paused = AtomicBool::new(false);
+        let flags = SearchFlags { found: &found, cancelled: &cancelled, paused: &paused };
+        let deadline = Instant::now() + Duration::from_millis(300);
+        let matcher = PrefixMatcher::new("impossible1234567890", Match::Prefix);
+        
+        let start = Instant::now();
+        let totals: Vec<u64> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| {
+                    search_worker(std::slice::from_ref(&matcher), deadline, flags, &attempts)
+                }))
+                .collect();
+            handles.into_iter()
+                .map(|handle| {
+                    let (keypair, total) = handle.join().unwrap();
+                    assert!(keypair.is_none());
+                    total
+                })
+                .collect()
+        });
+        
+        // Batched reporting must not lose or double count any attempt
+        let counted = attempts.load(Ordering::SeqCst);
+        assert_eq!(counted, totals.iter().sum::<u64>());
+        assert!(totals.iter().all(|&total| total > 0));
+        println!(
+            "{} attempts across 4 threads, {:.0} attempts/sec",
+            counted,
+            counted as f64 / start.elapsed().as_secs_f64()
+        );
+    }
+    
+    #[test]
+    fn test_prefix_matcher_agrees_with_encoding() {
+        let naive = |pubkey: &Pubkey, prefix: &str| {
+            pubkey.to_string().to_lowercase().starts_with(&prefix.to_lowercase())
+        };
+        let prefixes = ["a", "A", "1", "z", "So", "ai", "11", "4x", "9", "Lo", "ab1", ""];
+        let matchers: Vec<PrefixMatcher> = prefixes.iter().map(|p| PrefixMatcher::new(p, Match::Prefix)).collect();
+        
+        let mut checked_matches = 0;
+        for i in 0..20_000u32 {
+            // Mix in keys with leading zero bytes, which take the fallback path
+            let mut bytes = Pubkey::new_unique().to_bytes();
+            bytes = solana_sdk::hash::hashv(&[&bytes, &i.to_le_bytes()]).to_bytes();
+            if i % 50 == 0 {
+                bytes[0] = 0;
+            }
+            if i % 500 == 0 {
+                bytes[1] = 0;
+            }
+            let pubkey = Pubkey::new_from_array(bytes);
+            
+            for (prefix, matcher) in prefixes.iter().zip(&matchers) {
+                let expected = naive(&pubkey, prefix);
+                assert_eq!(matcher.matches(&pubkey), expected,
+                           "prefix '{}' disagrees for {}", prefix, pubkey);
+                if expected {
+                    checked_matches += 1;
+                }
+            }
+        }
+        assert!(checked_matches > 1000, "too few matching keys exercised");
+    }
+    
+    #[test]
+    fn test_prefix_matcher_extreme_prefixes() {
+        let key = Keypair::new().pubkey();
+        // The full address always matches itself, in any case
+        assert!(PrefixMatcher::new(&key.to_string(), Match::Prefix).matches(&key));
+        assert!(PrefixMatcher::new(&key.to_string().to_uppercase(), Match::Prefix).matches(&key));
+        // Longer than any address, or containing non-base58 characters
+        assert!(!PrefixMatcher::new(&"a".repeat(45), Match::Prefix).matches(&key));
+        assert!(!PrefixMatcher::new("a0", Match::Prefix).matches(&key));
+    }
+    
+    #[test]
+    fn test_validate_prefix() {
+        assert!(validate_prefix("ai").is_ok());
+        assert!(validate_prefix("Sol").is_ok());
+        // Excluded uppercase letters still match their valid lowercase forms
+        assert!(validate_prefix("OIl").is_ok());
+        
+        assert!(matches!(validate_prefix(""), Err(VanityError::InvalidPrefix(_))));
+        assert!(matches!(validate_prefix("a0"), Err(VanityError::InvalidPrefix(_))));
+        assert!(matches!(validate_prefix("a-b"), Err(VanityError::InvalidPrefix(_))));
+    }
+    
+    #[test]
+    fn test_estimated_attempts() {
+        assert_eq!(estimated_attempts(""), 1.0);
+        // Digits have a single match, letters usually two
+        assert_eq!(estimated_attempts("1"), 58.0);
+        assert_eq!(estimated_attempts("a"), 29.0);
+        assert_eq!(estimated_attempts("ab1"), 29.0 * 29.0 * 58.0);
+    }
+
+    #[test]
+    fn test_likely_to_time_out() {
+        // 58^2 attempts at 1000/s take about 3.4 seconds
+        let difficulty = estimated_attempts("11");
+        assert!(!likely_to_time_out(difficulty, 1000.0, 60));
+        assert!(likely_to_time_out(difficulty, 1000.0, 3));
+
+        // A six-character prefix outlasts a minute even on many threads
+        let rate = ESTIMATED_ATTEMPTS_PER_THREAD * 16.0;
+        assert!(likely_to_time_out(estimated_attempts("abcdef"), rate, 60));
+        assert!(!likely_to_time_out(estimated_attempts("ab"), rate, 60));
+
+        assert!(likely_to_time_out(difficulty, 0.0, 60));
+        assert!(estimated_seconds("ab", 2) < estimated_seconds("ab", 1));
+    }
+
+    #[test]
+    fn test_thread_count_for_max_threads() {
+        // 0 auto-detects, up to the limit
+        assert_eq!(thread_count_for(0, 4), 4);
+        assert_eq!(thread_count_for(0, 32), AUTO_THREAD_LIMIT);
+        // Explicit values are kept, but never exceed the cores
+        assert_eq!(thread_count_for(2, 4), 2);
+        assert_eq!(thread_count_for(16, 32), 16);
+        assert_eq!(thread_count_for(64, 4), 4);
+        assert_eq!(thread_count_for(3, 0), 1);
+
+        let auto = configured_thread_count(0);
+        assert!((1..=AUTO_THREAD_LIMIT).contains(&auto));
+    }
+    
+    #[test]
+    fn test_record_vanity_run_accumulates_totals() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = vanity_stats_path(&dir.path().join("data"));
+        assert!(load_vanity_stats(&path).unwrap().is_empty());
+        
+        record_vanity_run(&path, "ai", 1_000, 1.5).unwrap();
+        let totals = record_vanity_run(&path, "AI", 2_500, 2.0).unwrap();
+        assert_eq!(totals, PrefixStats { attempts: 3_500, seconds: 3.5, runs: 2 });
+        
+        record_vanity_run(&path, "xyz", 10, 0.1).unwrap();
+        let stats = load_vanity_stats(&path).unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats["ai"], totals);
+    }
+    
+    #[test]
+    fn test_estimate_difficulty_combined() {
+        // Without a suffix this is the prefix estimate
+        assert_eq!(estimate_difficulty_combined("ab1", "", false), estimated_attempts("ab1") as u64);
+        assert_eq!(estimate_difficulty_combined("", "", false), 1);
+        
+        // The two ends multiply, and case sensitivity removes the second accepted case
+        assert_eq!(estimate_difficulty_combined("ab", "cd", false), 29u64.pow(4));
+        assert_eq!(estimate_difficulty_combined("ab", "1", false), 29 * 29 * 58);
+        assert_eq!(estimate_difficulty_combined("ab", "cd", true), 58u64.pow(4));
+        assert_eq!(estimate_difficulty_combined("ab", "cd", false), estimate_difficulty_combined("abcd", "", false));
+        
+        let config = VanityConfig {
+            prefix: "ab".to_string(),
+            suffix: "cd".to_string(),
+            ..VanityConfig::default()
+        };
+        assert_eq!(config.expected_attempts(), 29f64.powi(4));
+        assert_eq!(config.target_description(), "prefix 'ab' and suffix 'cd'");
+        
+        // Either of two equally hard prefixes halves the prefix part
+        let either = VanityConfig {
+            prefixes: vec!["ab".to_string(), "ef".to_string()],
+            ..config.clone()
+        };
+        assert!((either.prefix_attempts() - 29f64.powi(2) / 2.0).abs() < 1e-6);
+        assert!((either.expected_attempts() - 29f64.powi(4) / 2.0).abs() < 1e-6);
+        
+        // A text anywhere in the address still multiplies with the suffix
+        let contains = VanityConfig {
+            match_mode: Match::Contains,
+            ..config.clone()
+        };
+        assert_eq!(contains.expected_attempts(), estimated_attempts_for("ab", Match::Contains) * 29f64.powi(2));
+    }
+    
+    #[test]
+    fn test_suffix_search_cannot_take_a_second_suffix() {
+        let config = VanityConfig {
+            prefix: "a".to_string(),
+            match_mode: Match::Suffix,
+            suffix: "b".to_string(),
+            ..VanityConfig::default()
+        };
+        assert!(matches!(config.validate_suffix(), Err(VanityError::InvalidPrefix(_))));
+        assert!(matches!(
+            generate_vanity_keypair(&config, &Arc::new(AtomicBool::new(false))),
+            Err(VanityError::InvalidPrefix(_))
+        ));
+        
+        // Without the suffix, or matching the prefix, it is fine
+        assert!(VanityConfig { suffix: String::new(), ..config.clone() }.validate_suffix().is_ok());
+        assert!(VanityConfig { match_mode: Match::Prefix, ..config.clone() }.validate_suffix().is_ok());
+        assert!(VanityConfig { match_mode: Match::Prefix, suffix: "0".to_string(), ..config }.validate_suffix().is_err());
+    }
+    
+    #[test]
+    fn test_estimate_difficulty_combined_saturates() {
+        // 58^10 fits, but 44 characters at each end are far beyond u64
+        assert_eq!(estimate_difficulty_combined("11111", "11111", true), 58u64.pow(10));
+        assert_eq!(estimate_difficulty_combined(&"z".repeat(44), &"z".repeat(44), false), u64::MAX);
+        assert_eq!(estimate_difficulty_combined(&"1".repeat(11), "", true), u64::MAX);
+    }
+    
+    #[test]
+    fn test_generation_matches_prefix_and_suffix() {
+        let config = VanityConfig {
+            prefix: "a".to_string(),
+            suffix: "b".to_string(),
+            timeout_seconds: 30,
+            thread_count: 2,
+            ..VanityConfig::default()
+        };
+        let address = generate_vanity_keypair(&config, &Arc::new(AtomicBool::new(false)))
+            .unwrap()
+            .pubkey()
+            .to_string()
+            .to_lowercase();
+        assert!(address.starts_with('a') && address.ends_with('b'), "{}", address);
+    }
 }
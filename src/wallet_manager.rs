@@ -1,111 +1,483 @@
 // wallet_manager.rs
 
+use crate::file_searcher::{self, SearchConfig};
 use crate::key_validator;
-use crate::secure_storage::{self, SecureStorageError};
+use crate::secure_storage;
+use serde::Serialize;
+use solana_derivation_path::DerivationPath;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::keypair::{
+    generate_seed_from_seed_phrase_and_passphrase, keypair_from_seed_and_derivation_path,
+};
+use solana_sdk::signer::Signer;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::io::{self, Error, ErrorKind};
-use std::path::Path; // To validate a key from a file before adding
+use std::fmt;
+use std::io::{self, Error, ErrorKind, Write};
+use std::path::{Path, PathBuf}; // To validate a key from a file before adding
+use zeroize::Zeroizing;
 
 // Solana keypair constants
 const KEYPAIR_BYTES: usize = 64; // Full keypair: 32 bytes secret + 32 bytes public
 const SECRET_KEY_BYTES: usize = 32; // Just the secret key portion
 
+/// Path of the first seed-phrase account, as used by most Solana wallets.
+/// `m/44'/501'/0'` (no change level, as `solana-keygen` uses) is also accepted.
+pub const DEFAULT_DERIVATION_PATH: &str = "m/44'/501'/0'/0'";
+
+/// Why a wallet operation failed. Converts into `io::Error` (keeping the
+/// matching `ErrorKind`) for callers that only report the message.
+#[derive(Debug)]
+pub enum WalletManagerError {
+    /// A key file or stored wallet that does not exist
+    NotFound(String),
+    /// The wallet name or key is already taken
+    AlreadyExists(String),
+    /// Key data that is not a usable Solana keypair
+    InvalidKey(String),
+    /// A rejected argument, such as an empty name or a bad seed phrase
+    InvalidInput(String),
+    /// The encrypted vault or keychain could not be used
+    Storage(secure_storage::SecureStorageError),
+    /// Reading or writing a file outside the vault failed
+    Io(io::Error),
+}
+
+impl WalletManagerError {
+    /// The `io::ErrorKind` this error converts to
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            WalletManagerError::NotFound(_) => ErrorKind::NotFound,
+            WalletManagerError::AlreadyExists(_) => ErrorKind::AlreadyExists,
+            WalletManagerError::InvalidKey(_) => ErrorKind::InvalidData,
+            WalletManagerError::InvalidInput(_) => ErrorKind::InvalidInput,
+            WalletManagerError::Storage(secure_storage::SecureStorageError::IoError(e)) => e.kind(),
+            WalletManagerError::Storage(_) => ErrorKind::Other,
+            WalletManagerError::Io(e) => e.kind(),
+        }
+    }
+}
+
+impl fmt::Display for WalletManagerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalletManagerError::NotFound(msg)
+            | WalletManagerError::AlreadyExists(msg)
+            | WalletManagerError::InvalidKey(msg)
+            | WalletManagerError::InvalidInput(msg) => write!(f, "{}", msg),
+            WalletManagerError::Storage(e) => write!(f, "{}", e),
+            WalletManagerError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for WalletManagerError {}
+
+impl From<io::Error> for WalletManagerError {
+    fn from(error: io::Error) -> Self {
+        WalletManagerError::Io(error)
+    }
+}
+
+impl From<secure_storage::SecureStorageError> for WalletManagerError {
+    fn from(error: secure_storage::SecureStorageError) -> Self {
+        match error {
+            secure_storage::SecureStorageError::AlreadyExists(msg) => WalletManagerError::AlreadyExists(msg),
+            secure_storage::SecureStorageError::NotFound(msg) => WalletManagerError::NotFound(msg),
+            _ => WalletManagerError::Storage(error),
+        }
+    }
+}
+
+impl From<WalletManagerError> for io::Error {
+    fn from(error: WalletManagerError) -> Self {
+        match error {
+            WalletManagerError::Io(e) => e,
+            WalletManagerError::Storage(e) => e.into(),
+            _ => Error::new(error.kind(), error.to_string()),
+        }
+    }
+}
+
 /// Adds a new wallet by reading a private key from a JSON file and storing it securely.
-/// The wallet will be stored under the given `wallet_name`.
-pub fn add_wallet_from_file(wallet_name: &str, key_file_path: &str) -> io::Result<()> {
-    println!(
+/// The file holds either the 64-byte keypair or just the 32-byte secret key,
+/// in which case the public half is derived and the full keypair stored.
+/// The wallet is stored under `wallet_name`, or under `wallet_name_2`,
+/// `wallet_name_3`, ... when that name is taken. Returns the name used.
+pub fn add_wallet_from_file(wallet_name: &str, key_file_path: &str) -> Result<String, WalletManagerError> {
+    eprintln!(
         "[wallet_manager] Attempting to add wallet 	{}	 from file: {}",
         wallet_name, key_file_path
     );
 
+    let keypair_bytes = read_keypair_file(key_file_path)?;
+    let wallet_name = add_wallet_under_unique_name(wallet_name, &keypair_bytes)?;
+    eprintln!(
+        "[wallet_manager] Wallet 	{}	 added successfully from {}.",
+        wallet_name, key_file_path
+    );
+    Ok(wallet_name)
+}
+
+/// Adds a new wallet from key JSON read from `reader` (e.g. stdin), with the
+/// same checks and naming as `add_wallet_from_file`. Lets scripts pipe a key
+/// in without writing it to a temporary file first. Returns the name used.
+pub fn add_wallet_from_reader(
+    wallet_name: &str,
+    reader: impl io::Read,
+) -> Result<String, WalletManagerError> {
+    let contents = Zeroizing::new(key_validator::read_key_data(reader)?);
+    let keypair_bytes = parse_keypair_json(&contents).map_err(|e| {
+        WalletManagerError::InvalidKey(format!("Input is not a valid Solana wallet key: {}.", e))
+    })?;
+    add_wallet_under_unique_name(wallet_name, &keypair_bytes)
+}
+
+/// Stores key bytes under `wallet_name`, or the first free `wallet_name_N`
+/// when that name is taken, and returns the name used. For keys that would
+/// be lost if the store failed, such as freshly found vanity addresses.
+pub fn add_wallet_under_unique_name(
+    wallet_name: &str,
+    keypair_bytes: &[u8; KEYPAIR_BYTES],
+) -> Result<String, WalletManagerError> {
+    let taken_names: HashSet<String> = secure_storage::list_wallet_names()?
+        .into_iter()
+        .collect();
+    let wallet_name = unique_wallet_name(wallet_name, &taken_names);
+    add_wallet_from_bytes(&wallet_name, keypair_bytes)?;
+    Ok(wallet_name)
+}
+
+/// Runs every check `add_wallet_from_file` makes on a key file and returns
+/// the public key it holds, without touching secure storage.
+pub fn validate_wallet_file(key_file_path: &str) -> Result<Pubkey, WalletManagerError> {
+    let keypair_bytes = read_keypair_file(key_file_path)?;
+    verified_pubkey(&keypair_bytes)
+}
+
+// Reads the 64-byte keypair of a key file, checking that the file exists and
+// holds a Solana key
+fn read_keypair_file(
+    key_file_path: &str,
+) -> Result<Zeroizing<[u8; KEYPAIR_BYTES]>, WalletManagerError> {
     if !Path::new(key_file_path).exists() {
-        return Err(Error::new(
-            ErrorKind::NotFound,
-            format!("Key file not found: {}", key_file_path),
+        return Err(WalletManagerError::NotFound(format!(
+            "Key file not found: {}",
+            key_file_path
+        )));
+    }
+
+    let contents = Zeroizing::new(key_validator::read_key_file(key_file_path).map_err(|e| {
+        let message = format!("Error validating key file {}: {}", key_file_path, e);
+        // Oversized or non-text files are bad keys rather than I/O failures
+        if e.kind() == ErrorKind::InvalidData {
+            WalletManagerError::InvalidKey(message)
+        } else {
+            WalletManagerError::Io(Error::new(e.kind(), message))
+        }
+    })?);
+    parse_keypair_json(&contents).map_err(|e| {
+        WalletManagerError::InvalidKey(format!(
+            "File 	{}	 is not a valid Solana wallet key file: {}.",
+            key_file_path, e
+        ))
+    })
+}
+
+// Extracts the 64-byte keypair from key JSON, failing with a description of
+// what the text holds instead when it is not a Solana key
+fn parse_keypair_json(contents: &str) -> Result<Zeroizing<[u8; KEYPAIR_BYTES]>, WalletManagerError> {
+    // A bare 32-byte secret key is accepted here, where the user named the
+    // file to import, but not when scanning for key files
+    if !key_validator::is_solana_wallet_json(contents) && !key_validator::is_secret_key_json(contents) {
+        return Err(WalletManagerError::InvalidKey(
+            key_validator::describe_key_json(contents),
         ));
     }
 
-    // 1. Validate if the file is a Solana wallet JSON
-    match key_validator::is_solana_wallet_json_file(key_file_path) {
-        Ok(true) => {
-            // 2. Read the raw key bytes from the file (assuming it's a JSON array of u8)
-            // The is_solana_wallet_json_file already does a good job of parsing and validating structure.
-            // We need to extract the actual key bytes here.
-            let contents = fs::read_to_string(key_file_path)?;
-            let parsed_json: serde_json::Value = serde_json::from_str(&contents).map_err(|e| {
-                Error::new(
-                    ErrorKind::InvalidData,
-                    format!("Failed to parse JSON: {}", e),
-                )
-            })?;
-
-            if let serde_json::Value::Array(arr) = parsed_json {
-                if arr.len() == 64 {
-                    let mut key_bytes: Vec<u8> = Vec::with_capacity(64);
-                    for val in arr {
-                        if let serde_json::Value::Number(num) = val {
-                            if let Some(byte_val) = num.as_u64() {
-                                if byte_val <= u8::MAX as u64 {
-                                    key_bytes.push(byte_val as u8);
-                                } else {
-                                    return Err(Error::new(
-                                        ErrorKind::InvalidData,
-                                        "Key byte value out of u8 range",
-                                    ));
-                                }
-                            } else {
-                                return Err(Error::new(
-                                    ErrorKind::InvalidData,
-                                    "Key byte value not a valid u64",
-                                ));
-                            }
-                        } else {
-                            return Err(Error::new(
-                                ErrorKind::InvalidData,
-                                "Key array element not a number",
-                            ));
-                        }
-                    }
-                    // 3. Store the validated key bytes securely
-                    secure_storage::store_private_key(wallet_name, &key_bytes)
-                        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
-                    println!(
-                        "[wallet_manager] Wallet 	{}	 added successfully from {}.",
-                        wallet_name, key_file_path
-                    );
-                    Ok(())
-                } else {
-                    Err(Error::new(
-                        ErrorKind::InvalidData,
-                        "JSON key array does not contain 64 elements.",
-                    ))
-                }
-            } else {
-                Err(Error::new(
-                    ErrorKind::InvalidData,
-                    "Validated JSON key is not an array as expected.",
-                ))
-            }
+    // The validator checked for an array of 64 or 32 byte-sized numbers
+    let bytes: Zeroizing<Vec<u8>> =
+        Zeroizing::new(serde_json::from_str(contents).map_err(|e| {
+            WalletManagerError::InvalidKey(format!("Failed to parse JSON: {}", e))
+        })?);
+    if bytes.len() == SECRET_KEY_BYTES {
+        // A bare secret key: expand it to the full keypair
+        let mut secret_key = Zeroizing::new([0u8; SECRET_KEY_BYTES]);
+        secret_key.copy_from_slice(&bytes);
+        let keypair = solana_sdk::signer::keypair::Keypair::new_from_array(*secret_key);
+        return Ok(Zeroizing::new(keypair.to_bytes()));
+    }
+    let mut keypair_bytes = Zeroizing::new([0u8; KEYPAIR_BYTES]);
+    keypair_bytes.copy_from_slice(&bytes);
+    Ok(keypair_bytes)
+}
+
+/// Stores an in-memory keypair under `wallet_name` without writing it to disk
+/// anywhere outside the encrypted vault. Used for keys generated in-process,
+/// such as vanity addresses.
+pub fn add_wallet_from_keypair(
+    wallet_name: &str,
+    keypair: &solana_sdk::signer::keypair::Keypair,
+) -> Result<(), WalletManagerError> {
+    add_wallet_from_bytes(wallet_name, &keypair.to_bytes())
+}
+
+/// Stores a 64-byte keypair (secret key followed by public key) under
+/// `wallet_name`. Every import path ends here, so key material only ever
+/// reaches disk inside the encrypted vault. Refuses a keypair whose public
+/// half does not match its secret half, one that is already stored, or a
+/// name that is already taken.
+pub fn add_wallet_from_bytes(
+    wallet_name: &str,
+    key_bytes: &[u8; KEYPAIR_BYTES],
+) -> Result<(), WalletManagerError> {
+    verified_pubkey(key_bytes)?;
+    secure_storage::store_new_private_key(wallet_name, key_bytes)?;
+    Ok(())
+}
+
+// The public key of a keypair, refusing one whose public half does not
+// match its secret half
+fn verified_pubkey(key_bytes: &[u8; KEYPAIR_BYTES]) -> Result<Pubkey, WalletManagerError> {
+    let pubkey = keypair_from_bytes(key_bytes)?.pubkey();
+    if pubkey.as_ref() != &key_bytes[SECRET_KEY_BYTES..] {
+        return Err(WalletManagerError::InvalidKey(
+            "Public key does not match the secret key.".to_string(),
+        ));
+    }
+    Ok(pubkey)
+}
+
+/// Derives `count` accounts from a BIP39 seed phrase and stores each one as
+/// `{prefix}_{account}`. `derivation_path` gives the first account's path
+/// (default `DEFAULT_DERIVATION_PATH`); later accounts increment its account
+/// level, keeping the change level if it has one. Accounts whose pubkey is
+/// already stored are skipped. Returns the names of the wallets that were created.
+pub fn add_wallets_from_seed(
+    prefix: &str,
+    phrase: &str,
+    count: usize,
+    derivation_path: Option<&str>,
+) -> Result<Vec<String>, WalletManagerError> {
+    eprintln!(
+        "[wallet_manager] Deriving {} wallet(s) from seed phrase with prefix {}",
+        count, prefix
+    );
+
+    if prefix.trim().is_empty() {
+        return Err(WalletManagerError::InvalidInput(
+            "Wallet name prefix must not be empty.".to_string(),
+        ));
+    }
+
+    let (first_account, change) = parse_derivation_path(derivation_path.unwrap_or(DEFAULT_DERIVATION_PATH))?;
+    let seed = seed_from_phrase(phrase)?;
+
+    // Derive everything up front so a name clash is reported before anything is stored
+    let existing_names = secure_storage::list_wallet_names()?;
+    let mut to_store = Vec::with_capacity(count);
+    for index in 0..count {
+        let account = u32::try_from(index)
+            .ok()
+            .and_then(|index| first_account.checked_add(index))
+            .filter(|&account| account < HARDENED_OFFSET)
+            .ok_or_else(|| WalletManagerError::InvalidInput("Account index out of range.".to_string()))?;
+        let keypair = derive_seed_account(&seed, account, change)?;
+
+        if let Some(existing_name) = find_wallet_by_pubkey(&keypair.pubkey())? {
+            eprintln!(
+                "Skipping account {} ({}): already stored as {}.",
+                account,
+                keypair.pubkey(),
+                existing_name
+            );
+            continue;
         }
-        Ok(false) => Err(Error::new(
-            ErrorKind::InvalidData,
-            format!(
-                "File 	{}	 is not a valid Solana wallet key file.",
-                key_file_path
-            ),
-        )),
-        Err(e) => Err(Error::new(
-            ErrorKind::Other,
-            format!("Error validating key file {}: {}", key_file_path, e),
+
+        let wallet_name = format!("{}_{}", prefix, account);
+        if existing_names.contains(&wallet_name) {
+            return Err(WalletManagerError::AlreadyExists(format!(
+                "A wallet named {} already exists.",
+                wallet_name
+            )));
+        }
+        to_store.push((wallet_name, keypair));
+    }
+
+    let mut created = Vec::with_capacity(to_store.len());
+    for (wallet_name, keypair) in to_store {
+        add_wallet_from_bytes(&wallet_name, &keypair.to_bytes())?;
+        created.push(wallet_name);
+    }
+
+    eprintln!("{} wallet(s) derived from seed phrase.", created.len());
+    Ok(created)
+}
+
+/// Derivation path of `account` in the layout of `DEFAULT_DERIVATION_PATH`
+pub fn seed_account_derivation_path(account: u32) -> String {
+    format!("m/44'/501'/{}'/0'", account)
+}
+
+/// Public key of `account` derived from a seed phrase along
+/// `seed_account_derivation_path`, without storing anything, so the address
+/// can be checked before it is imported.
+pub fn seed_account_pubkey(phrase: &str, account: u32) -> Result<Pubkey, WalletManagerError> {
+    if account >= HARDENED_OFFSET {
+        return Err(WalletManagerError::InvalidInput(
+            "Account index out of range.".to_string(),
+        ));
+    }
+    let seed = seed_from_phrase(phrase)?;
+    Ok(derive_seed_account(&seed, account, Some(0))?.pubkey())
+}
+
+// Checks a BIP39 English phrase, tolerating extra whitespace, and returns
+// its seed (with an empty passphrase)
+fn seed_from_phrase(phrase: &str) -> Result<Zeroizing<Vec<u8>>, WalletManagerError> {
+    let normalized_phrase = Zeroizing::new(phrase.split_whitespace().collect::<Vec<_>>().join(" "));
+    bip39::Mnemonic::parse_in_normalized(bip39::Language::English, &normalized_phrase)
+        .map_err(|e| WalletManagerError::InvalidInput(format!("Invalid seed phrase: {}", e)))?;
+    Ok(Zeroizing::new(generate_seed_from_seed_phrase_and_passphrase(&normalized_phrase, "")))
+}
+
+// The keypair at `m/44'/501'/<account>'[/<change>']` under `seed`
+fn derive_seed_account(
+    seed: &[u8],
+    account: u32,
+    change: Option<u32>,
+) -> Result<solana_sdk::signer::keypair::Keypair, WalletManagerError> {
+    let derivation_path = DerivationPath::new_bip44(Some(account), change);
+    keypair_from_seed_and_derivation_path(seed, Some(derivation_path))
+        .map_err(|e| WalletManagerError::InvalidKey(format!("Failed to derive account {}: {}", account, e)))
+}
+
+// BIP32 indexes at or above this are hardened; every Solana level is
+const HARDENED_OFFSET: u32 = 1 << 31;
+
+// Splits a Solana BIP44 path (`m/44'/501'/<account>'` or
+// `m/44'/501'/<account>'/<change>'`) into its account and change indexes.
+// Every level is hardened, whether or not it is written with a `'`.
+fn parse_derivation_path(path: &str) -> Result<(u32, Option<u32>), WalletManagerError> {
+    let invalid = |detail: String| {
+        WalletManagerError::InvalidInput(format!("Invalid derivation path {}: {}", path, detail))
+    };
+    let parsed = DerivationPath::from_absolute_path_str(path.trim())
+        .map_err(|e| invalid(e.to_string()))?;
+    let indexes: Vec<u32> = parsed.path().iter().map(|index| index.to_u32()).collect();
+    match indexes.as_slice() {
+        [44, 501, account] => Ok((*account, None)),
+        [44, 501, account, change] => Ok((*account, Some(*change))),
+        _ => Err(invalid(
+            "expected m/44'/501'/<account>' or m/44'/501'/<account>'/<change>'".to_string(),
         )),
     }
 }
 
+/// Per key file found by `import_wallets_from_dir`: its path and whether it
+/// was imported, or why not
+pub type ImportResults = Vec<(String, Result<(), String>)>;
+
+/// Finds the Solana key files under `dir_path` and imports each one as
+/// `{name_prefix}{file stem}`, adding `_2`, `_3`, ... when that name is taken.
+/// Files whose key is already stored are skipped. Returns one entry per key
+/// file found: its path and whether it was imported, or why not.
+pub fn import_wallets_from_dir(
+    dir_path: &str,
+    name_prefix: &str,
+    config: &SearchConfig,
+) -> Result<ImportResults, WalletManagerError> {
+    eprintln!(
+        "[wallet_manager] Importing wallets from {} with prefix {}",
+        dir_path, name_prefix
+    );
+
+    let mut key_files = file_searcher::search_solana_wallet_files(dir_path, config)?;
+    key_files.sort(); // Parallel search order varies; keep suffixes stable
+
+    let mut results = Vec::with_capacity(key_files.len());
+    for key_file in key_files {
+        let stem = key_validator::key_file_stem(&key_file).unwrap_or_else(|| "wallet".to_string());
+        let result = add_wallet_from_file(&format!("{}{}", name_prefix, stem), &key_file)
+            .map(|_| ())
+            .map_err(|e| e.to_string());
+        results.push((key_file, result));
+    }
+
+    let imported = results.iter().filter(|(_, result)| result.is_ok()).count();
+    eprintln!("{} of {} wallet file(s) imported.", imported, results.len());
+    Ok(results)
+}
+
+/// Directory where the Solana CLI keeps `id.json` and other keypairs:
+/// `$HOME/.config/solana`.
+pub fn solana_cli_config_dir() -> Result<PathBuf, WalletManagerError> {
+    std::env::var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+        .or_else(dirs::home_dir)
+        .map(|home| home.join(".config").join("solana"))
+        .ok_or_else(|| WalletManagerError::NotFound("Home directory not found".to_string()))
+}
+
+/// Imports the keypairs of the Solana CLI config directory, each named
+/// after its file (`id.json` becomes `id`). Returns the names they were
+/// stored under; files that are not keys or are already stored are skipped.
+pub fn import_solana_cli_keypairs() -> Result<Vec<String>, WalletManagerError> {
+    import_solana_cli_keypairs_from(&solana_cli_config_dir()?)
+}
+
+// Imports the key files directly inside `dir`, as the Solana CLI lays it out
+fn import_solana_cli_keypairs_from(dir: &Path) -> Result<Vec<String>, WalletManagerError> {
+    let entries = fs::read_dir(dir).map_err(|e| {
+        Error::new(
+            e.kind(),
+            format!("Cannot read Solana CLI directory {}: {}", dir.display(), e),
+        )
+    })?;
+    let mut key_files: Vec<String> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .map(|path| path.to_string_lossy().into_owned())
+        .filter(|path| matches!(key_validator::is_solana_wallet_json_file(path), Ok(true)))
+        .collect();
+    key_files.sort(); // read_dir order varies; keep suffixes stable
+
+    let mut imported = Vec::with_capacity(key_files.len());
+    for key_file in &key_files {
+        let stem = key_validator::key_file_stem(key_file).unwrap_or_else(|| "wallet".to_string());
+        match add_wallet_from_file(&stem, key_file) {
+            Ok(name) => imported.push(name),
+            Err(e) => eprintln!("Skipped {}: {}", key_file, e),
+        }
+    }
+
+    eprintln!(
+        "{} of {} Solana CLI keypair(s) imported from {}.",
+        imported.len(),
+        key_files.len(),
+        dir.display()
+    );
+    Ok(imported)
+}
+
+// Returns `base`, or `base_2`, `base_3`, ... for the first name not in `taken`
+fn unique_wallet_name(base: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|suffix| format!("{}_{}", base, suffix))
+        .find(|name| !taken.contains(name))
+        .expect("an unused suffix always exists")
+}
+
 /// Lists the names of all securely stored wallets.
-pub fn list_wallets() -> io::Result<()> {
-    println!("[wallet_manager] Listing all stored wallets...");
+pub fn list_wallets() -> Result<(), WalletManagerError> {
+    eprintln!("[wallet_manager] Listing all stored wallets...");
     match secure_storage::list_wallet_names()
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))
+        .map_err(WalletManagerError::from)
     {
         Ok(names) => {
             if names.is_empty() {
@@ -126,27 +498,26 @@ pub fn list_wallets() -> io::Result<()> {
 }
 
 /// Removes a wallet with the given `wallet_name` from secure storage.
-pub fn remove_wallet(wallet_name: &str) -> io::Result<()> {
-    println!(
+pub fn remove_wallet(wallet_name: &str) -> Result<(), WalletManagerError> {
+    eprintln!(
         "[wallet_manager] Attempting to remove wallet: {}",
         wallet_name
     );
     // First, check if wallet exists to provide better feedback
-    let wallets = secure_storage::list_wallet_names()
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+    let wallets = secure_storage::list_wallet_names()?;
     if !wallets.contains(&wallet_name.to_string()) {
-        println!("Wallet 	{}	 not found.", wallet_name);
-        return Err(Error::new(
-            ErrorKind::NotFound,
-            format!("Wallet 	{}	 not found.", wallet_name),
-        ));
+        eprintln!("Wallet 	{}	 not found.", wallet_name);
+        return Err(WalletManagerError::NotFound(format!(
+            "Wallet 	{}	 not found.",
+            wallet_name
+        )));
     }
 
     match secure_storage::remove_private_key(wallet_name)
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))
+        .map_err(WalletManagerError::from)
     {
         Ok(_) => {
-            println!("Wallet 	{}	 removed successfully.", wallet_name);
+            eprintln!("Wallet 	{}	 removed successfully.", wallet_name);
             Ok(())
         }
         Err(e) => {
@@ -156,40 +527,220 @@ pub fn remove_wallet(wallet_name: &str) -> io::Result<()> {
     }
 }
 
+/// Renames a stored wallet, keeping its key bytes unchanged.
+pub fn rename_wallet(old_name: &str, new_name: &str) -> Result<(), WalletManagerError> {
+    eprintln!(
+        "[wallet_manager] Attempting to rename wallet {} to {}",
+        old_name, new_name
+    );
+
+    if new_name.trim().is_empty() {
+        return Err(WalletManagerError::InvalidInput(
+            "New wallet name must not be empty.".to_string(),
+        ));
+    }
+
+    secure_storage::rename_private_key(old_name, new_name)?;
+    eprintln!(
+        "Wallet {} renamed to {} successfully.",
+        old_name, new_name
+    );
+    Ok(())
+}
+
+/// Writes the key bytes of a stored wallet to `dest_path` as the 64-number JSON array
+/// the Solana CLI expects. An existing file is only replaced when `overwrite` is set.
+pub fn export_wallet_to_file(
+    wallet_name: &str,
+    dest_path: &str,
+    overwrite: bool,
+) -> Result<(), WalletManagerError> {
+    eprintln!(
+        "[wallet_manager] Exporting wallet {} to {}",
+        wallet_name, dest_path
+    );
+
+    let key_bytes = secure_storage::retrieve_private_key(wallet_name)?
+        .ok_or_else(|| WalletManagerError::NotFound(format!("Wallet {} not found.", wallet_name)))?;
+
+    let json = serde_json::to_string(&*key_bytes)
+        .map(Zeroizing::new)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true);
+    if overwrite {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(dest_path).map_err(|e| {
+        if e.kind() == ErrorKind::AlreadyExists {
+            WalletManagerError::AlreadyExists(format!(
+                "Refusing to overwrite existing file {}",
+                dest_path
+            ))
+        } else {
+            WalletManagerError::Io(e)
+        }
+    })?;
+
+    // The mode above only applies to newly created files
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+
+    file.write_all(json.as_bytes())?;
+    file.sync_all()?;
+    eprintln!("Wallet {} exported to {}.", wallet_name, dest_path);
+    Ok(())
+}
+
+// One entry of the shareable address book: public information only
+#[derive(Serialize)]
+struct AddressBookEntry {
+    name: String,
+    pubkey: String,
+    label: Option<String>,
+}
+
+/// Writes a JSON array of `{name, pubkey, label}` for every stored wallet to
+/// `dest_path`. Only public keys are derived and written, never key bytes,
+/// so the file is safe to share.
+pub fn export_address_book(dest_path: &Path) -> Result<(), WalletManagerError> {
+    let records = secure_storage::retrieve_all_wallet_records()?;
+    let mut names: Vec<&String> = records.keys().collect();
+    names.sort();
+
+    let mut entries = Vec::with_capacity(names.len());
+    for name in names {
+        let record = &records[name];
+        entries.push(AddressBookEntry {
+            name: name.clone(),
+            pubkey: record_pubkey(record)?.to_string(),
+            label: record.label.clone(),
+        });
+    }
+
+    let json = serde_json::to_string_pretty(&entries)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+    fs::write(dest_path, json)?;
+    eprintln!(
+        "Address book with {} wallet(s) written to {}.",
+        entries.len(),
+        dest_path.display()
+    );
+    Ok(())
+}
+
+/// Returns the name of the stored wallet whose public key matches `pubkey`, if any.
+/// When the key is stored under several names, the oldest one is returned.
+pub fn find_wallet_by_pubkey(pubkey: &Pubkey) -> Result<Option<String>, WalletManagerError> {
+    let records = secure_storage::retrieve_all_wallet_records()?;
+    let mut found: Option<(i64, u64, &String)> = None;
+    for (name, record) in &records {
+        if record_pubkey(record)? != *pubkey {
+            continue;
+        }
+        let candidate = (record.created_at, record.sequence, name);
+        if found.is_none_or(|current| candidate < current) {
+            found = Some(candidate);
+        }
+    }
+    Ok(found.map(|(_, _, name)| name.clone()))
+}
+
+/// Public key of a stored wallet, taken from its record without rebuilding
+/// the keypair. `None` when there is no wallet of that name.
+pub fn get_wallet_pubkey(wallet_name: &str) -> Result<Option<Pubkey>, WalletManagerError> {
+    match secure_storage::retrieve_wallet_record(wallet_name)? {
+        Some(record) => Ok(Some(record_pubkey(&record)?)),
+        None => Ok(None),
+    }
+}
+
+/// The public key of `record`: the cached one, or derived from the secret
+/// half of the key bytes when the record has none. The cache is derived
+/// again whenever the vault is loaded or imported, so a record read from
+/// disk never carries an address its key does not own.
+pub fn record_pubkey(record: &secure_storage::WalletRecord) -> Result<Pubkey, WalletManagerError> {
+    match record.pubkey.as_deref().map(str::parse::<Pubkey>) {
+        Some(Ok(pubkey)) => Ok(pubkey),
+        _ => Ok(keypair_from_bytes(&record.key_bytes)?.pubkey()),
+    }
+}
+
+/// Groups stored wallets by public key and returns each key that is stored
+/// under more than one name. Names are ordered oldest import first, so the
+/// first one is the name to keep; imports in the same second go by the order
+/// they were added, and only records too old to have one by name.
+pub fn find_duplicate_wallets() -> Result<Vec<(Pubkey, Vec<String>)>, WalletManagerError> {
+    let records = secure_storage::retrieve_all_wallet_records()?;
+    let mut by_pubkey: HashMap<Pubkey, Vec<(i64, u64, String)>> = HashMap::new();
+    for (name, record) in &records {
+        let pubkey = record_pubkey(record)?;
+        by_pubkey
+            .entry(pubkey)
+            .or_default()
+            .push((record.created_at, record.sequence, name.clone()));
+    }
+
+    let mut duplicates: Vec<(Pubkey, Vec<String>)> = by_pubkey
+        .into_iter()
+        .filter(|(_, names)| names.len() > 1)
+        .map(|(pubkey, mut names)| {
+            names.sort();
+            (pubkey, names.into_iter().map(|(_, _, name)| name).collect())
+        })
+        .collect();
+    duplicates.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(duplicates)
+}
+
 // Placeholder for future use, e.g., by transaction module
 pub fn get_wallet_keypair(
     wallet_name: &str,
-) -> io::Result<Option<solana_sdk::signer::keypair::Keypair>> {
-    match secure_storage::retrieve_private_key(wallet_name)
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?
+) -> Result<Option<solana_sdk::signer::keypair::Keypair>, WalletManagerError> {
+    match secure_storage::retrieve_private_key(wallet_name)?
     {
-        Some(key_bytes) => {
-            // new_from_array expects only the 32-byte secret key, not the full 64-byte keypair
-            // Convert Vec<u8> to [u8; 32] array
-            if key_bytes.len() != KEYPAIR_BYTES {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!("Invalid key length: expected {} bytes, got {}", KEYPAIR_BYTES, key_bytes.len())
-                ));
-            }
-            let mut secret_key = [0u8; SECRET_KEY_BYTES];
-            secret_key.copy_from_slice(&key_bytes[0..SECRET_KEY_BYTES]);
-            let keypair = solana_sdk::signer::keypair::Keypair::new_from_array(secret_key);
-            Ok(Some(keypair))
-        }
+        Some(key_bytes) => Ok(Some(keypair_from_bytes(&key_bytes)?)),
         None => Ok(None),
     }
 }
 
+// Rebuilds a keypair from the stored 64-byte representation
+fn keypair_from_bytes(
+    key_bytes: &[u8],
+) -> Result<solana_sdk::signer::keypair::Keypair, WalletManagerError> {
+    // new_from_array expects only the 32-byte secret key, not the full 64-byte keypair
+    // Convert the slice to a [u8; 32] array
+    if key_bytes.len() != KEYPAIR_BYTES {
+        return Err(WalletManagerError::InvalidKey(format!(
+            "Invalid key length: expected {} bytes, got {}",
+            KEYPAIR_BYTES,
+            key_bytes.len()
+        )));
+    }
+    let mut secret_key = Zeroizing::new([0u8; SECRET_KEY_BYTES]);
+    secret_key.copy_from_slice(&key_bytes[0..SECRET_KEY_BYTES]);
+    Ok(solana_sdk::signer::keypair::Keypair::new_from_array(*secret_key))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::secure_storage; // For direct interaction for setup/teardown if needed
+    use crate::secure_storage::test_env::{setup_test_env, teardown_test_env};
+    use crate::secure_storage;
     use solana_sdk::signer::keypair::Keypair;
-    use solana_sdk::signer::Signer;
-    use std::env;
     use std::fs::File;
-    use std::io::Write;
     use tempfile::tempdir;
 
     // Helper to create a dummy Solana key JSON file
@@ -218,82 +769,10 @@ mod tests {
         file_path.to_str().unwrap().to_string()
     }
 
-    fn setup_test_env() -> (tempfile::TempDir, String) {
-        let temp_dir = tempdir().unwrap();
-        let temp_dir_path = temp_dir.path().to_str().unwrap();
-
-        // Use a unique timestamp for this test run
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis();
-
-        // Set a unique test service name for keychain isolation
-        let test_service_name = format!("svmai_test_{}", timestamp);
-        println!(
-            "[test_debug] Setting test service name: {}",
-            &test_service_name
-        );
-        env::set_var("SVMAI_TEST_SERVICE_NAME", &test_service_name);
-
-        // Create a unique config file path within the temp directory
-        let test_config_path = temp_dir
-            .path()
-            .join(format!(".svmai_wallets_{}.json", timestamp));
-        let test_config_path_str = test_config_path.to_str().unwrap();
-        println!(
-            "[test_debug] Setting test config path: {}",
-            test_config_path_str
-        );
-        env::set_var("SVMAI_TEST_CONFIG_PATH", test_config_path_str);
-
-        // Set HOME to the temp directory
-        env::set_var("HOME", temp_dir_path);
-
-        // Clear any existing test key from keychain for a clean run
-        if let Ok(entry) = keyring::Entry::new(
-            &test_service_name,
-            secure_storage::KEYCHAIN_MASTER_KEY_ACCOUNT_NAME,
-        ) {
-            let _ = entry.delete_credential();
-            println!("[test_debug] Cleared existing keychain entry");
-        }
-
-        (temp_dir, test_service_name)
-    }
-
-    fn teardown_test_env(temp_dir: tempfile::TempDir, test_service_name: &str) {
-        println!("[test_debug] Tearing down test environment");
-
-        // Clean up the keychain entry with the test-specific service name
-        if let Ok(entry) = keyring::Entry::new(
-            test_service_name,
-            secure_storage::KEYCHAIN_MASTER_KEY_ACCOUNT_NAME,
-        ) {
-            let _ = entry.delete_credential();
-            println!("[test_debug] Deleted test keychain entry");
-        }
-
-        // Clean up environment variables
-        env::remove_var("SVMAI_TEST_SERVICE_NAME");
-        env::remove_var("SVMAI_TEST_CONFIG_PATH");
-        env::remove_var("HOME");
-
-        // Clean up config file
-        if let Ok(config_path) = secure_storage::get_config_path() {
-            let _ = fs::remove_file(&config_path);
-            println!("[test_debug] Removed config file: {:?}", config_path);
-        }
-
-        // Drop temp directory
-        drop(temp_dir);
-        println!("[test_debug] Test environment cleanup complete");
-    }
-
     #[test]
     fn test_add_list_remove_wallet() {
         // Setup with unique environment
-        let (temp_dir, test_service_name) = setup_test_env();
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
 
         // Create test wallet
         let wallet1_name = "test_wallet1";
@@ -355,16 +834,217 @@ mod tests {
         teardown_test_env(temp_dir, &test_service_name);
     }
 
+    #[test]
+    fn test_export_address_book_has_no_key_material() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+
+        let keypair = Keypair::new();
+        let key_file = create_dummy_key_file(&temp_dir, "book.json", Some(&keypair));
+        add_wallet_from_file("book_wallet", &key_file).unwrap();
+
+        let dest = temp_dir.path().join("addresses.json");
+        export_address_book(&dest).unwrap();
+        let content = fs::read_to_string(&dest).unwrap();
+        assert!(content.contains("book_wallet"));
+        assert!(content.contains(&keypair.pubkey().to_string()));
+
+        // No array anywhere in the file may look like a keypair
+        fn has_key_array(value: &serde_json::Value) -> bool {
+            match value {
+                serde_json::Value::Array(items) => {
+                    items.len() == KEYPAIR_BYTES || items.iter().any(has_key_array)
+                }
+                serde_json::Value::Object(fields) => fields.values().any(has_key_array),
+                _ => false,
+            }
+        }
+        let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(parsed.as_array().map(Vec::len), Some(1));
+        assert!(!has_key_array(&parsed));
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_import_wallets_from_dir_deduplicates_names() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+        let scan_dir = tempdir().unwrap();
+        fs::create_dir(scan_dir.path().join("a")).unwrap();
+        fs::create_dir(scan_dir.path().join("b")).unwrap();
+
+        // Two files share a stem, and a wallet with that name already exists
+        let existing = Keypair::new();
+        let existing_file = create_dummy_key_file(&temp_dir, "existing.json", Some(&existing));
+        add_wallet_from_file("imp_id", &existing_file).unwrap();
+        let first = Keypair::new();
+        let second = Keypair::new();
+        fs::write(scan_dir.path().join("a/id.json"), serde_json::to_string(&first.to_bytes().to_vec()).unwrap()).unwrap();
+        fs::write(scan_dir.path().join("b/id.json"), serde_json::to_string(&second.to_bytes().to_vec()).unwrap()).unwrap();
+
+        let results = import_wallets_from_dir(
+            scan_dir.path().to_str().unwrap(),
+            "imp_",
+            &SearchConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+
+        let first_name = find_wallet_by_pubkey(&first.pubkey()).unwrap();
+        let second_name = find_wallet_by_pubkey(&second.pubkey()).unwrap();
+        assert_eq!(first_name.as_deref(), Some("imp_id_2"));
+        assert_eq!(second_name.as_deref(), Some("imp_id_3"));
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_validate_wallet_file() {
+        let temp_dir = tempdir().unwrap();
+        let keypair = Keypair::new();
+        let valid = create_dummy_key_file(&temp_dir, "valid.json", Some(&keypair));
+        assert_eq!(validate_wallet_file(&valid).unwrap(), keypair.pubkey());
+
+        // The public half of all-zero bytes does not match the secret half
+        let mismatched = create_dummy_key_file(&temp_dir, "mismatched.json", None);
+        assert_eq!(validate_wallet_file(&mismatched).unwrap_err().kind(), ErrorKind::InvalidData);
+
+        let not_a_key = temp_dir.path().join("not_a_key.json");
+        fs::write(&not_a_key, "[1, 2, 3]").unwrap();
+        assert_eq!(validate_wallet_file(not_a_key.to_str().unwrap()).unwrap_err().kind(), ErrorKind::InvalidData);
+
+        let missing = temp_dir.path().join("missing.json");
+        assert_eq!(validate_wallet_file(missing.to_str().unwrap()).unwrap_err().kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_error_variants_tell_missing_from_invalid_files() {
+        let temp_dir = tempdir().unwrap();
+        let missing = temp_dir.path().join("missing.json");
+        let err = add_wallet_from_file("missing", missing.to_str().unwrap()).unwrap_err();
+        assert!(matches!(err, WalletManagerError::NotFound(_)), "{:?}", err);
+
+        let not_a_key = temp_dir.path().join("config.json");
+        fs::write(&not_a_key, r#"{"rpc": "http://localhost"}"#).unwrap();
+        let err = add_wallet_from_file("config", not_a_key.to_str().unwrap()).unwrap_err();
+        assert!(matches!(err, WalletManagerError::InvalidKey(_)), "{:?}", err);
+
+        // Converting for io::Result callers keeps the kind and the message
+        let message = err.to_string();
+        let io_error = io::Error::from(err);
+        assert_eq!(io_error.kind(), ErrorKind::InvalidData);
+        assert_eq!(io_error.to_string(), message);
+    }
+
+    #[test]
+    fn test_import_solana_cli_keypairs() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+        let solana_dir = tempdir().unwrap();
+        fs::create_dir(solana_dir.path().join("cli")).unwrap();
+        fs::write(solana_dir.path().join("cli/config.yml"), "json_rpc_url: http://localhost\n").unwrap();
+        fs::write(solana_dir.path().join("notes.json"), "{}").unwrap();
+        let id = Keypair::new();
+        let devnet = Keypair::new();
+        fs::write(solana_dir.path().join("id.json"), serde_json::to_string(&id.to_bytes().to_vec()).unwrap()).unwrap();
+        fs::write(solana_dir.path().join("devnet.json"), serde_json::to_string(&devnet.to_bytes().to_vec()).unwrap()).unwrap();
+        fs::write(solana_dir.path().join("hash.json"), serde_json::to_string(&[7u8; 32]).unwrap()).unwrap();
+
+        // Only the keypairs are imported, named after their files
+        let imported = import_solana_cli_keypairs_from(solana_dir.path()).unwrap();
+        assert_eq!(imported, vec!["devnet".to_string(), "id".to_string()]);
+        assert_eq!(find_wallet_by_pubkey(&id.pubkey()).unwrap().as_deref(), Some("id"));
+        assert_eq!(find_wallet_by_pubkey(&devnet.pubkey()).unwrap().as_deref(), Some("devnet"));
+
+        // Running it again finds nothing new
+        assert!(import_solana_cli_keypairs_from(solana_dir.path()).unwrap().is_empty());
+        assert!(import_solana_cli_keypairs_from(&solana_dir.path().join("missing")).is_err());
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_import_gzip_compressed_key_files() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+        let scan_dir = tempdir().unwrap();
+        let keypair = Keypair::new();
+        let gz_path = scan_dir.path().join("backup.json.gz");
+        key_validator::test_fixtures::write_gzip(&gz_path, serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap().as_bytes());
+
+        // Found by a directory import and named without the .json.gz extension
+        let results = import_wallets_from_dir(
+            scan_dir.path().to_str().unwrap(),
+            "gz_",
+            &SearchConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].1.is_ok());
+        assert_eq!(find_wallet_by_pubkey(&keypair.pubkey()).unwrap().as_deref(), Some("gz_backup"));
+        let stored = get_wallet_keypair("gz_backup").unwrap().unwrap();
+        assert_eq!(stored.pubkey(), keypair.pubkey());
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_import_wallets_from_dir_with_mixed_files() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+        let scan_dir = tempdir().unwrap();
+
+        let fresh = Keypair::new();
+        let known = Keypair::new();
+        fs::write(scan_dir.path().join("fresh.json"), serde_json::to_string(&fresh.to_bytes().to_vec()).unwrap()).unwrap();
+        fs::write(scan_dir.path().join("known.json"), serde_json::to_string(&known.to_bytes().to_vec()).unwrap()).unwrap();
+        fs::write(scan_dir.path().join("package.json"), r#"{"name": "not-a-wallet"}"#).unwrap();
+        fs::write(scan_dir.path().join("short.json"), "[1, 2, 3]").unwrap();
+
+        // A key that is already stored is skipped rather than stored twice
+        let known_file = create_dummy_key_file(&temp_dir, "known_src.json", Some(&known));
+        add_wallet_from_file("already_here", &known_file).unwrap();
+
+        let results = import_wallets_from_dir(
+            scan_dir.path().to_str().unwrap(),
+            "",
+            &SearchConfig::default(),
+        )
+        .unwrap();
+
+        // Only key files are reported; other JSON files are not candidates
+        assert_eq!(results.len(), 2);
+        let result_for = |stem: &str| {
+            results
+                .iter()
+                .find(|(path, _)| path.ends_with(&format!("{}.json", stem)))
+                .map(|(_, result)| result.clone())
+                .unwrap()
+        };
+        assert_eq!(result_for("fresh"), Ok(()));
+        assert!(result_for("known").unwrap_err().contains("already_here"));
+
+        let mut names = secure_storage::list_wallet_names().unwrap();
+        names.sort();
+        assert_eq!(names, vec!["already_here".to_string(), "fresh".to_string()]);
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_unique_wallet_name() {
+        let taken: HashSet<String> = ["w".to_string(), "w_2".to_string()].into_iter().collect();
+        assert_eq!(unique_wallet_name("v", &taken), "v");
+        assert_eq!(unique_wallet_name("w", &taken), "w_3");
+    }
+
     #[test]
     fn test_add_wallet_invalid_file_path() {
-        let (temp_dir, test_service_name) = setup_test_env();
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
         assert!(add_wallet_from_file("w1", "non_existent_file.json").is_err());
         teardown_test_env(temp_dir, &test_service_name);
     }
 
     #[test]
     fn test_add_wallet_not_solana_key_file() {
-        let (temp_dir, test_service_name) = setup_test_env();
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
         let not_a_key_file_path = temp_dir.path().join("not_a_key.json");
         let mut file = File::create(&not_a_key_file_path).unwrap();
         writeln!(file, "{{\"message\": \"this is not a key\"}}").unwrap();
@@ -375,8 +1055,381 @@ mod tests {
 
     #[test]
     fn test_remove_non_existent_wallet() {
-        let (temp_dir, test_service_name) = setup_test_env();
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
         assert!(remove_wallet("ghost_wallet").is_err());
         teardown_test_env(temp_dir, &test_service_name);
     }
+
+    #[test]
+    fn test_rename_wallet() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+        let keypair = Keypair::new();
+        let key_file_path = create_dummy_key_file(&temp_dir, "rename.json", Some(&keypair));
+        add_wallet_from_file("before_rename", &key_file_path).unwrap();
+
+        assert!(rename_wallet("before_rename", "after_rename").is_ok());
+
+        let names = secure_storage::list_wallet_names().unwrap();
+        assert_eq!(names, vec!["after_rename".to_string()]);
+        let renamed = get_wallet_keypair("after_rename").unwrap().unwrap();
+        assert_eq!(renamed.pubkey(), keypair.pubkey());
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_rename_wallet_to_existing_name() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+        let keypair1 = Keypair::new();
+        let keypair2 = Keypair::new();
+        let key_file1_path = create_dummy_key_file(&temp_dir, "first.json", Some(&keypair1));
+        let key_file2_path = create_dummy_key_file(&temp_dir, "second.json", Some(&keypair2));
+        add_wallet_from_file("first", &key_file1_path).unwrap();
+        add_wallet_from_file("second", &key_file2_path).unwrap();
+
+        let err = rename_wallet("first", "second").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+        // Both wallets keep their original keys
+        let first = get_wallet_keypair("first").unwrap().unwrap();
+        let second = get_wallet_keypair("second").unwrap().unwrap();
+        assert_eq!(first.pubkey(), keypair1.pubkey());
+        assert_eq!(second.pubkey(), keypair2.pubkey());
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_rename_non_existent_wallet() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+        let err = rename_wallet("ghost_wallet", "new_ghost").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_add_wallets_with_same_name_keeps_both() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+        fs::create_dir_all(temp_dir.path().join("a")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("b")).unwrap();
+        let first = Keypair::new();
+        let second = Keypair::new();
+        let first_file = create_dummy_key_file(&temp_dir, "a/id.json", Some(&first));
+        let second_file = create_dummy_key_file(&temp_dir, "b/id.json", Some(&second));
+
+        assert_eq!(add_wallet_from_file("id", &first_file).unwrap(), "id");
+        assert_eq!(add_wallet_from_file("id", &second_file).unwrap(), "id_2");
+
+        let mut names = secure_storage::list_wallet_names().unwrap();
+        names.sort();
+        assert_eq!(names, vec!["id".to_string(), "id_2".to_string()]);
+        assert_eq!(get_wallet_keypair("id").unwrap().unwrap().pubkey(), first.pubkey());
+        assert_eq!(get_wallet_keypair("id_2").unwrap().unwrap().pubkey(), second.pubkey());
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_add_same_key_twice_is_rejected() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+        let keypair = Keypair::new();
+        let key_file_path = create_dummy_key_file(&temp_dir, "dup.json", Some(&keypair));
+        add_wallet_from_file("original", &key_file_path).unwrap();
+
+        assert_eq!(
+            find_wallet_by_pubkey(&keypair.pubkey()).unwrap(),
+            Some("original".to_string())
+        );
+        assert_eq!(find_wallet_by_pubkey(&Keypair::new().pubkey()).unwrap(), None);
+
+        let err = add_wallet_from_file("duplicate", &key_file_path).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        assert!(err.to_string().contains("original"));
+
+        let names = secure_storage::list_wallet_names().unwrap();
+        assert_eq!(names, vec!["original".to_string()]);
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_add_wallet_from_secret_key_file() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+        let keypair = Keypair::new();
+        let full_file = temp_dir.path().join("full.json");
+        let secret_file = temp_dir.path().join("secret.json");
+        fs::write(&full_file, serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap()).unwrap();
+        fs::write(
+            &secret_file,
+            serde_json::to_string(&keypair.to_bytes()[..SECRET_KEY_BYTES]).unwrap(),
+        )
+        .unwrap();
+
+        // Both files hold the same wallet
+        let full_pubkey = validate_wallet_file(full_file.to_str().unwrap()).unwrap();
+        let secret_pubkey = validate_wallet_file(secret_file.to_str().unwrap()).unwrap();
+        assert_eq!(full_pubkey, keypair.pubkey());
+        assert_eq!(secret_pubkey, keypair.pubkey());
+
+        add_wallet_from_file("from_secret", secret_file.to_str().unwrap()).unwrap();
+        let stored = get_wallet_keypair("from_secret").unwrap().unwrap();
+        assert_eq!(stored.to_bytes(), keypair.to_bytes());
+
+        // The full keypair is then recognised as the same wallet
+        let err = add_wallet_from_file("from_full", full_file.to_str().unwrap()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_add_wallet_from_reader() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+        let keypair = Keypair::new();
+        let key_json = serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap();
+
+        let name = add_wallet_from_reader("piped", key_json.as_bytes()).unwrap();
+        assert_eq!(name, "piped");
+        let stored = get_wallet_keypair("piped").unwrap().unwrap();
+        assert_eq!(stored.pubkey(), keypair.pubkey());
+
+        let err = add_wallet_from_reader("garbage", &b"[1, 2, 3]"[..]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains("array of 3 numbers"));
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_add_wallet_from_keypair() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+        let keypair = Keypair::new();
+        add_wallet_from_keypair("generated", &keypair).unwrap();
+
+        let stored = get_wallet_keypair("generated").unwrap().unwrap();
+        assert_eq!(stored.pubkey(), keypair.pubkey());
+
+        let err = add_wallet_from_keypair("again", &keypair).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+        // A different key under a taken name leaves the stored one alone
+        let err = add_wallet_from_keypair("generated", &Keypair::new()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        assert_eq!(get_wallet_pubkey("generated").unwrap(), Some(keypair.pubkey()));
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_cached_pubkey_matches_derived_one() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+        let keypair = Keypair::new();
+        add_wallet_from_keypair("cached", &keypair).unwrap();
+
+        // The record carries the pubkey, and it is the one the secret key derives
+        let record = secure_storage::retrieve_wallet_record("cached").unwrap().unwrap();
+        let derived = keypair_from_bytes(&record.key_bytes).unwrap().pubkey();
+        assert_eq!(record.pubkey, Some(derived.to_string()));
+        assert_eq!(derived, keypair.pubkey());
+        assert_eq!(get_wallet_pubkey("cached").unwrap(), Some(derived));
+        assert_eq!(get_wallet_pubkey("missing").unwrap(), None);
+
+        // A record without the cache still resolves, by deriving
+        let mut uncached = record.clone();
+        uncached.pubkey = None;
+        assert_eq!(record_pubkey(&uncached).unwrap(), derived);
+
+        // A corrupt public half is never taken for the wallet's address
+        let mut corrupt_bytes = keypair.to_bytes();
+        corrupt_bytes[SECRET_KEY_BYTES..].copy_from_slice(&Keypair::new().pubkey().to_bytes());
+        let corrupt = secure_storage::WalletRecord::new(corrupt_bytes.to_vec());
+        assert_eq!(corrupt.pubkey, Some(derived.to_string()));
+        let mut corrupt_uncached = corrupt.clone();
+        corrupt_uncached.pubkey = None;
+        assert_eq!(record_pubkey(&corrupt_uncached).unwrap(), derived);
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_add_wallet_from_bytes_rejects_mismatched_public_key() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+        let mut key_bytes = Keypair::new().to_bytes();
+        key_bytes[SECRET_KEY_BYTES..].copy_from_slice(&Keypair::new().pubkey().to_bytes());
+
+        let err = add_wallet_from_bytes("mismatched", &key_bytes).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(secure_storage::list_wallet_names().unwrap().is_empty());
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_export_wallet_to_file() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+        let keypair = Keypair::new();
+        let key_file_path = create_dummy_key_file(&temp_dir, "source.json", Some(&keypair));
+        add_wallet_from_file("exported", &key_file_path).unwrap();
+
+        let dest = temp_dir.path().join("exported.json");
+        let dest_str = dest.to_str().unwrap();
+        export_wallet_to_file("exported", dest_str, false).unwrap();
+
+        assert!(key_validator::is_solana_wallet_json_file(dest_str).unwrap());
+        let exported: Vec<u8> = serde_json::from_str(&fs::read_to_string(&dest).unwrap()).unwrap();
+        assert_eq!(exported, keypair.to_bytes().to_vec());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&dest).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // Existing files are only replaced when asked to
+        let err = export_wallet_to_file("exported", dest_str, false).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        assert!(export_wallet_to_file("exported", dest_str, true).is_ok());
+
+        let missing = export_wallet_to_file("ghost_wallet", dest_str, true).unwrap_err();
+        assert_eq!(missing.kind(), ErrorKind::NotFound);
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    // Published BIP44 example from the Solana web3.js documentation
+    const TEST_MNEMONIC: &str =
+        "neither lonely flavor argue grass remind eye tag avocado spot unusual intact";
+
+    #[test]
+    fn test_add_wallets_from_seed() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+
+        let created = add_wallets_from_seed("seed", TEST_MNEMONIC, 2, None).unwrap();
+        assert_eq!(created, vec!["seed_0".to_string(), "seed_1".to_string()]);
+
+        let first = get_wallet_keypair("seed_0").unwrap().unwrap();
+        let second = get_wallet_keypair("seed_1").unwrap().unwrap();
+        assert_eq!(
+            first.pubkey().to_string(),
+            "5vftMkHL72JaJG6ExQfGAsT2uGVHpRR7oTNUPMs68Y2N"
+        );
+        assert_eq!(
+            second.pubkey().to_string(),
+            "GcXbfQ5yY3uxCyBNDPBbR5FjumHf89E7YHXuULfGDBBv"
+        );
+
+        // Deriving again only adds the accounts that are not stored yet
+        let created = add_wallets_from_seed("more", TEST_MNEMONIC, 3, None).unwrap();
+        assert_eq!(created, vec!["more_2".to_string()]);
+
+        let err = add_wallets_from_seed("seed", "not a valid mnemonic", 1, None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_add_wallets_from_seed_with_derivation_paths() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+
+        // The explicit default path matches the published web3.js example
+        let created = add_wallets_from_seed("change", TEST_MNEMONIC, 1, Some("m/44'/501'/0'/0'")).unwrap();
+        assert_eq!(created, vec!["change_0".to_string()]);
+        let with_change = get_wallet_keypair("change_0").unwrap().unwrap();
+        assert_eq!(
+            with_change.pubkey().to_string(),
+            "5vftMkHL72JaJG6ExQfGAsT2uGVHpRR7oTNUPMs68Y2N"
+        );
+
+        // Without the change level the same account is a different key
+        let created = add_wallets_from_seed("nochange", TEST_MNEMONIC, 2, Some("m/44'/501'/0'")).unwrap();
+        assert_eq!(created, vec!["nochange_0".to_string(), "nochange_1".to_string()]);
+        let seed = generate_seed_from_seed_phrase_and_passphrase(TEST_MNEMONIC, "");
+        for account in 0..2 {
+            let stored = get_wallet_keypair(&format!("nochange_{}", account)).unwrap().unwrap();
+            let expected = keypair_from_seed_and_derivation_path(
+                &seed,
+                Some(DerivationPath::new_bip44(Some(account), None)),
+            )
+            .unwrap();
+            assert_eq!(stored.pubkey(), expected.pubkey());
+            assert_ne!(stored.pubkey(), with_change.pubkey());
+        }
+
+        // Later accounts start from the path's account index
+        let created = add_wallets_from_seed("later", TEST_MNEMONIC, 1, Some("m/44'/501'/1'/0'")).unwrap();
+        assert_eq!(created, vec!["later_1".to_string()]);
+        assert_eq!(
+            get_wallet_keypair("later_1").unwrap().unwrap().pubkey().to_string(),
+            "GcXbfQ5yY3uxCyBNDPBbR5FjumHf89E7YHXuULfGDBBv"
+        );
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
+
+    #[test]
+    fn test_seed_account_pubkey() {
+        // Matches what importing the same account stores, without storing anything
+        assert_eq!(
+            seed_account_pubkey(TEST_MNEMONIC, 0).unwrap().to_string(),
+            "5vftMkHL72JaJG6ExQfGAsT2uGVHpRR7oTNUPMs68Y2N"
+        );
+        assert_eq!(
+            seed_account_pubkey(&format!("  {}\n", TEST_MNEMONIC), 1).unwrap().to_string(),
+            "GcXbfQ5yY3uxCyBNDPBbR5FjumHf89E7YHXuULfGDBBv"
+        );
+        assert_eq!(parse_derivation_path(&seed_account_derivation_path(3)).unwrap(), (3, Some(0)));
+
+        let err = seed_account_pubkey("not a valid mnemonic", 0).unwrap_err();
+        assert!(matches!(err, WalletManagerError::InvalidInput(_)));
+        let err = seed_account_pubkey(TEST_MNEMONIC, HARDENED_OFFSET).unwrap_err();
+        assert!(matches!(err, WalletManagerError::InvalidInput(_)));
+    }
+
+    #[test]
+    fn test_parse_derivation_path() {
+        assert_eq!(parse_derivation_path("m/44'/501'/0'/0'").unwrap(), (0, Some(0)));
+        assert_eq!(parse_derivation_path("m/44'/501'/3'").unwrap(), (3, None));
+        // Unmarked levels are hardened too
+        assert_eq!(parse_derivation_path("m/44/501/2/1").unwrap(), (2, Some(1)));
+
+        for path in ["m/44'/60'/0'/0'", "m/44'/501'", "m/44'/501'/0'/0'/0'", "44'/501'/0'", "nonsense"] {
+            let err = parse_derivation_path(path).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidInput, "{} should be rejected", path);
+        }
+    }
+
+    #[test]
+    fn test_find_duplicate_wallets() {
+        let (temp_dir, test_service_name, _env_guard) = setup_test_env();
+        assert!(find_duplicate_wallets().unwrap().is_empty());
+
+        // Older versions stored the same key again under a new name
+        let repeated = Keypair::new();
+        let unique = Keypair::new();
+        add_wallet_from_keypair("original", &repeated).unwrap();
+        add_wallet_from_keypair("unique", &unique).unwrap();
+        secure_storage::store_private_key("copy_b", &repeated.to_bytes()).unwrap();
+        secure_storage::store_private_key("copy_a", &repeated.to_bytes()).unwrap();
+
+        let duplicates = find_duplicate_wallets().unwrap();
+        assert_eq!(duplicates.len(), 1);
+        let (pubkey, names) = &duplicates[0];
+        assert_eq!(*pubkey, repeated.pubkey());
+        // Oldest first, in the order they were added even within one second,
+        // not by name
+        assert_eq!(names, &vec!["original", "copy_b", "copy_a"]);
+        // Lookups by key land on the same name duplicates would keep
+        assert_eq!(find_wallet_by_pubkey(&repeated.pubkey()).unwrap().as_deref(), Some("original"));
+
+        // Removing all but one name leaves no duplicates
+        for name in &names[1..] {
+            remove_wallet(name).unwrap();
+        }
+        assert!(find_duplicate_wallets().unwrap().is_empty());
+        assert!(get_wallet_keypair("unique").unwrap().is_some());
+
+        teardown_test_env(temp_dir, &test_service_name);
+    }
 }
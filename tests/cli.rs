@@ -0,0 +1,162 @@
+use assert_cmd::Command;
+use solana_sdk::signer::{keypair::Keypair, Signer};
+use tempfile::tempdir;
+
+// Runs the binary against an isolated vault location
+fn svmai(config_dir: &tempfile::TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("svmai").unwrap();
+    cmd.env(
+        "SVMAI_TEST_CONFIG_PATH",
+        config_dir.path().join("wallets.json"),
+    )
+    .env("SVMAI_TEST_SERVICE_NAME", "svmai_cli_integration_test");
+    cmd
+}
+
+#[test]
+fn list_on_empty_vault_prints_nothing() {
+    let config_dir = tempdir().unwrap();
+    svmai(&config_dir).arg("list").assert().success().stdout("");
+}
+
+#[test]
+fn import_solana_cli_without_solana_dir_fails() {
+    let config_dir = tempdir().unwrap();
+    let home = tempdir().unwrap();
+    svmai(&config_dir)
+        .env("HOME", home.path())
+        .arg("import-solana-cli")
+        .assert()
+        .failure()
+        .stdout("");
+}
+
+#[test]
+fn check_prints_pubkey_without_storing() {
+    let config_dir = tempdir().unwrap();
+    let keypair = Keypair::new();
+    let key_file = config_dir.path().join("id.json");
+    std::fs::write(&key_file, format!("{:?}", keypair.to_bytes())).unwrap();
+
+    svmai(&config_dir)
+        .arg("check")
+        .arg(&key_file)
+        .assert()
+        .success()
+        .stdout(format!("{}\n", keypair.pubkey()));
+    assert!(!config_dir.path().join("wallets.json").exists());
+
+    std::fs::write(&key_file, "[1, 2, 3]").unwrap();
+    svmai(&config_dir)
+        .arg("check")
+        .arg(&key_file)
+        .assert()
+        .failure()
+        .stdout("");
+}
+
+#[test]
+fn add_from_stdin_rejects_invalid_key() {
+    let config_dir = tempdir().unwrap();
+    svmai(&config_dir)
+        .args(["add", "piped", "-"])
+        .write_stdin("[1, 2, 3]")
+        .assert()
+        .failure()
+        .stdout("");
+    assert!(!config_dir.path().join("wallets.json").exists());
+}
+
+#[test]
+fn remove_missing_wallet_fails() {
+    let config_dir = tempdir().unwrap();
+    svmai(&config_dir)
+        .args(["remove", "ghost_wallet"])
+        .assert()
+        .failure()
+        .stdout("");
+}
+
+#[test]
+fn sign_with_missing_wallet_fails() {
+    let config_dir = tempdir().unwrap();
+    svmai(&config_dir)
+        .args(["sign", "ghost_wallet", "hello"])
+        .assert()
+        .failure()
+        .stdout("");
+}
+
+#[test]
+fn dedupe_on_empty_vault_prints_nothing() {
+    let config_dir = tempdir().unwrap();
+    svmai(&config_dir)
+        .args(["dedupe", "--apply"])
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn batch_with_missing_wallet_writes_no_log() {
+    let config_dir = tempdir().unwrap();
+    let out = config_dir.path().join("batch.log");
+    svmai(&config_dir)
+        .args(["batch", "ghost_wallet", "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin=1", "--out"])
+        .arg(&out)
+        .assert()
+        .failure()
+        .stdout("");
+    assert!(!out.exists());
+}
+
+#[test]
+fn ata_rejects_invalid_mint() {
+    let config_dir = tempdir().unwrap();
+    svmai(&config_dir)
+        .args(["ata", "any_wallet", "not_a_mint"])
+        .assert()
+        .failure()
+        .stdout("");
+}
+
+#[test]
+fn airdrop_refuses_mainnet() {
+    let config_dir = tempdir().unwrap();
+    svmai(&config_dir)
+        .args(["airdrop", "any_wallet", "1", "--url", "https://api.mainnet-beta.solana.com"])
+        .assert()
+        .failure()
+        .stdout("");
+}
+
+#[test]
+fn export_addresses_on_empty_vault_writes_empty_list() {
+    let config_dir = tempdir().unwrap();
+    let dest = config_dir.path().join("addresses.json");
+    svmai(&config_dir)
+        .arg("export-addresses")
+        .arg(&dest)
+        .assert()
+        .success();
+    let content = std::fs::read_to_string(&dest).unwrap();
+    assert_eq!(content.trim(), "[]");
+}
+
+#[test]
+fn bash_completions_mention_subcommands() {
+    let config_dir = tempdir().unwrap();
+    let output = svmai(&config_dir)
+        .args(["completions", "bash"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let script = String::from_utf8(output).unwrap();
+    assert!(!script.is_empty());
+    for subcommand in ["add", "list", "remove", "vanity"] {
+        assert!(script.contains(subcommand), "missing {}", subcommand);
+    }
+}